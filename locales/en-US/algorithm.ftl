@@ -9,6 +9,14 @@ algorithm_effect_exfil_instance = {-algorithm_effect_exfil_name} {$potency}
 
 -algorithm_effect_modify_name = Modify
 algorithm_effect_modify_instance = {-algorithm_effect_modify_name} {$target}'s {$stat} by {$potency}
+algorithm_effect_modify_timed_instance = {-algorithm_effect_modify_name} {$target}'s {$stat} by {$potency} for {NUMBER($seconds, maximumFractionDigits: 1)}s
 
 -algorithm_effect_purge_name = Purge
 algorithm_effect_purge_instance = {-algorithm_effect_purge_name} {$target}'s {$stat} by {$potency}
+algorithm_effect_purge_all_instance = {-algorithm_effect_purge_name} all of {$target}'s stats by {$potency}
+
+algorithm_rarity_common = Common
+algorithm_rarity_uncommon = Uncommon
+algorithm_rarity_rare = Rare
+algorithm_rarity_epic = Epic
+algorithm_rarity_legendary = Legendary
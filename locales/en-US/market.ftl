@@ -1,2 +1,13 @@
 market_unlock_already_unlocked = Already unlocked
 market_unlock_credit_cost = {$credit_cost} credits
+market_servers_header = Servers
+market_server_purchase = Buy Server
+market_server_upgrade_clock_speed = +{$clock_speed}
+market_server_upgrade_threads = +1 Thread
+market_server_upgrade_connection_strength = +1 Connection Strength
+market_server_upgrade_stealth = +1 Stealth
+market_algorithms_header = Algorithms
+market_algorithm_restock = New stock in {$minutes}m {$seconds}s
+market_algorithm_sold_out = Sold out
+market_algorithm_sell_header = Sell Algorithms
+market_algorithm_sell = Sell for {$credit_value} credits
@@ -4,6 +4,7 @@ ui_confirmation_next = Next
 ui_confirmation_back = Back
 ui_confirmation_create = Create
 ui_confirmation_stop = Stop
+ui_confirmation_dismiss = Dismiss
 
 ui_menu_sidebar_market_tab = Market
 ui_menu_sidebar_servers_section = Servers
@@ -14,8 +15,22 @@ ui_menu_sidebar_exploit_tab = Exploit
 ui_menu_sidebar_glossary_tab = Glossary
 
 ui_window_tutorial_title = Tutorial
+ui_window_offline_summary_title = While You Were Away
+
+ui_offline_summary_elapsed = Your exploits kept running for {$hours}h {$minutes}m.
+ui_offline_summary_credits = Credits siphoned: {$credits}
+ui_offline_summary_algorithms = Algorithms exfiltrated: {$algorithm_count}
+ui_offline_summary_algorithms_lost = Algorithms lost: {$algorithm_count}
 
 ui_server_thread_count = Threads: {$thread_count}
+ui_server_underclocked = Underclocked to {$clock_speed}
+ui_rack_power = Power: {NUMBER($power_draw, maximumFractionDigits: 0)} / {$power_capacity} W
+ui_rack_bandwidth = Bandwidth: {$bandwidth} Mbps ({$bandwidth_per_server} Mbps per server)
+ui_server_active_exploits_header = Active Exploits
+ui_server_exploit_weight = Weight
+ui_server_exploit_pin = Pin
+ui_server_defensive_script = Defense
+ui_server_defensive_script_none = None
 ui_server_clock_speed = { $unit ->
     [ghz] {NUMBER($clock_speed, minimumFractionDigits: 4)} GHz
     [mhz] {NUMBER($clock_speed, minimumFractionDigits: 4)} MHz
@@ -24,7 +39,16 @@ ui_server_clock_speed = { $unit ->
 }
 
 ui_algorithm_instruction_count = Instruction Count: {$instruction_count}
+ui_algorithm_effect_checkpoint = At instruction {$instruction}:
 ui_algorithm_algorithms_header = Algorithms
 ui_algorithm_effects_header = Effects
 ui_algorithm_procedure_header = Procedure
 ui_algorithm_scripts_header = Scripts
+
+ui_script_add_procedure = Add Procedure
+ui_script_remove_procedure = Remove
+ui_script_threads_required = Threads required: {$thread_count}
+ui_script_node_follows = after {$nodes}
+ui_script_branch_from = Branch
+
+ui_exploit_not_enough_threads = This script needs {$threads_required} threads, but the server only has {$thread_count} free.
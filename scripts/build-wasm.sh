@@ -6,7 +6,7 @@ OUT_DIR="$WORKING_DIR/wasm_out"
 OUT_NAME="serverlooter"
 CARGO_BUILD_PROFILE="wasm"
 
-cargo build --profile $CARGO_BUILD_PROFILE --target wasm32-unknown-unknown
+cargo build --profile $CARGO_BUILD_PROFILE --target wasm32-unknown-unknown --bin serverlooter
 if [ -d "$OUT_DIR" ]; then rm -rf $OUT_DIR; fi
 mkdir $OUT_DIR
 wasm-bindgen --target web \
@@ -1,34 +1,114 @@
 use std::sync::{Arc, Mutex};
-use bevy::prelude::Commands;
 use uuid::Uuid;
+use crate::algorithm::effect::AlgorithmEffect;
 use crate::algorithm::effect::application::AlgorithmEffectApplication;
-use crate::event::exploit_started::ExploitStarted;
 use crate::executor::Executor;
+use crate::rng::{random_uuid, SimulationRng};
 use crate::script::executor::ScriptExecutor;
 use crate::script::Script;
-use crate::server::Server;
+use crate::server::{Server, ServerStatType, ServerStats};
+use crate::simulation::event::SimulationEvent;
+use crate::target::tier::DifficultyTier;
 use crate::ui::clock_speed::ClockSpeed;
+use crate::TICKS_PER_SECOND;
+
+/// How much connection health each point of [ConnectionStrength](ServerStatType::ConnectionStrength) is worth.
+const HEALTH_PER_CONNECTION_STRENGTH: i64 = 5;
+
+/// How many ticks an exploit that lost its connection waits before it can start reconnecting,
+/// before the target's connection strength and the host's stealth are taken into account.
+pub(crate) const BASE_RECONNECT_DELAY: i64 = 10;
+
+/// How many seconds a target takes to refill its credit pool from empty.
+const CREDIT_POOL_REFILL_SECONDS: u64 = 300;
 
 pub struct ExploitTarget {
     pub server: Arc<Mutex<Server>>,
     pub script: Arc<Mutex<Script>>,
 
+    /// How much connection health exploits against this target have.
+    pub connection_health: u32,
+
+    /// How many credits are left to siphon from this target.
+    pub credits: u64,
+
+    /// How many credits this target holds once its pool has refilled.
+    pub max_credits: u64,
+
+    pub tier: DifficultyTier,
+
+    /// The asset path of the [TargetDefinition](crate::target::definition::TargetDefinition)
+    /// this target was built from, if any.
+    pub definition: Option<String>,
+
     pub id: Uuid,
     script_executor: ScriptExecutor,
+
+    /// Ticks since the credit pool last refilled.
+    credit_regen_ticks: u32,
 }
 
 impl ExploitTarget {
-    pub fn new(server: Arc<Mutex<Server>>, script: Arc<Mutex<Script>>) -> ExploitTarget {
-        let id = Uuid::new_v4();
+    pub fn new(
+        id: Uuid,
+        server: Arc<Mutex<Server>>,
+        script: Arc<Mutex<Script>>,
+        connection_health: u32,
+        max_credits: u64,
+    ) -> ExploitTarget {
         let script_executor = ScriptExecutor::from_arc(&script);
 
         ExploitTarget {
             server,
             script,
+            connection_health,
+            credits: max_credits,
+            max_credits,
+            tier: DifficultyTier::default(),
+            definition: None,
             id,
             script_executor,
+            credit_regen_ticks: 0,
+        }
+    }
+
+    /// Replaces everything about this target except its identity.
+    /// Any stat modifications on the old server are lost, and the defensive script starts over.
+    /// Credits already siphoned stay siphoned.
+    pub fn redefine(&mut self, server: Arc<Mutex<Server>>, script: Arc<Mutex<Script>>, connection_health: u32, max_credits: u64) {
+        self.script_executor = ScriptExecutor::from_arc(&script);
+        self.script_executor.start_execution();
+        self.server = server;
+        self.script = script;
+        self.connection_health = connection_health;
+        self.credits = self.credits.min(max_credits);
+        self.max_credits = max_credits;
+    }
+
+    /// Takes up to `credits` out of the credit pool, returning how many were actually there to take.
+    pub fn siphon_credits(&mut self, credits: u64) -> u64 {
+        let siphoned = credits.min(self.credits);
+        self.credits -= siphoned;
+
+        siphoned
+    }
+
+    /// Refills the credit pool by `ticks` worth of regeneration.
+    /// The pool refills a share of its max every second, so richer targets recover more credits.
+    pub fn regenerate_credits(&mut self, ticks: u32) {
+        self.credit_regen_ticks += ticks;
+        while self.credit_regen_ticks >= TICKS_PER_SECOND as u32 {
+            self.credit_regen_ticks -= TICKS_PER_SECOND as u32;
+            let regen_per_second = self.max_credits.div_ceil(CREDIT_POOL_REFILL_SECONDS);
+            self.credits = self.credits.saturating_add(regen_per_second).min(self.max_credits);
         }
     }
+
+    /// Throws away the defensive script's progress, so it has to start its current run over.
+    pub fn interrupt(&mut self) {
+        self.script_executor = ScriptExecutor::from_arc(&self.script);
+        self.script_executor.start_execution();
+    }
 }
 
 #[derive(Clone)]
@@ -42,7 +122,16 @@ pub struct ActiveExploit {
 
     pub auto_reconnect: Arc<Mutex<bool>>,
     pub has_connected: Arc<Mutex<bool>>,
+
+    /// Ticks left before a reconnecting exploit starts building up its connection again.
+    pub reconnect_delay: Arc<Mutex<u32>>,
     pub id: Uuid,
+
+    /// Ticks spent running since the connection last regenerated.
+    regen_ticks: Arc<Mutex<u32>>,
+
+    /// Every roll made on behalf of this exploit, by either side, is drawn from this substream.
+    pub rng: Arc<Mutex<SimulationRng>>,
     script_executor: Arc<Mutex<ScriptExecutor>>,
 }
 
@@ -60,8 +149,9 @@ impl ActiveExploit {
         hosting_server: Arc<Mutex<Server>>,
         clock_allocation: ClockSpeed,
         auto_reconnect: bool,
+        mut rng: SimulationRng,
     ) -> ActiveExploit {
-        let id = Uuid::new_v4();
+        let id = random_uuid(&mut rng);
 
         let mut new_exploit = ActiveExploit {
             target,
@@ -73,7 +163,10 @@ impl ActiveExploit {
             connection_current_health: Arc::default(),
             script_executor: Arc::default(),
             has_connected: Arc::default(),
+            reconnect_delay: Arc::default(),
+            regen_ticks: Arc::default(),
             auto_reconnect: Arc::new(Mutex::new(auto_reconnect)),
+            rng: Arc::new(Mutex::new(rng)),
         };
 
         new_exploit.restart();
@@ -85,13 +178,119 @@ impl ActiveExploit {
     }
 
     pub fn restart(&mut self) {
-        // ZJ-TODO: this should be passed in from the server
-        self.connection_max_health = Arc::new(Mutex::new(50));
+        self.clear_modifications();
+
+        // Only exploits the target kicked out have to wait; brand new and manually restarted ones start connecting straight away
+        let was_disconnected = matches!(self.status(), ActiveExploitStatus::Disconnected);
+        let reconnect_delay = if was_disconnected { self.reconnect_delay_for() } else { 0 };
+        self.reconnect_delay = Arc::new(Mutex::new(reconnect_delay));
+        self.connection_max_health = Arc::new(Mutex::new(self.max_health_for()));
         self.connection_current_health = Arc::new(Mutex::new(0));
         self.script_executor = Arc::new(Mutex::new(ScriptExecutor::from_arc(&self.script)));
+        self.regen_ticks = Arc::default();
         self.has_connected = Arc::new(Mutex::new(false));
     }
 
+    /// Restores the connection state of an exploit loaded from a save.
+    /// Script progress isn't saved, so connected exploits resume from the start of their scripts.
+    pub fn restore_connection(&mut self, max_health: u32, current_health: u32, has_connected: bool) {
+        *self.connection_max_health.lock().unwrap() = max_health;
+        *self.connection_current_health.lock().unwrap() = current_health;
+        *self.has_connected.lock().unwrap() = has_connected;
+
+        if has_connected {
+            self.target.lock().unwrap().script_executor.start_execution();
+            if current_health > 0 {
+                self.start_execution();
+            }
+        }
+    }
+
+    /// Removes every stat modification this exploit's scripts made, on both the host and the target.
+    pub fn clear_modifications(&self) {
+        self.hosting_server.lock().unwrap().stats.remove_from_exploit(self.id);
+        self.target.lock().unwrap().server.lock().unwrap().stats.remove_from_exploit(self.id);
+    }
+
+    /// Reads `stat` from the host and target servers.
+    fn stat_values(&self, stat: ServerStatType) -> (i64, i64) {
+        let host_value = self.hosting_server.lock().unwrap().stats.value_of(stat.clone());
+        let target_value = self.target.lock().unwrap().server.lock().unwrap().stats.value_of(stat);
+
+        (host_value as i64, target_value as i64)
+    }
+
+    /// How much connection health this exploit has once connected.
+    /// The host's connection strength adds to the target's base connection health, and the target's takes away from it.
+    fn max_health_for(&self) -> u32 {
+        let connection_health = self.target.lock().unwrap().connection_health as i64;
+        let (host_strength, target_strength) = self.stat_values(ServerStatType::ConnectionStrength);
+        let max_health = connection_health + (host_strength - target_strength) * HEALTH_PER_CONNECTION_STRENGTH;
+
+        max_health.clamp(1, u32::MAX as i64) as u32
+    }
+
+    /// How stealthy the host is once the target's detection has been taken into account.
+    fn stealth_for(&self) -> i64 {
+        let (host_stealth, _) = self.stat_values(ServerStatType::Stealth);
+        let (_, target_detection) = self.stat_values(ServerStatType::Detection);
+
+        host_stealth - target_detection
+    }
+
+    /// How much connection health builds up each tick while connecting.
+    fn connect_rate_for(&self) -> u32 {
+        (1 + self.stealth_for()).clamp(1, u32::MAX as i64) as u32
+    }
+
+    /// How many ticks this exploit waits before reconnecting after losing its connection.
+    fn reconnect_delay_for(&self) -> u32 {
+        let (_, target_strength) = self.stat_values(ServerStatType::ConnectionStrength);
+
+        (BASE_RECONNECT_DELAY + target_strength - self.stealth_for()).clamp(0, u32::MAX as i64) as u32
+    }
+
+    /// Recovers the host's [ConnectionRegen](ServerStatType::ConnectionRegen) worth of connection health
+    /// for every second the exploit spends running over `ticks`, up to its max health.
+    fn regenerate(&self, ticks: u32) {
+        if !matches!(self.status(), ActiveExploitStatus::Running) {
+            return;
+        }
+
+        let mut regen_ticks = self.regen_ticks.lock().unwrap();
+        *regen_ticks += ticks;
+        let seconds = *regen_ticks / TICKS_PER_SECOND as u32;
+        if seconds == 0 {
+            return;
+        }
+        *regen_ticks %= TICKS_PER_SECOND as u32;
+
+        let (host_regen, _) = self.stat_values(ServerStatType::ConnectionRegen);
+        let max_health = *self.connection_max_health.lock().unwrap();
+        let mut current_health = self.connection_current_health.lock().unwrap();
+        *current_health = (*current_health as i64 + host_regen.max(0) * seconds as i64).min(max_health as i64) as u32;
+    }
+
+    /// Keeps the connection's max health in step with stats that changed since connecting.
+    /// A live connection gains (or loses) health along with its max, so buffs help straight away.
+    fn update_max_health(&self) {
+        let new_max_health = self.max_health_for();
+        let mut max_health = self.connection_max_health.lock().unwrap();
+        let mut current_health = self.connection_current_health.lock().unwrap();
+
+        if *self.has_connected.lock().unwrap() && *current_health > 0 {
+            *current_health = if new_max_health > *max_health {
+                current_health.saturating_add(new_max_health - *max_health)
+            } else {
+                (*current_health).min(new_max_health)
+            };
+        } else {
+            *current_health = (*current_health).min(new_max_health);
+        }
+
+        *max_health = new_max_health;
+    }
+
     pub fn start_execution(&mut self) {
         self.script_executor.lock().unwrap().start_execution();
     }
@@ -108,6 +307,12 @@ impl ActiveExploit {
         self.script_executor.lock().unwrap().total_instructions()
     }
 
+    /// Whether the exploit is connected and its script is making progress,
+    /// which is when its host's defensive script runs against the target.
+    pub fn is_executing(&self) -> bool {
+        matches!(self.status(), ActiveExploitStatus::Running) && !self.script_executor.lock().unwrap().is_paused()
+    }
+
     pub fn status(&self) -> ActiveExploitStatus {
         if *self.has_connected.lock().unwrap() {
             if *self.connection_current_health.lock().unwrap() == 0 {
@@ -122,25 +327,38 @@ impl ActiveExploit {
 
     pub fn tick(
         &mut self,
-        commands: &mut Commands,
+        events: &mut Vec<SimulationEvent>,
         host_ticks_since_last: u64,
-        target_ticks_since_last: u64
+        target_ticks_since_last: u64,
+        game_ticks: u32,
     ) -> (Vec<AlgorithmEffectApplication>, Vec<AlgorithmEffectApplication>) {
+        self.update_max_health();
+
         if !*self.has_connected.lock().unwrap() {
+            // Whatever ticks are left once the delay runs out go towards connecting
+            let connecting_ticks = {
+                let mut reconnect_delay = self.reconnect_delay.lock().unwrap();
+                let waited = (*reconnect_delay).min(game_ticks);
+                *reconnect_delay -= waited;
+                if *reconnect_delay > 0 {
+                    return (vec![], vec![]);
+                }
+
+                game_ticks - waited
+            };
+
+            let health_increase = self.connect_rate_for().saturating_mul(connecting_ticks);
             let mut current_health = self.connection_current_health.lock().unwrap();
             let max_health = self.connection_max_health.lock().unwrap();
 
-            let health_increase_per_tick = 1; // ZJ-TODO
-            *current_health = (*current_health + health_increase_per_tick).min(*max_health);
+            *current_health = current_health.saturating_add(health_increase).min(*max_health);
 
             if *current_health >= *max_health {
                 *self.has_connected.lock().unwrap() = true;
                 self.script_executor.lock().unwrap().start_execution();
                 self.target.lock().unwrap().script_executor.start_execution();
 
-                // ZJ-TODO: this should probably be ExploitActive
-                //          as we "start" before we're connected
-                commands.trigger(ExploitStarted {
+                events.push(SimulationEvent::Connected {
                     exploit_id: self.id,
                 });
             }
@@ -148,9 +366,15 @@ impl ActiveExploit {
             return (vec![], vec![]);
         }
 
+        self.regenerate(game_ticks);
+
         let mut exploit_target = self.target.lock().unwrap();
-        let new_host_effects = self.script_executor.lock().unwrap()
-            .tick_execution(host_ticks_since_last)
+        let script = self.script.clone();
+        let new_host_effects = Self::tick_repeating(
+            &mut self.script_executor.lock().unwrap(),
+            &script,
+            host_ticks_since_last,
+        )
             .into_iter()
             .map(|effect| AlgorithmEffectApplication {
                 host_server: self.hosting_server.clone(),
@@ -158,18 +382,14 @@ impl ActiveExploit {
                 effect,
                 script: self.script.clone(),
             })
-            .collect();
+            .collect::<Vec<_>>();
 
-        if self.script_executor.lock().unwrap().is_complete() {
-            std::mem::swap(
-                &mut self.script_executor,
-                &mut Arc::new(Mutex::new(ScriptExecutor::from_arc(&self.script)))
-            );
-            self.script_executor.lock().unwrap().start_execution();
-        }
-
-        let new_target_effects = exploit_target.script_executor
-            .tick_execution(target_ticks_since_last)
+        let target_script = exploit_target.script.clone();
+        let new_target_effects = Self::tick_repeating(
+            &mut exploit_target.script_executor,
+            &target_script,
+            target_ticks_since_last,
+        )
             .into_iter()
             .map(|effect| AlgorithmEffectApplication {
                 host_server: exploit_target.server.clone(),
@@ -179,16 +399,38 @@ impl ActiveExploit {
             })
             .collect();
 
-        if exploit_target.script_executor.is_complete() {
-            let mut new_script_executor = ScriptExecutor::from_arc(&exploit_target.script);
+        (new_host_effects, new_target_effects)
+    }
+
+    /// Ticks a script that starts over each time it completes, carrying ticks left over at the end of
+    /// one run into the next, so no work is lost between runs.
+    pub(crate) fn tick_repeating(
+        script_executor: &mut ScriptExecutor,
+        script: &Arc<Mutex<Script>>,
+        tick_count: u64,
+    ) -> Vec<AlgorithmEffect> {
+        let mut effects = vec![];
+        let mut remaining_ticks = tick_count;
+
+        loop {
+            let result = script_executor.tick(remaining_ticks);
+            effects.extend(result.effects);
+
+            if !script_executor.is_complete() {
+                break;
+            }
 
-            std::mem::swap(
-                &mut exploit_target.script_executor,
-                &mut new_script_executor,
-            );
-            exploit_target.script_executor.start_execution();
+            *script_executor = ScriptExecutor::from_arc(script);
+            script_executor.start_execution();
+
+            // A run that used none of its ticks (eg. an empty script) would otherwise repeat forever
+            if result.unused_ticks == 0 || result.unused_ticks == remaining_ticks {
+                break;
+            }
+
+            remaining_ticks = result.unused_ticks;
         }
 
-        (new_host_effects, new_target_effects)
+        effects
     }
 }
\ No newline at end of file
@@ -1,11 +1,15 @@
 use crate::algorithm::effect::AlgorithmEffect;
 use crate::algorithm::id::AlgorithmId;
+use crate::algorithm::rarity::AlgorithmRarity;
 use crate::ui::instruction_count::InstructionCount;
 
 #[derive(Clone)]
 pub struct Algorithm {
     pub id: AlgorithmId,
 
+    /// How good this algorithm rolled when it was generated.
+    pub rarity: AlgorithmRarity,
+
     /// How many instructions does this algorithm contain?
     /// Once all instructions are executed, the algorithm is considered complete
     pub instruction_count: InstructionCount,
@@ -25,6 +29,7 @@ impl Default for Algorithm {
     fn default() -> Self {
         Algorithm {
             id: AlgorithmId::Invalid,
+            rarity: AlgorithmRarity::Common,
             instruction_count: InstructionCount::new(0),
             instruction_effects: vec![],
         }
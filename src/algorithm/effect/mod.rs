@@ -5,13 +5,15 @@ pub mod value;
 use std::collections::HashMap;
 use std::fmt::Debug;
 use fluent_templates::fluent_bundle::FluentValue;
+use serde::{Deserialize, Serialize};
 use crate::algorithm::effect::target::AlgorithmEffectTarget;
 use crate::algorithm::effect::value::AlgorithmEffectValue;
 use crate::l10n::Localizable;
 use crate::l10n::message_id::MessageId;
 use crate::server::ServerStatType;
+use crate::TICKS_PER_SECOND;
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum AlgorithmEffect {
     /// `Terminate` damages the connection health between the two servers.
     /// This is primarily used by opposing servers to disconnect the player's exploits.
@@ -28,13 +30,24 @@ pub enum AlgorithmEffect {
     /// `Modify` alters the stats of a server.
     /// This can be used to buff or debuff a `target`, either the hosting server or remote target server.
     /// The higher the `potency`, the stronger the effect on `stat`.
-    Modify { target: AlgorithmEffectTarget, stat: ServerStatType, potency: AlgorithmEffectValue },
+    /// The modification wears off after `duration` ticks, or lasts forever without one.
+    Modify {
+        target: AlgorithmEffectTarget,
+        stat: ServerStatType,
+        potency: AlgorithmEffectValue,
+        #[serde(default)]
+        duration: Option<u32>,
+    },
 
     /// `Purge` removes negative modifications of the type `stat` from `target`.
     /// Unlike [Modify](AlgorithmEffect::Modify), `Purge` will only return a stat to its baseline level.
     /// The higher the `potency`, the more negative modifications on self will be removed,
     /// or positive modifications on target will be removed.
     Purge { target: AlgorithmEffectTarget, stat: ServerStatType, potency: AlgorithmEffectValue },
+
+    /// `PurgeAll` is a [Purge](AlgorithmEffect::Purge) of every stat on `target` at once,
+    /// with the full `potency` spent on each stat.
+    PurgeAll { target: AlgorithmEffectTarget, potency: AlgorithmEffectValue },
 }
 
 impl Localizable for AlgorithmEffect {
@@ -43,8 +56,10 @@ impl Localizable for AlgorithmEffect {
             AlgorithmEffect::Terminate { .. } => MessageId::AlgorithmEffectTerminateInstance,
             AlgorithmEffect::Siphon { .. } => MessageId::AlgorithmEffectSiphonInstance,
             AlgorithmEffect::Exfil { .. } => MessageId::AlgorithmEffectExfilInstance,
-            AlgorithmEffect::Modify { .. } => MessageId::AlgorithmEffectModifyInstance,
+            AlgorithmEffect::Modify { duration: None, .. } => MessageId::AlgorithmEffectModifyInstance,
+            AlgorithmEffect::Modify { duration: Some(_), .. } => MessageId::AlgorithmEffectModifyTimedInstance,
             AlgorithmEffect::Purge { .. } => MessageId::AlgorithmEffectPurgeInstance,
+            AlgorithmEffect::PurgeAll { .. } => MessageId::AlgorithmEffectPurgeAllInstance,
         }
     }
 
@@ -57,14 +72,51 @@ impl Localizable for AlgorithmEffect {
                   ("potency", format!("{potency}").into())
                 ])
             }
-            AlgorithmEffect::Modify { target, stat, potency }
-            | AlgorithmEffect::Purge { target, stat, potency } => {
+            AlgorithmEffect::Modify { target, stat, potency, duration } => {
+                let seconds = duration.unwrap_or_default() as f64 / TICKS_PER_SECOND as f64;
+                HashMap::from([
+                    ("target", format!("{target:?}").into()),
+                    ("stat", format!("{stat:?}").into()),
+                    ("potency", format!("{potency}").into()),
+                    ("seconds", seconds.into()),
+                ])
+            }
+            AlgorithmEffect::Purge { target, stat, potency } => {
                 HashMap::from([
                     ("target", format!("{target:?}").into()),
                     ("stat", format!("{stat:?}").into()),
                     ("potency", format!("{potency}").into())
                 ])
             }
+            AlgorithmEffect::PurgeAll { target, potency } => {
+                HashMap::from([
+                    ("target", format!("{target:?}").into()),
+                    ("potency", format!("{potency}").into())
+                ])
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+/// Every effect the game knows about, with every kind of value and target.
+#[cfg(test)]
+pub(crate) fn every_effect() -> Vec<AlgorithmEffect> {
+    let mut effects = vec![];
+    for potency in [AlgorithmEffectValue::Static(3), AlgorithmEffectValue::Range(1..5)] {
+        effects.push(AlgorithmEffect::Terminate { potency: potency.clone() });
+        effects.push(AlgorithmEffect::Siphon { potency: potency.clone() });
+        effects.push(AlgorithmEffect::Exfil { potency: potency.clone() });
+
+        for target in [AlgorithmEffectTarget::SelfServer, AlgorithmEffectTarget::TargetServer] {
+            for stat in ServerStatType::ALL {
+                for duration in [None, Some(100)] {
+                    effects.push(AlgorithmEffect::Modify { target: target.clone(), stat: stat.clone(), potency: potency.clone(), duration });
+                }
+                effects.push(AlgorithmEffect::Purge { target: target.clone(), stat: stat.clone(), potency: potency.clone() });
+            }
+            effects.push(AlgorithmEffect::PurgeAll { target: target.clone(), potency: potency.clone() });
+        }
+    }
+
+    effects
+}
@@ -1,6 +1,7 @@
 use std::fmt::{Debug, Formatter};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Eq, PartialEq, Hash)]
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum AlgorithmEffectTarget {
     /// The algorithm effect will target the server running the algorithm.
     /// This is often used for self-buffs.
@@ -1,10 +1,11 @@
 use std::fmt::{Display, Formatter};
 use std::ops::Range;
-use rand::RngExt;
+use rand::{Rng, RngExt};
+use serde::{Deserialize, Serialize};
 
 pub type AlgorithmEffectValueT = i32;
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Serialize, Deserialize)]
 pub enum AlgorithmEffectValue {
     /// This value will always be a single value (the provided `i32`).
     Static(AlgorithmEffectValueT),
@@ -28,10 +29,11 @@ impl Display for AlgorithmEffectValue {
 impl AlgorithmEffectValue {
     /// Gets or generates a value.
     /// Repeated calls may result in different values in the case of range values (such as [RangeInclusive](AlgorithmEffectValue::RangeInclusive)).
-    pub fn make_value(&self) -> AlgorithmEffectValueT {
-        let rng = &mut rand::rng();
+    pub fn make_value(&self, rng: &mut impl Rng) -> AlgorithmEffectValueT {
         match self {
             Self::Static(v) => *v,
+            // An empty range can't be sampled, so it behaves as though it were just its start
+            Self::Range(range) if range.is_empty() => range.start,
             Self::Range(range) => {
                 rng.sample(
                     rand::distr::Uniform::new(range.start, range.end).unwrap()
@@ -1,7 +1,7 @@
 use std::sync::{Arc, Mutex, Weak};
 use crate::algorithm::algorithm::Algorithm;
 use crate::algorithm::effect::AlgorithmEffect;
-use crate::executor::Executor;
+use crate::executor::{Executor, TickResult};
 
 #[derive(Clone)]
 pub struct AlgorithmExecutor {
@@ -33,25 +33,26 @@ impl Executor for AlgorithmExecutor {
         self.is_paused = true;
     }
 
-    fn tick_execution(&mut self, tick_count: u64) -> Vec<AlgorithmEffect> {
+    fn tick(&mut self, tick_count: u64) -> TickResult {
         if self.is_paused {
-            return vec![];
+            return TickResult { effects: vec![], unused_ticks: tick_count };
         }
 
         let algorithm_arc = self.algorithm.upgrade().unwrap();
         let algorithm = algorithm_arc.lock().unwrap();
 
-        let next_effects = algorithm
+        let effects = algorithm
             .instruction_effects
             .iter()
             .filter(|(instruction_count, _)| **instruction_count > self.instruction_pointer && self.instruction_pointer + tick_count >= **instruction_count)
-            .map(|(_, effects)| effects.to_owned())
-            .flatten()
+            .flat_map(|(_, effects)| effects.to_owned())
             .collect::<Vec<AlgorithmEffect>>();
 
-        self.instruction_pointer = (self.instruction_pointer + tick_count).min(*algorithm.instruction_count);
+        let remaining_instructions = algorithm.instruction_count.saturating_sub(self.instruction_pointer);
+        let used_ticks = tick_count.min(remaining_instructions);
+        self.instruction_pointer += used_ticks;
 
-        next_effects
+        TickResult { effects, unused_ticks: tick_count - used_ticks }
     }
 
     fn is_complete(&self) -> bool {
@@ -1,45 +1,73 @@
 use std::sync::{Arc, Mutex};
-use rand::distr::{Distribution, Uniform};
+use rand::distr::Uniform;
 use rand::prelude::IndexedRandom;
-use rand::RngExt;
-use uuid::Uuid;
+use rand::{Rng, RngExt};
 use crate::algorithm::algorithm::Algorithm;
 use crate::algorithm::effect::{AlgorithmEffect, target::AlgorithmEffectTarget, value::AlgorithmEffectValue};
 use crate::algorithm::id::AlgorithmId;
+use crate::algorithm::rarity::AlgorithmRarity;
+use crate::rng::random_uuid;
 use crate::server::ServerStatType;
+use crate::target::tier::DifficultyTier;
 use crate::ui::instruction_count::InstructionCount;
+use crate::TICKS_PER_SECOND;
+
+/// How good an algorithm the generator is allowed to make.
+/// The budget decides the algorithm's [rarity](AlgorithmRarity), which shapes how many effects it has,
+/// how strong they are and how many instructions it takes to run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GenerationBudget(pub u32);
+
+impl GenerationBudget {
+    /// The budget for an algorithm exfiltrated from a target of `tier`, when the exfil beat the
+    /// target's resistance by `margin`. The roll can land anywhere from half to one and a half times as well.
+    pub fn from_exfil(margin: i32, tier: DifficultyTier, rng: &mut impl Rng) -> GenerationBudget {
+        let budget = margin.max(1) as f64 * (1 + tier.0) as f64 * rng.random_range(0.5..1.5);
+        GenerationBudget(budget.round() as u32)
+    }
+}
+
+/// How long the modifications of generated `Modify` effects last, in seconds.
+const MIN_MODIFY_DURATION: u32 = 5;
+const MAX_MODIFY_DURATION: u32 = 30;
 
 pub struct AlgorithmGenerator;
 
 impl AlgorithmGenerator {
-    pub fn generate() -> Arc<Mutex<Algorithm>> {
-        let num_effects_distr = rand_distr::Normal::new(1.0f32, 0.7).unwrap();
-        let num_effects = num_effects_distr
-            .sample(&mut rand::rng())
-            .floor()
-            .max(1.0)
-            as u8;
+    pub fn generate(budget: GenerationBudget, rng: &mut impl Rng) -> Arc<Mutex<Algorithm>> {
+        let rarity = AlgorithmRarity::from_budget(budget.0);
+        let (min_effects, max_effects) = rarity.effect_count();
+        let num_effects = rng.random_range(min_effects..=max_effects);
 
         let ph_val = 0;
+        // Stats are picked separately, so adding a stat doesn't make stat effects crowd out everything else
+        let ph_stat = ServerStatType::SiphonResist;
         let effect_options = [
             AlgorithmEffect::Siphon { potency: AlgorithmEffectValue::Static(ph_val) },
             AlgorithmEffect::Siphon { potency: AlgorithmEffectValue::Range(ph_val..ph_val) },
             AlgorithmEffect::Exfil { potency: AlgorithmEffectValue::Static(ph_val) },
             AlgorithmEffect::Exfil { potency: AlgorithmEffectValue::Range(ph_val..ph_val) },
-            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::SelfServer, stat: ServerStatType::SiphonResist, potency: AlgorithmEffectValue::Static(ph_val) },
-            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::SelfServer, stat: ServerStatType::SiphonResist, potency: AlgorithmEffectValue::Range(ph_val..ph_val) },
-            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::SelfServer, stat: ServerStatType::ExfilResist, potency: AlgorithmEffectValue::Static(ph_val) },
-            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::SelfServer, stat: ServerStatType::ExfilResist, potency: AlgorithmEffectValue::Range(ph_val..ph_val) },
-            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::TargetServer, stat: ServerStatType::SiphonResist, potency: AlgorithmEffectValue::Static(ph_val) },
-            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::TargetServer, stat: ServerStatType::SiphonResist, potency: AlgorithmEffectValue::Range(ph_val..ph_val) },
-            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::TargetServer, stat: ServerStatType::ExfilResist, potency: AlgorithmEffectValue::Static(ph_val) },
-            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::TargetServer, stat: ServerStatType::ExfilResist, potency: AlgorithmEffectValue::Range(ph_val..ph_val) },
+            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::SelfServer, stat: ph_stat.clone(), potency: AlgorithmEffectValue::Static(ph_val), duration: None },
+            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::SelfServer, stat: ph_stat.clone(), potency: AlgorithmEffectValue::Range(ph_val..ph_val), duration: None },
+            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::TargetServer, stat: ph_stat.clone(), potency: AlgorithmEffectValue::Static(ph_val), duration: None },
+            AlgorithmEffect::Modify { target: AlgorithmEffectTarget::TargetServer, stat: ph_stat.clone(), potency: AlgorithmEffectValue::Range(ph_val..ph_val), duration: None },
+            AlgorithmEffect::Terminate { potency: AlgorithmEffectValue::Static(ph_val) },
+            AlgorithmEffect::Terminate { potency: AlgorithmEffectValue::Range(ph_val..ph_val) },
+            AlgorithmEffect::Purge { target: AlgorithmEffectTarget::SelfServer, stat: ph_stat.clone(), potency: AlgorithmEffectValue::Static(ph_val) },
+            AlgorithmEffect::Purge { target: AlgorithmEffectTarget::SelfServer, stat: ph_stat.clone(), potency: AlgorithmEffectValue::Range(ph_val..ph_val) },
+            AlgorithmEffect::Purge { target: AlgorithmEffectTarget::TargetServer, stat: ph_stat.clone(), potency: AlgorithmEffectValue::Static(ph_val) },
+            AlgorithmEffect::Purge { target: AlgorithmEffectTarget::TargetServer, stat: ph_stat, potency: AlgorithmEffectValue::Range(ph_val..ph_val) },
+            AlgorithmEffect::PurgeAll { target: AlgorithmEffectTarget::SelfServer, potency: AlgorithmEffectValue::Static(ph_val) },
+            AlgorithmEffect::PurgeAll { target: AlgorithmEffectTarget::TargetServer, potency: AlgorithmEffectValue::Static(ph_val) },
         ];
 
         let mut added_effects = vec![];
-        let mut rng = rand::rng();
         for _ in 0..num_effects {
-            let mut new_effect = effect_options.choose(&mut rng).unwrap().to_owned();
+            let mut new_effect = effect_options.choose(rng).unwrap().to_owned();
+            if let AlgorithmEffect::Modify { ref mut stat, .. } | AlgorithmEffect::Purge { ref mut stat, .. } = new_effect {
+                *stat = ServerStatType::ALL.choose(rng).unwrap().clone();
+            }
+
             match new_effect {
                 AlgorithmEffect::Siphon { ref mut potency } => {
                     match potency {
@@ -63,7 +91,8 @@ impl AlgorithmGenerator {
                         }
                     }
                 }
-                AlgorithmEffect::Modify { ref mut potency, .. } => {
+                AlgorithmEffect::Modify { ref mut potency, ref mut duration, .. } => {
+                    *duration = Some(rng.random_range(MIN_MODIFY_DURATION..=MAX_MODIFY_DURATION) * TICKS_PER_SECOND as u32);
                     match potency {
                         AlgorithmEffectValue::Static(val) => {
                             *val = rng.random_range(-10..10);
@@ -74,22 +103,193 @@ impl AlgorithmGenerator {
                         }
                     }
                 }
-                _ => todo!("generator: unsupported effect {:?}", new_effect),
+                AlgorithmEffect::Terminate { ref mut potency } => {
+                    match potency {
+                        AlgorithmEffectValue::Static(val) => {
+                            *val = rng.random_range(1..5);
+                        }
+                        AlgorithmEffectValue::Range(range) => {
+                            range.start = rng.random_range(1..4);
+                            range.end = rng.random_range((range.start + 1)..=5);
+                        }
+                    }
+                }
+                // Purging only ever undoes debuffs on ourselves and buffs on the opponent,
+                // so the potency points towards baseline for whichever side is targeted
+                AlgorithmEffect::Purge { target: AlgorithmEffectTarget::SelfServer, ref mut potency, .. }
+                | AlgorithmEffect::PurgeAll { target: AlgorithmEffectTarget::SelfServer, ref mut potency } => {
+                    match potency {
+                        AlgorithmEffectValue::Static(val) => {
+                            *val = rng.random_range(1..10);
+                        }
+                        AlgorithmEffectValue::Range(range) => {
+                            range.start = rng.random_range(1..8);
+                            range.end = rng.random_range((range.start + 1)..=10);
+                        }
+                    }
+                }
+                AlgorithmEffect::Purge { target: AlgorithmEffectTarget::TargetServer, ref mut potency, .. }
+                | AlgorithmEffect::PurgeAll { target: AlgorithmEffectTarget::TargetServer, ref mut potency } => {
+                    match potency {
+                        AlgorithmEffectValue::Static(val) => {
+                            *val = rng.random_range(-10..0);
+                        }
+                        AlgorithmEffectValue::Range(range) => {
+                            range.start = rng.random_range(-10..-2);
+                            range.end = rng.random_range((range.start + 1)..=-1);
+                        }
+                    }
+                }
             }
 
+            Self::scale_potency(&mut new_effect, rarity.potency_multiplier());
             added_effects.push(new_effect);
         }
 
         let instruction_count = InstructionCount::new(
-            rng.sample(Uniform::new(1_000_000, 3_000_000).unwrap()) * num_effects as u64
+            rng.sample(Uniform::new(1_000_000, 3_000_000).unwrap()) * num_effects as u64 * rarity.instruction_quarters() / 4
         );
 
-        let instruction_effects = vec![(instruction_count.clone(), added_effects)];
+        // Effects trigger at evenly spaced checkpoints, the last one on the final instruction
+        let checkpoint_count = added_effects.len() as u64;
+        let instruction_effects = added_effects
+            .into_iter()
+            .enumerate()
+            .map(|(index, effect)| {
+                let checkpoint = *instruction_count * (index as u64 + 1) / checkpoint_count;
+                (InstructionCount::new(checkpoint), vec![effect])
+            })
+            .collect();
 
         Arc::new(Mutex::new(Algorithm {
-            id: AlgorithmId::Id(Uuid::new_v4()),
+            id: AlgorithmId::Id(random_uuid(rng)),
+            rarity,
             instruction_count,
             instruction_effects,
         }))
     }
+
+    fn scale_potency(effect: &mut AlgorithmEffect, multiplier: i32) {
+        let potency = match effect {
+            AlgorithmEffect::Terminate { potency }
+            | AlgorithmEffect::Siphon { potency }
+            | AlgorithmEffect::Exfil { potency }
+            | AlgorithmEffect::Modify { potency, .. }
+            | AlgorithmEffect::Purge { potency, .. }
+            | AlgorithmEffect::PurgeAll { potency, .. } => potency,
+        };
+
+        match potency {
+            AlgorithmEffectValue::Static(value) => *value *= multiplier,
+            AlgorithmEffectValue::Range(range) => {
+                range.start *= multiplier;
+                range.end *= multiplier;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::rng::RngStreams;
+    use super::*;
+
+    #[test]
+    fn rarer_algorithms_are_stronger_and_faster() {
+        let mut rng = RngStreams::new(1234).next_stream();
+        for budget in [0, 10, 25, 50, 100] {
+            let budget = GenerationBudget(budget);
+            let rarity = AlgorithmRarity::from_budget(budget.0);
+            let (min_effects, max_effects) = rarity.effect_count();
+
+            for _ in 0..20 {
+                let algorithm = AlgorithmGenerator::generate(budget, &mut rng);
+                let algorithm = algorithm.lock().unwrap();
+                let effect_count = algorithm.instruction_effects.iter().map(|(_, effects)| effects.len()).sum::<usize>();
+
+                assert_eq!(algorithm.rarity, rarity);
+                assert!((min_effects as usize..=max_effects as usize).contains(&effect_count));
+                assert!(*algorithm.instruction_count < 3_000_000 * effect_count as u64 * rarity.instruction_quarters() / 4);
+            }
+        }
+    }
+
+    #[test]
+    fn effects_are_spread_across_checkpoints() {
+        let mut rng = RngStreams::new(1234).next_stream();
+        for _ in 0..20 {
+            let algorithm = AlgorithmGenerator::generate(GenerationBudget(100), &mut rng);
+            let algorithm = algorithm.lock().unwrap();
+            let checkpoints = algorithm.instruction_effects.iter().map(|(instruction, _)| **instruction).collect::<Vec<_>>();
+
+            assert_eq!(checkpoints.len(), 3);
+            assert!(checkpoints.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(checkpoints.last(), Some(&*algorithm.instruction_count));
+        }
+    }
+
+    #[test]
+    fn every_effect_can_be_generated() {
+        let mut rng = RngStreams::new(1234).next_stream();
+        let mut generated = HashSet::new();
+        for _ in 0..500 {
+            let algorithm = AlgorithmGenerator::generate(GenerationBudget(100), &mut rng);
+            for (_, effects) in &algorithm.lock().unwrap().instruction_effects {
+                for effect in effects {
+                    let (kind, potency) = match effect {
+                        AlgorithmEffect::Terminate { potency } => ("terminate", potency),
+                        AlgorithmEffect::Siphon { potency } => ("siphon", potency),
+                        AlgorithmEffect::Exfil { potency } => ("exfil", potency),
+                        AlgorithmEffect::Modify { potency, .. } => ("modify", potency),
+                        AlgorithmEffect::Purge { potency, .. } => ("purge", potency),
+                        AlgorithmEffect::PurgeAll { potency, .. } => ("purge_all", potency),
+                    };
+
+                    // Range rolls panic when the range is empty
+                    if let AlgorithmEffectValue::Range(range) = potency {
+                        assert!(range.start < range.end, "{effect:?} has an empty range");
+                    }
+
+                    generated.insert(kind);
+                }
+            }
+        }
+
+        assert_eq!(generated.len(), 6);
+    }
+
+    #[test]
+    fn purge_potency_points_towards_baseline() {
+        let mut rng = RngStreams::new(1234).next_stream();
+        for _ in 0..500 {
+            let algorithm = AlgorithmGenerator::generate(GenerationBudget(100), &mut rng);
+            for (_, effects) in &algorithm.lock().unwrap().instruction_effects {
+                for effect in effects {
+                    let (AlgorithmEffect::Purge { target, potency, .. } | AlgorithmEffect::PurgeAll { target, potency }) = effect else {
+                        continue;
+                    };
+
+                    let (low, high) = match potency {
+                        AlgorithmEffectValue::Static(value) => (*value, *value),
+                        AlgorithmEffectValue::Range(range) => (range.start, range.end),
+                    };
+
+                    match target {
+                        AlgorithmEffectTarget::SelfServer => assert!(low > 0, "{effect:?}"),
+                        AlgorithmEffectTarget::TargetServer => assert!(high < 0, "{effect:?}"),
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn better_exfils_earn_bigger_budgets() {
+        let budget = |margin, tier| GenerationBudget::from_exfil(margin, DifficultyTier(tier), &mut RngStreams::new(1234).next_stream());
+
+        assert!(budget(1, 0) < budget(5, 0));
+        assert!(budget(5, 0) < budget(5, 3));
+        assert!(budget(-3, 0).0 >= 1);
+    }
 }
@@ -0,0 +1,242 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use rand::RngExt;
+use crate::algorithm::algorithm::Algorithm;
+use crate::algorithm::effect::AlgorithmEffect;
+use crate::algorithm::effect::target::AlgorithmEffectTarget;
+use crate::algorithm::effect::value::{AlgorithmEffectValue, AlgorithmEffectValueT};
+use crate::algorithm::generator::{AlgorithmGenerator, GenerationBudget};
+use crate::rng::RngStreams;
+use crate::TICKS_PER_SECOND;
+
+/// How many algorithms are on offer after each restock.
+pub const STOCK_SIZE: usize = 4;
+
+/// How long the market's stock lasts before it's replaced.
+pub const RESTOCK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// The market never stocks anything better than an Epic algorithm; Legendaries have to be exfiltrated.
+const MAX_STOCK_BUDGET: u32 = 100;
+
+/// How much of an algorithm's price the market pays to buy it back.
+const SELL_BACK_RATIO: f64 = 0.5;
+
+/// The instruction count prices are quoted against; faster algorithms cost more, slower ones less.
+const REFERENCE_INSTRUCTION_COUNT: f64 = 2_000_000.0;
+
+/// Timed modifications lasting this many ticks or longer are worth as much as permanent ones.
+const MODIFY_FULL_VALUE_TICKS: u32 = 60 * TICKS_PER_SECOND as u32;
+
+/// Mixed into the run seed to seed the market's own streams, so they don't line up with the run's.
+const MARKET_SEED_SALT: u64 = 0x6D61_726B_6574;
+
+/// Sells generated algorithms, and buys back whatever the player doesn't want.
+pub struct AlgorithmMarket {
+    pub stock: Vec<Arc<Mutex<Algorithm>>>,
+
+    /// How long until the stock is replaced.
+    pub time_until_restock: Duration,
+
+    /// Restocks happen on wall time, so they draw from streams of their own;
+    /// drawing from the run's would change which stream the next exploit gets.
+    pub rng_streams: RngStreams,
+}
+
+impl AlgorithmMarket {
+    /// Creates a market with nothing on offer, which restocks on its first [advance](AlgorithmMarket::advance).
+    /// Every restock derives from `run_seed`.
+    pub fn new(run_seed: u64) -> AlgorithmMarket {
+        AlgorithmMarket {
+            stock: vec![],
+            time_until_restock: Duration::ZERO,
+            rng_streams: RngStreams::new(run_seed ^ MARKET_SEED_SALT),
+        }
+    }
+
+    /// Advances the restock timer by `elapsed`, replacing the stock if it ran out.
+    /// Returns whether the market restocked.
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        if elapsed < self.time_until_restock {
+            self.time_until_restock -= elapsed;
+            return false;
+        }
+
+        // However long the market went unattended, only one fresh batch of stock is generated
+        let mut rng = self.rng_streams.next_stream();
+        self.stock = (0..STOCK_SIZE)
+            .map(|_| {
+                let budget = GenerationBudget(rng.random_range(0..MAX_STOCK_BUDGET));
+                AlgorithmGenerator::generate(budget, &mut rng)
+            })
+            .collect();
+        self.time_until_restock = RESTOCK_INTERVAL;
+
+        true
+    }
+
+    /// Takes `algorithm` out of the stock, returning whether it was on offer.
+    pub fn take(&mut self, algorithm: &Arc<Mutex<Algorithm>>) -> bool {
+        let stock_count = self.stock.len();
+        self.stock.retain(|stocked| !Arc::ptr_eq(stocked, algorithm));
+
+        self.stock.len() != stock_count
+    }
+
+    /// What the market charges for `algorithm`, from the strength of its effects and how quickly it runs.
+    pub fn price(algorithm: &Algorithm) -> u128 {
+        let effect_value = algorithm
+            .instruction_effects
+            .iter()
+            .flat_map(|(_, effects)| effects)
+            .map(effect_value)
+            .sum::<f64>();
+
+        let speed = REFERENCE_INSTRUCTION_COUNT / (*algorithm.instruction_count).max(1) as f64;
+
+        ((effect_value * speed).round() as u128).max(1)
+    }
+
+    /// What the market pays for `algorithm`.
+    pub fn sell_price(algorithm: &Algorithm) -> u128 {
+        ((Self::price(algorithm) as f64 * SELL_BACK_RATIO).floor() as u128).max(1)
+    }
+}
+
+/// How many credits each point of an effect's potency is worth.
+fn effect_value(effect: &AlgorithmEffect) -> f64 {
+    let (credits_per_potency, potency) = match effect {
+        AlgorithmEffect::Siphon { potency } => (10.0, potency),
+        AlgorithmEffect::Exfil { potency } => (12.0, potency),
+        AlgorithmEffect::Terminate { potency } => (6.0, potency),
+        AlgorithmEffect::Purge { potency, .. } => (5.0, potency),
+        AlgorithmEffect::PurgeAll { potency, .. } => (15.0, potency),
+        AlgorithmEffect::Modify { potency, .. } => (4.0, potency),
+    };
+
+    // Modifications help when they buff yourself or debuff the target; the other way round they're worthless
+    let direction = match effect {
+        AlgorithmEffect::Modify { target: AlgorithmEffectTarget::TargetServer, .. } => -1.0,
+        _ => 1.0,
+    };
+    let helpful_potency = |value: AlgorithmEffectValueT| (direction * value as f64).max(0.0);
+
+    let average_potency = match potency {
+        AlgorithmEffectValue::Static(value) => helpful_potency(*value),
+        AlgorithmEffectValue::Range(range) if range.is_empty() => helpful_potency(range.start),
+        AlgorithmEffectValue::Range(range) => range.clone().map(helpful_potency).sum::<f64>() / range.len() as f64,
+    };
+
+    let lasting = match effect {
+        AlgorithmEffect::Modify { duration: Some(duration), .. } => {
+            (*duration as f64 / MODIFY_FULL_VALUE_TICKS as f64).min(1.0)
+        }
+        _ => 1.0,
+    };
+
+    credits_per_potency * average_potency * lasting
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::ServerStatType;
+    use super::*;
+
+    fn siphon(instruction_count: u64, potency: AlgorithmEffectValue) -> Algorithm {
+        Algorithm {
+            instruction_count: instruction_count.into(),
+            instruction_effects: vec![(instruction_count.into(), vec![AlgorithmEffect::Siphon { potency }])],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn stronger_and_faster_algorithms_cost_more() {
+        let base = AlgorithmMarket::price(&siphon(2_000_000, AlgorithmEffectValue::Static(5)));
+        assert_eq!(base, 50);
+
+        assert!(AlgorithmMarket::price(&siphon(2_000_000, (5..10).into())) > base);
+        assert!(AlgorithmMarket::price(&siphon(1_000_000, AlgorithmEffectValue::Static(5))) > base);
+        assert!(AlgorithmMarket::sell_price(&siphon(2_000_000, AlgorithmEffectValue::Static(5))) < base);
+    }
+
+    fn modify(target: AlgorithmEffectTarget, potency: AlgorithmEffectValue, duration: Option<u32>) -> Algorithm {
+        let effect = AlgorithmEffect::Modify { target, stat: ServerStatType::SiphonResist, potency, duration };
+        Algorithm {
+            instruction_count: 2_000_000.into(),
+            instruction_effects: vec![(2_000_000.into(), vec![effect])],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn only_helpful_modifications_are_worth_anything() {
+        let buff = AlgorithmMarket::price(&modify(AlgorithmEffectTarget::SelfServer, 9.into(), None));
+        let debuff = AlgorithmMarket::price(&modify(AlgorithmEffectTarget::TargetServer, (-9).into(), None));
+        assert_eq!(buff, 36);
+        assert_eq!(debuff, buff);
+
+        assert_eq!(AlgorithmMarket::price(&modify(AlgorithmEffectTarget::SelfServer, (-9).into(), None)), 1);
+        assert_eq!(AlgorithmMarket::price(&modify(AlgorithmEffectTarget::TargetServer, 9.into(), None)), 1);
+    }
+
+    #[test]
+    fn modifications_are_valued_by_their_helpful_range() {
+        // Only the rolls above zero help, so -8..8 is worth what 1..8 is, half the time
+        let crossing = AlgorithmMarket::price(&modify(AlgorithmEffectTarget::SelfServer, (-8..8).into(), None));
+        assert_eq!(crossing, 7);
+
+        let helpful = AlgorithmMarket::price(&modify(AlgorithmEffectTarget::SelfServer, (1..8).into(), None));
+        assert!(helpful > crossing);
+    }
+
+    #[test]
+    fn timed_modifications_cost_less_than_permanent_ones() {
+        let permanent = AlgorithmMarket::price(&modify(AlgorithmEffectTarget::SelfServer, 9.into(), None));
+        let ten_seconds = AlgorithmMarket::price(&modify(AlgorithmEffectTarget::SelfServer, 9.into(), Some(10 * TICKS_PER_SECOND as u32)));
+        let half = AlgorithmMarket::price(&modify(AlgorithmEffectTarget::SelfServer, 9.into(), Some(MODIFY_FULL_VALUE_TICKS / 2)));
+
+        assert_eq!(half, permanent / 2);
+        assert!(ten_seconds < half);
+        assert_eq!(AlgorithmMarket::price(&modify(AlgorithmEffectTarget::SelfServer, 9.into(), Some(MODIFY_FULL_VALUE_TICKS * 2))), permanent);
+    }
+
+    #[test]
+    fn stock_is_replaced_on_a_timer() {
+        let mut market = AlgorithmMarket::new(1234);
+
+        assert!(market.advance(Duration::ZERO));
+        assert_eq!(market.stock.len(), STOCK_SIZE);
+        let first_stock = market.stock.clone();
+
+        assert!(!market.advance(RESTOCK_INTERVAL / 2));
+        assert!(market.advance(RESTOCK_INTERVAL / 2));
+        assert!(!Arc::ptr_eq(&market.stock[0], &first_stock[0]));
+    }
+
+    #[test]
+    fn restocks_are_reproducible_from_the_run_seed() {
+        let stock_ids = |market: &AlgorithmMarket| market.stock
+            .iter()
+            .map(|algorithm| algorithm.lock().unwrap().id.clone())
+            .collect::<Vec<_>>();
+
+        let mut first = AlgorithmMarket::new(1234);
+        let mut second = AlgorithmMarket::new(1234);
+        for _ in 0..3 {
+            first.advance(RESTOCK_INTERVAL);
+            second.advance(RESTOCK_INTERVAL);
+            assert_eq!(stock_ids(&first), stock_ids(&second));
+        }
+    }
+
+    #[test]
+    fn only_stocked_algorithms_can_be_taken() {
+        let mut market = AlgorithmMarket::new(1234);
+        market.advance(Duration::ZERO);
+        let algorithm = market.stock[1].clone();
+
+        assert!(market.take(&algorithm));
+        assert!(!market.take(&algorithm));
+        assert_eq!(market.stock.len(), STOCK_SIZE - 1);
+    }
+}
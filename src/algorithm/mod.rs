@@ -3,4 +3,6 @@ pub mod effect;
 pub mod executor;
 pub mod generator;
 pub mod id;
+pub mod market;
 pub mod procedure;
+pub mod rarity;
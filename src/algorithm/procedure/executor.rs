@@ -1,16 +1,18 @@
 use std::sync::{Arc, Mutex, Weak};
 use crate::algorithm::algorithm::Algorithm;
-use crate::algorithm::effect::AlgorithmEffect;
 use crate::algorithm::executor::AlgorithmExecutor;
-use crate::algorithm::procedure::{AlgorithmProcedure, AlgorithmProcedureIterator};
-use crate::executor::Executor;
+use crate::algorithm::procedure::AlgorithmProcedure;
+use crate::executor::{Executor, TickResult};
 
 #[derive(Clone)]
 pub struct AlgorithmProcedureExecutor {
-    procedure: Weak<Mutex<AlgorithmProcedure>>,
-    procedure_iterator: AlgorithmProcedureIterator,
-    finished_algorithms: Vec<Weak<Mutex<Algorithm>>>,
-    algorithm_executor: AlgorithmExecutor,
+    algorithms: Vec<Weak<Mutex<Algorithm>>>,
+
+    /// For each node, the nodes that must finish before it starts.
+    predecessors: Vec<Vec<usize>>,
+
+    /// For each node, its executor once it has started.
+    node_executors: Vec<Option<AlgorithmExecutor>>,
 
     total_expected_instructions: u64,
     is_paused: bool,
@@ -21,78 +23,120 @@ impl AlgorithmProcedureExecutor {
     /// If the procedure contains no algorithms, returns None.
     pub fn from(algorithm_procedure: &Arc<Mutex<AlgorithmProcedure>>) -> Option<AlgorithmProcedureExecutor> {
         let procedure_inner = algorithm_procedure.lock().unwrap();
-        let total_expected_instructions = procedure_inner.instruction_count();
-
-        let mut procedure_iterator = procedure_inner.iterator();
-
-        let Some(algorithm) = procedure_iterator.next() else {
+        if procedure_inner.is_empty() {
             return None;
-        };
+        }
 
+        let nodes = procedure_inner.nodes();
         Some(AlgorithmProcedureExecutor {
-            procedure: Arc::downgrade(algorithm_procedure),
-            procedure_iterator,
-            finished_algorithms: Vec::new(),
-            algorithm_executor: AlgorithmExecutor::from(algorithm),
-            total_expected_instructions,
+            algorithms: nodes.iter().map(|node| Arc::downgrade(&node.algorithm)).collect(),
+            predecessors: nodes.iter().map(|node| node.predecessors.clone()).collect(),
+            node_executors: vec![None; nodes.len()],
+            total_expected_instructions: procedure_inner.instruction_count(),
             is_paused: true,
         })
     }
+
+    /// How many branches of the procedure are currently executing an algorithm.
+    pub fn running_branches(&self) -> usize {
+        self
+            .node_executors
+            .iter()
+            .flatten()
+            .filter(|executor| !executor.is_complete())
+            .count()
+    }
+
+    fn is_node_complete(&self, node: usize) -> bool {
+        self.node_executors[node].as_ref().is_some_and(|executor| executor.is_complete())
+    }
 }
 
 impl Executor for AlgorithmProcedureExecutor {
     fn start_execution(&mut self) {
         self.is_paused = false;
-        self.algorithm_executor.start_execution();
+        for executor in self.node_executors.iter_mut().flatten() {
+            executor.start_execution();
+        }
     }
 
     fn stop_execution(&mut self) {
         self.is_paused = true;
-        self.algorithm_executor.stop_execution();
+        for executor in self.node_executors.iter_mut().flatten() {
+            executor.stop_execution();
+        }
     }
 
-    fn tick_execution(&mut self, tick_count: u64) -> Vec<AlgorithmEffect> {
+    fn tick(&mut self, tick_count: u64) -> TickResult {
         if self.is_paused {
-            return vec![];
+            return TickResult { effects: vec![], unused_ticks: tick_count };
         }
 
-        if self.algorithm_executor.is_complete() {
-            // Take old algorithm and store it so we can track overall progress
-            let finished_algorithm = std::mem::take(
-                &mut self.algorithm_executor.algorithm
-            );
-
-            self.finished_algorithms.push(finished_algorithm);
-
-            // Pop next algorithm and begin executing it
-            let Some(next_algorithm_executor) = self.procedure_iterator.next() else {
-                return vec![];
+        // For each node that has finished, how many of this call's ticks were left once it did.
+        // Nodes only follow earlier nodes, so a single pass in order lets ticks flow through
+        // as many algorithms as they can complete.
+        let mut ticks_left_after = vec![None; self.node_executors.len()];
+        let mut effects = vec![];
+
+        for node in 0..self.node_executors.len() {
+            let available_ticks = match &self.node_executors[node] {
+                Some(executor) if executor.is_complete() => {
+                    ticks_left_after[node] = Some(tick_count);
+                    continue;
+                }
+                Some(_) => tick_count,
+                None => {
+                    // A node starts as its last predecessor finishes, with whatever ticks that predecessor left
+                    let predecessor_ticks = self.predecessors[node]
+                        .iter()
+                        .map(|&predecessor| ticks_left_after[predecessor])
+                        .collect::<Option<Vec<u64>>>();
+
+                    let Some(predecessor_ticks) = predecessor_ticks else {
+                        continue;
+                    };
+
+                    let mut executor = AlgorithmExecutor::from(self.algorithms[node].clone());
+                    executor.start_execution();
+                    self.node_executors[node] = Some(executor);
+
+                    predecessor_ticks.into_iter().min().unwrap_or(tick_count)
+                }
             };
 
-            self.algorithm_executor = AlgorithmExecutor::from(next_algorithm_executor);
-            self.algorithm_executor.start_execution();
+            let executor = self.node_executors[node].as_mut().unwrap();
+            let result = executor.tick(available_ticks);
+            effects.extend(result.effects);
+
+            if executor.is_complete() {
+                ticks_left_after[node] = Some(result.unused_ticks);
+            }
         }
 
-        self.algorithm_executor.tick_execution(tick_count)
+        // The procedure is only done once its last branch finishes
+        let unused_ticks = if self.is_complete() {
+            ticks_left_after.into_iter().flatten().min().unwrap_or(0)
+        } else {
+            0
+        };
+
+        TickResult { effects, unused_ticks }
     }
 
     fn is_complete(&self) -> bool {
-        self.procedure_iterator.is_empty() && self.algorithm_executor.is_complete()
+        (0..self.node_executors.len()).all(|node| self.is_node_complete(node))
     }
 
     fn progress(&self) -> u64 {
-        let completed_instruction_count: u64 = self
-            .finished_algorithms
+        self
+            .node_executors
             .iter()
-            .map(|algorithm| *algorithm.upgrade().unwrap().lock().unwrap().instruction_count)
-            .sum();
-
-        let current_instruction_pointer = self.algorithm_executor.instruction_pointer;
-
-        completed_instruction_count + current_instruction_pointer
+            .flatten()
+            .map(|executor| executor.progress())
+            .sum()
     }
 
     fn total_instructions(&self) -> u64 {
         self.total_expected_instructions
     }
-}
\ No newline at end of file
+}
@@ -1,4 +1,5 @@
-// ZJ-TODO: figure out how to join/split procedures
+// A procedure is a graph of algorithms. Each algorithm starts once every algorithm before it has finished,
+// so a procedure can split into branches that run concurrently, and join them back together.
 // eg       x-x-x
 //         /     \
 //    x-x-x       x-x-x
@@ -7,13 +8,39 @@
 
 pub mod executor;
 
+use std::fmt::{Display, Formatter};
 use std::sync::{Arc, Mutex, Weak};
 use crate::algorithm::algorithm::Algorithm;
 use crate::algorithm::id::AlgorithmId;
 
+#[derive(Debug, PartialEq)]
+pub enum ProcedureError {
+    /// A node was added after a node that doesn't exist (yet).
+    UnknownPredecessor { predecessor: usize },
+}
+
+impl Display for ProcedureError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcedureError::UnknownPredecessor { predecessor } => write!(f, "procedure has no node {predecessor}"),
+        }
+    }
+}
+
+impl std::error::Error for ProcedureError {}
+
+#[derive(Clone)]
+pub struct ProcedureNode {
+    pub algorithm: Arc<Mutex<Algorithm>>,
+
+    /// The nodes that must all finish before this one starts.
+    /// Nodes only ever follow nodes added before them, so procedures can never loop.
+    pub predecessors: Vec<usize>,
+}
+
 #[derive(Clone)]
 pub struct AlgorithmProcedure {
-    algorithms: Vec<Arc<Mutex<Algorithm>>>,
+    nodes: Vec<ProcedureNode>,
 }
 
 impl AlgorithmProcedure {
@@ -21,40 +48,153 @@ impl AlgorithmProcedure {
     /// The algorithms will be executed such that the first element of the slice is executed
     /// before the second element of the slice, and so on.
     pub fn from(algorithms: &[Arc<Mutex<Algorithm>>]) -> Self {
-        AlgorithmProcedure {
-            algorithms: algorithms.to_vec(),
+        let mut procedure = AlgorithmProcedure { nodes: Vec::new() };
+        for algorithm in algorithms {
+            procedure.add_algorithm(algorithm.clone());
         }
+
+        procedure
     }
 
-    /// Adds an algorithm to the end of the procedure.
+    /// Adds an algorithm to the end of the procedure, once every branch has finished.
     pub fn add_algorithm(&mut self, algorithm: Arc<Mutex<Algorithm>>) {
-        // We execute algorithms from end of the vec to front
-        self.algorithms.push(algorithm);
+        let predecessors = self.last_nodes();
+        self.nodes.push(ProcedureNode { algorithm, predecessors });
+    }
+
+    /// Adds an algorithm that starts once all of `predecessors` have finished, returning its node.
+    /// Following a node that already has a successor splits the procedure into branches;
+    /// following several nodes joins their branches.
+    pub fn add_node(&mut self, algorithm: Arc<Mutex<Algorithm>>, predecessors: &[usize]) -> Result<usize, ProcedureError> {
+        if let Some(&predecessor) = predecessors.iter().find(|&&predecessor| predecessor >= self.nodes.len()) {
+            return Err(ProcedureError::UnknownPredecessor { predecessor });
+        }
+
+        let mut predecessors = predecessors.to_vec();
+        predecessors.sort_unstable();
+        predecessors.dedup();
+
+        self.nodes.push(ProcedureNode { algorithm, predecessors });
+        Ok(self.nodes.len() - 1)
     }
 
     /// Removes an algorithm from the procedure, if it exists within.
+    /// Nodes that followed it instead follow whatever it followed, so branches stay connected.
     /// No error is returned if the element does not exist.
     pub fn remove_algorithm(&mut self, algorithm_id: AlgorithmId) {
-        self.algorithms.retain(|algo| {
-            algo.lock().unwrap().id != algorithm_id
-        });
+        while let Some(removed) = self
+            .nodes
+            .iter()
+            .position(|node| node.algorithm.lock().unwrap().id == algorithm_id)
+        {
+            let removed_node = self.nodes.remove(removed);
+            for node in &mut self.nodes[removed..] {
+                if node.predecessors.contains(&removed) {
+                    node.predecessors.retain(|&predecessor| predecessor != removed);
+                    node.predecessors.extend(&removed_node.predecessors);
+                }
+
+                for predecessor in &mut node.predecessors {
+                    if *predecessor > removed {
+                        *predecessor -= 1;
+                    }
+                }
+
+                node.predecessors.sort_unstable();
+                node.predecessors.dedup();
+            }
+        }
+    }
+
+    pub fn nodes(&self) -> &[ProcedureNode] {
+        &self.nodes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// The nodes nothing follows yet; the ends of every branch.
+    pub fn last_nodes(&self) -> Vec<usize> {
+        (0..self.nodes.len())
+            .filter(|&node| self.nodes[node + 1..].iter().all(|later| !later.predecessors.contains(&node)))
+            .collect()
+    }
+
+    /// The most algorithms that could ever be running at once, which is how many threads the procedure needs.
+    /// This is the largest set of nodes where none has to wait on another.
+    pub fn peak_parallelism(&self) -> u32 {
+        let node_count = self.nodes.len();
+
+        // Whether node j (transitively) waits on node i
+        // Nodes only follow earlier nodes, so each row is complete before any later row reads it
+        let mut waits_on = vec![vec![false; node_count]; node_count];
+        for j in 0..node_count {
+            let (earlier, later) = waits_on.split_at_mut(j);
+            for &i in &self.nodes[j].predecessors {
+                later[0][i] = true;
+                for (waits, &inherited) in later[0].iter_mut().zip(&earlier[i]) {
+                    *waits |= inherited;
+                }
+            }
+        }
+
+        // By Dilworth's theorem, the largest set of independent nodes is the node count minus the
+        // largest matching between nodes and the nodes that wait on them
+        let mut matched_to = vec![None; node_count];
+        let mut matching = 0;
+        for i in 0..node_count {
+            let mut visited = vec![false; node_count];
+            if Self::find_augmenting_path(i, &waits_on, &mut visited, &mut matched_to) {
+                matching += 1;
+            }
+        }
+
+        (node_count - matching) as u32
+    }
+
+    fn find_augmenting_path(
+        node: usize,
+        waits_on: &[Vec<bool>],
+        visited: &mut [bool],
+        matched_to: &mut [Option<usize>],
+    ) -> bool {
+        for successor in 0..waits_on.len() {
+            if !waits_on[successor][node] || visited[successor] {
+                continue;
+            }
+
+            visited[successor] = true;
+            let is_free = match matched_to[successor] {
+                None => true,
+                Some(other) => Self::find_augmenting_path(other, waits_on, visited, matched_to),
+            };
+
+            if is_free {
+                matched_to[successor] = Some(node);
+                return true;
+            }
+        }
+
+        false
     }
 
     pub fn iterator(&self) -> AlgorithmProcedureIterator {
-        AlgorithmProcedureIterator::new(self.algorithms.clone())
+        AlgorithmProcedureIterator::new(self.nodes.iter().map(|node| node.algorithm.clone()).collect())
     }
 
     /// Returns the number of instructions algorithms in the procedure have remaining.
     /// This returns a current count, meaning calls to [next()](AlgorithmProcedure::next) will reduce this value.
     pub fn instruction_count(&self) -> u64 {
         self
-            .algorithms
+            .nodes
             .iter()
-            .map(|algo| *algo.lock().unwrap().instruction_count)
+            .map(|node| *node.algorithm.lock().unwrap().instruction_count)
             .sum::<u64>()
     }
 }
 
+/// Iterates every algorithm in a procedure, with each one after all of the algorithms it waits on.
 #[derive(Clone)]
 pub struct AlgorithmProcedureIterator {
     algorithms: Vec<Arc<Mutex<Algorithm>>>,
@@ -88,3 +228,83 @@ impl Iterator for AlgorithmProcedureIterator {
         maybe_algorithm
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+    use super::*;
+
+    fn make_algorithm() -> Arc<Mutex<Algorithm>> {
+        Arc::new(Mutex::new(Algorithm {
+            id: Uuid::new_v4().into(),
+            rarity: Default::default(),
+            instruction_count: 3.into(),
+            instruction_effects: Default::default(),
+        }))
+    }
+
+    fn predecessors(procedure: &AlgorithmProcedure) -> Vec<Vec<usize>> {
+        procedure.nodes().iter().map(|node| node.predecessors.clone()).collect()
+    }
+
+    /// ```text
+    ///      1 - 2
+    ///     /     \
+    ///    0       4
+    ///     \     /
+    ///      - 3 -
+    /// ```
+    fn diamond() -> AlgorithmProcedure {
+        let mut procedure = AlgorithmProcedure::from(&[make_algorithm(), make_algorithm(), make_algorithm()]);
+        procedure.add_node(make_algorithm(), &[0]).unwrap();
+        procedure.add_node(make_algorithm(), &[2, 3]).unwrap();
+        procedure
+    }
+
+    #[test]
+    fn algorithms_are_added_in_series() {
+        let procedure = AlgorithmProcedure::from(&[make_algorithm(), make_algorithm(), make_algorithm()]);
+
+        assert_eq!(predecessors(&procedure), vec![vec![], vec![0], vec![1]]);
+        assert_eq!(procedure.peak_parallelism(), 1);
+    }
+
+    #[test]
+    fn forked_branches_run_in_parallel() {
+        let mut procedure = diamond();
+        assert_eq!(procedure.peak_parallelism(), 2);
+        assert_eq!(procedure.last_nodes(), vec![4]);
+
+        // A third branch off the first algorithm, left dangling
+        procedure.add_node(make_algorithm(), &[0]).unwrap();
+        assert_eq!(procedure.peak_parallelism(), 3);
+        assert_eq!(procedure.last_nodes(), vec![4, 5]);
+
+        // Appending joins every branch
+        procedure.add_algorithm(make_algorithm());
+        assert_eq!(predecessors(&procedure)[6], vec![4, 5]);
+    }
+
+    #[test]
+    fn unknown_predecessors_are_rejected() {
+        let mut procedure = AlgorithmProcedure::from(&[make_algorithm()]);
+
+        assert_eq!(procedure.add_node(make_algorithm(), &[1]), Err(ProcedureError::UnknownPredecessor { predecessor: 1 }));
+        assert_eq!(procedure.nodes().len(), 1);
+    }
+
+    #[test]
+    fn removing_a_node_reconnects_its_branch() {
+        let mut procedure = diamond();
+        let forked_id = procedure.nodes()[1].algorithm.lock().unwrap().id.clone();
+        procedure.remove_algorithm(forked_id);
+
+        assert_eq!(predecessors(&procedure), vec![vec![], vec![0], vec![0], vec![1, 2]]);
+        assert_eq!(procedure.peak_parallelism(), 2);
+
+        let first_id = procedure.nodes()[0].algorithm.lock().unwrap().id.clone();
+        procedure.remove_algorithm(first_id);
+
+        assert_eq!(predecessors(&procedure), vec![vec![], vec![], vec![0, 1]]);
+    }
+}
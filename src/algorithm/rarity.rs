@@ -0,0 +1,87 @@
+use serde::{Deserialize, Serialize};
+use crate::l10n::Localizable;
+use crate::l10n::message_id::MessageId;
+
+/// How good an algorithm rolled when it was generated.
+/// Rarer algorithms have more effects, stronger potencies and fewer instructions.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum AlgorithmRarity {
+    #[default]
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+impl AlgorithmRarity {
+    /// The rarity a generation budget buys.
+    pub fn from_budget(budget: u32) -> AlgorithmRarity {
+        match budget {
+            0..10 => AlgorithmRarity::Common,
+            10..25 => AlgorithmRarity::Uncommon,
+            25..50 => AlgorithmRarity::Rare,
+            50..100 => AlgorithmRarity::Epic,
+            _ => AlgorithmRarity::Legendary,
+        }
+    }
+
+    /// The fewest and most effects an algorithm of this rarity is generated with.
+    pub fn effect_count(self) -> (u8, u8) {
+        match self {
+            AlgorithmRarity::Common => (1, 1),
+            AlgorithmRarity::Uncommon => (1, 2),
+            AlgorithmRarity::Rare => (2, 2),
+            AlgorithmRarity::Epic => (2, 3),
+            AlgorithmRarity::Legendary => (3, 3),
+        }
+    }
+
+    /// How many times over generated potencies are scaled.
+    pub fn potency_multiplier(self) -> i32 {
+        match self {
+            AlgorithmRarity::Common => 1,
+            AlgorithmRarity::Uncommon => 2,
+            AlgorithmRarity::Rare => 3,
+            AlgorithmRarity::Epic => 4,
+            AlgorithmRarity::Legendary => 6,
+        }
+    }
+
+    /// How many quarters of the usual instruction count algorithms of this rarity need per effect.
+    pub fn instruction_quarters(self) -> u64 {
+        match self {
+            AlgorithmRarity::Common => 4,
+            AlgorithmRarity::Uncommon => 4,
+            AlgorithmRarity::Rare => 3,
+            AlgorithmRarity::Epic => 3,
+            AlgorithmRarity::Legendary => 2,
+        }
+    }
+}
+
+impl Localizable for AlgorithmRarity {
+    fn loc_key(&self) -> MessageId {
+        match self {
+            AlgorithmRarity::Common => MessageId::AlgorithmRarityCommon,
+            AlgorithmRarity::Uncommon => MessageId::AlgorithmRarityUncommon,
+            AlgorithmRarity::Rare => MessageId::AlgorithmRarityRare,
+            AlgorithmRarity::Epic => MessageId::AlgorithmRarityEpic,
+            AlgorithmRarity::Legendary => MessageId::AlgorithmRarityLegendary,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bigger_budgets_never_buy_worse_algorithms() {
+        let rarities = (0..200).map(AlgorithmRarity::from_budget).collect::<Vec<_>>();
+
+        assert_eq!(rarities[0], AlgorithmRarity::Common);
+        assert_eq!(rarities[199], AlgorithmRarity::Legendary);
+        assert!(rarities.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
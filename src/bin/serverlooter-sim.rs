@@ -0,0 +1,193 @@
+//! Runs many seeded fights of a scenario headlessly, and reports how they played out.
+//!
+//! ```text
+//! serverlooter-sim <scenario.ron> [--fights N] [--seed SEED] [--time-limit SECONDS] [--format text|csv]
+//! ```
+//!
+//! Fight `i` is seeded with `SEED + i`, so any single fight can be replayed with `--seed <its seed> --fights 1`.
+
+use std::process::ExitCode;
+use std::time::Duration;
+use serverlooter::simulation::fight::{run_fight, FightOutcome};
+use serverlooter::simulation::scenario::Scenario;
+
+const USAGE: &str = "usage: serverlooter-sim <scenario.ron> [--fights N] [--seed SEED] [--time-limit SECONDS] [--format text|csv]";
+
+enum Format {
+    Text,
+    Csv,
+}
+
+struct Args {
+    scenario_path: String,
+    fights: u64,
+    seed: u64,
+    time_limit: Duration,
+    format: Format,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut scenario_path = None;
+    let mut parsed = Args {
+        scenario_path: String::new(),
+        fights: 100,
+        seed: 0,
+        time_limit: Duration::from_secs(600),
+        format: Format::Text,
+    };
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{name} needs a value"));
+
+        match arg.as_str() {
+            "--fights" => parsed.fights = value("--fights")?.parse().map_err(|err| format!("--fights: {err}"))?,
+            "--seed" => parsed.seed = value("--seed")?.parse().map_err(|err| format!("--seed: {err}"))?,
+            "--time-limit" => {
+                let seconds = value("--time-limit")?.parse().map_err(|err| format!("--time-limit: {err}"))?;
+                parsed.time_limit = Duration::from_secs(seconds);
+            }
+            "--format" => {
+                parsed.format = match value("--format")?.as_str() {
+                    "text" => Format::Text,
+                    "csv" => Format::Csv,
+                    other => return Err(format!("unknown format '{other}'")),
+                }
+            }
+            _ if arg.starts_with("--") => return Err(format!("unknown option '{arg}'")),
+            _ if scenario_path.is_none() => scenario_path = Some(arg),
+            _ => return Err(format!("unexpected argument '{arg}'")),
+        }
+    }
+
+    parsed.scenario_path = scenario_path.ok_or("missing scenario path")?;
+    if parsed.fights == 0 {
+        return Err("--fights must be at least 1".to_string());
+    }
+    if parsed.time_limit.is_zero() {
+        return Err("--time-limit must be at least 1".to_string());
+    }
+
+    Ok(parsed)
+}
+
+/// Min, quartiles and max of a set of samples.
+struct Distribution {
+    min: f64,
+    p25: f64,
+    p50: f64,
+    p75: f64,
+    max: f64,
+    mean: f64,
+}
+
+impl Distribution {
+    fn of(mut samples: Vec<f64>) -> Option<Distribution> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        samples.sort_by(f64::total_cmp);
+        let percentile = |p: f64| samples[((samples.len() - 1) as f64 * p).round() as usize];
+
+        Some(Distribution {
+            min: samples[0],
+            p25: percentile(0.25),
+            p50: percentile(0.5),
+            p75: percentile(0.75),
+            max: samples[samples.len() - 1],
+            mean: samples.iter().sum::<f64>() / samples.len() as f64,
+        })
+    }
+}
+
+fn print_distribution(name: &str, samples: Vec<f64>) {
+    match Distribution::of(samples) {
+        Some(dist) => println!(
+            "{name:<24} mean {:>10.3}  min {:>10.3}  p25 {:>10.3}  p50 {:>10.3}  p75 {:>10.3}  max {:>10.3}",
+            dist.mean, dist.min, dist.p25, dist.p50, dist.p75, dist.max,
+        ),
+        None => println!("{name:<24} n/a"),
+    }
+}
+
+fn print_text(args: &Args, outcomes: &[FightOutcome]) {
+    let disconnects = outcomes.iter().filter(|outcome| outcome.disconnected_at.is_some()).count();
+
+    println!("scenario: {}", args.scenario_path);
+    println!(
+        "fights: {} (seeds {}..={}), time limit {}s",
+        outcomes.len(),
+        args.seed,
+        args.seed + args.fights - 1,
+        args.time_limit.as_secs(),
+    );
+    println!();
+    print_distribution("credits/second", outcomes.iter().map(FightOutcome::credits_per_second).collect());
+    print_distribution("exfils/second", outcomes.iter().map(FightOutcome::exfils_per_second).collect());
+    print_distribution(
+        "time to disconnect (s)",
+        outcomes.iter().filter_map(FightOutcome::time_to_disconnect).map(|time| time.as_secs_f64()).collect(),
+    );
+    println!(
+        "{:<24} {:.1}% ({disconnects}/{})",
+        "disconnect rate",
+        disconnects as f64 / outcomes.len() as f64 * 100.0,
+        outcomes.len(),
+    );
+}
+
+fn print_csv(outcomes: &[FightOutcome]) {
+    println!("seed,duration_secs,connected,disconnected,time_to_disconnect_secs,credits,credits_per_second,siphons,exfils,exfils_per_second,algorithms_stolen");
+    for outcome in outcomes {
+        println!(
+            "{},{:.3},{},{},{},{},{:.3},{},{},{:.3},{}",
+            outcome.seed,
+            outcome.duration.as_secs_f64(),
+            outcome.connected_at.is_some(),
+            outcome.disconnected_at.is_some(),
+            outcome.time_to_disconnect().map(|time| format!("{:.3}", time.as_secs_f64())).unwrap_or_default(),
+            outcome.credits,
+            outcome.credits_per_second(),
+            outcome.siphons,
+            outcome.exfils,
+            outcome.exfils_per_second(),
+            outcome.algorithms_stolen,
+        );
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let scenario = match std::fs::read_to_string(&args.scenario_path) {
+        Ok(contents) => Scenario::from_ron(&contents),
+        Err(err) => {
+            eprintln!("couldn't read {}: {err}", args.scenario_path);
+            return ExitCode::FAILURE;
+        }
+    };
+    let scenario = match scenario {
+        Ok(scenario) => scenario,
+        Err(err) => {
+            eprintln!("{}: {err}", args.scenario_path);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let outcomes = (0..args.fights)
+        .map(|fight| run_fight(&scenario, args.seed.wrapping_add(fight), args.time_limit))
+        .collect::<Vec<_>>();
+
+    match args.format {
+        Format::Text => print_text(&args, &outcomes),
+        Format::Csv => print_csv(&outcomes),
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,9 @@
+use std::sync::{Arc, Mutex};
+use bevy::prelude::Event;
+use crate::algorithm::algorithm::Algorithm;
+
+/// A target exfiltrated one of the player's algorithms.
+#[derive(Event)]
+pub struct AlgorithmStolen {
+    pub algorithm: Arc<Mutex<Algorithm>>,
+}
@@ -2,86 +2,95 @@ use bevy::prelude::Event;
 use bevy_egui::egui::include_image;
 use uuid::Uuid;
 use crate::algorithm::effect::{AlgorithmEffect, value::{AlgorithmEffectValue, AlgorithmEffectValueT}};
+use crate::server::ServerStatInstance;
 use crate::ui::mixed_text::MixedTextNode;
 
-#[derive(Clone, Event)]
+#[derive(Clone, Debug, Event)]
 pub struct ExploitEvent {
     pub active_exploit_id: Uuid,
     pub from_player_server: bool,
     pub algorithm_effect: AlgorithmEffect,
     pub potency_roll: Option<AlgorithmEffectValueT>,
     pub value_after_modification: Option<AlgorithmEffectValueT>,
+
+    /// The modifications (or parts of modifications) this effect removed from the server it targeted.
+    pub purged: Vec<ServerStatInstance>,
 }
 
 impl ExploitEvent {
     pub fn as_mixed_text(&self) -> Vec<MixedTextNode<'_>> {
-        match self.algorithm_effect {
-            AlgorithmEffect::Siphon { ref potency } => {
-                let potency_roll = self.potency_roll
-                    .expect(&format!("missing potency_roll: {:?}", self.algorithm_effect));
-                let value_after_modification = self.value_after_modification
-                    .expect(&format!("missing value_after_modification: {:?}", self.algorithm_effect));
+        let mut nodes = vec![MixedTextNode::Localizable(&self.algorithm_effect)];
 
-                if matches!(potency, AlgorithmEffectValue::Static(_)) {
-                    vec![
-                        MixedTextNode::Localizable(&self.algorithm_effect),
-                        MixedTextNode::Text(format!(" for ${}", value_after_modification))
-                    ]
-                } else {
-                    vec![
-                        MixedTextNode::Localizable(&self.algorithm_effect),
-                        MixedTextNode::Text(" (".to_string()),
-                        MixedTextNode::Image(include_image!("../../assets/sprites/dice.png")),
-                        MixedTextNode::Text(
-                            format!(" {potency_roll}) for ${value_after_modification}")
-                        ),
-                    ]
-                }
-            }
-            AlgorithmEffect::Exfil { ref potency } => {
-                let potency_roll = self.potency_roll
-                    .expect(&format!("missing potency_roll: {:?}", self.algorithm_effect));
+        // Effects that didn't resolve (eg. an exfil that couldn't get past the target's defenses) have nothing more to show
+        let Some(potency_roll) = self.potency_roll else {
+            return nodes;
+        };
 
-                if matches!(potency, AlgorithmEffectValue::Static(_)) {
-                    vec![
-                        MixedTextNode::Localizable(&self.algorithm_effect),
-                    ]
-                } else {
-                    vec![
-                        MixedTextNode::Localizable(&self.algorithm_effect),
-                        MixedTextNode::Text(" (".to_string()),
-                        MixedTextNode::Image(include_image!("../../assets/sprites/dice.png")),
-                        MixedTextNode::Text(format!("{potency_roll})")),
-                    ]
-                }
+        let potency = match self.algorithm_effect {
+            AlgorithmEffect::Terminate { ref potency }
+            | AlgorithmEffect::Siphon { ref potency }
+            | AlgorithmEffect::Exfil { ref potency }
+            | AlgorithmEffect::Modify { ref potency, .. }
+            | AlgorithmEffect::Purge { ref potency, .. }
+            | AlgorithmEffect::PurgeAll { ref potency, .. } => potency,
+        };
+
+        if matches!(potency, AlgorithmEffectValue::Range(_)) {
+            // ZJ-TODO: loc
+            nodes.push(MixedTextNode::Text(" (".to_string()));
+            nodes.push(MixedTextNode::Image(include_image!("../../assets/sprites/dice.png")));
+            nodes.push(MixedTextNode::Text(format!(" {potency_roll})")));
+        }
+
+        // ZJ-TODO: loc
+        if let Some(value_after_modification) = self.value_after_modification {
+            let outcome = match self.algorithm_effect {
+                AlgorithmEffect::Terminate { .. } if self.from_player_server => " interrupting defenses".to_string(),
+                AlgorithmEffect::Terminate { .. } => format!(" leaving {value_after_modification} connection health"),
+                AlgorithmEffect::Siphon { .. } => format!(" for ${value_after_modification}"),
+                AlgorithmEffect::Exfil { .. }
+                | AlgorithmEffect::PurgeAll { .. } => String::new(),
+                AlgorithmEffect::Modify { .. }
+                | AlgorithmEffect::Purge { .. } => format!(" to {value_after_modification}"),
+            };
+
+            if !outcome.is_empty() {
+                nodes.push(MixedTextNode::Text(outcome));
             }
-            AlgorithmEffect::Modify { ref potency, .. }
-            | AlgorithmEffect::Purge { ref potency, .. } => {
-                let potency_roll = self.potency_roll
-                    .expect(&format!("missing potency_roll: {:?}", self.algorithm_effect));
+        }
+
+        match self.purged.len() {
+            0 => {}
+            1 => nodes.push(MixedTextNode::Text(", removing 1 modification".to_string())),
+            purged_count => nodes.push(MixedTextNode::Text(format!(", removing {purged_count} modifications"))),
+        }
+
+        nodes
+    }
+}
 
-                let new_value = self.value_after_modification
-                    .expect(&format!("missing value_after_modification: {:?}", self.algorithm_effect));
+#[cfg(test)]
+mod tests {
+    use crate::algorithm::effect::every_effect;
+    use super::*;
 
-                if matches!(potency, AlgorithmEffectValue::Static(_)) {
-                    vec![
-                        MixedTextNode::Localizable(&self.algorithm_effect),
-                        // ZJ-TODO: loc
-                        MixedTextNode::Text(" ".to_string()),
-                        MixedTextNode::Text(format!("to {new_value}"))
-                    ]
-                } else {
-                    vec![
-                        MixedTextNode::Localizable(&self.algorithm_effect),
-                        // ZJ-TODO: loc
-                        MixedTextNode::Text(" (".to_string()),
-                        MixedTextNode::Image(include_image!("../../assets/sprites/dice.png")),
-                        // ZJ-TODO: loc
-                        MixedTextNode::Text(format!(" {potency_roll}) for {new_value}")),
-                    ]
+    #[test]
+    fn every_effect_is_displayable() {
+        for algorithm_effect in every_effect() {
+            for from_player_server in [true, false] {
+                for (potency_roll, value_after_modification) in [(None, None), (Some(3), None), (Some(3), Some(7))] {
+                    let event = ExploitEvent {
+                        active_exploit_id: Uuid::nil(),
+                        from_player_server,
+                        algorithm_effect: algorithm_effect.clone(),
+                        potency_roll,
+                        value_after_modification,
+                        purged: vec![],
+                    };
+
+                    assert!(!event.as_mixed_text().is_empty());
                 }
             }
-            _ => panic!("!!! UNSUPPORTED ALGORITHM EFFECT FOR as_mixed_text: {:?}", self.algorithm_effect),
         }
     }
-}
\ No newline at end of file
+}
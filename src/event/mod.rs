@@ -5,6 +5,13 @@ pub mod request_restart_exploit;
 pub mod request_resume_exploit;
 pub mod modify_credits;
 pub mod request_purchase_unlock;
+pub mod request_purchase_server;
+pub mod request_upgrade_server;
+pub mod request_purchase_algorithm;
+pub mod request_sell_algorithm;
+pub mod request_assign_defensive_script;
 pub mod exploit_event;
 pub mod exploit_started;
-pub mod tutorial_data_dump_purchased;
\ No newline at end of file
+pub mod tutorial_data_dump_purchased;
+pub mod offline_catch_up_finished;
+pub mod algorithm_stolen;
@@ -0,0 +1,7 @@
+use bevy::prelude::Event;
+use crate::offline::state::OfflineSummary;
+
+#[derive(Event)]
+pub struct OfflineCatchUpFinished {
+    pub summary: OfflineSummary,
+}
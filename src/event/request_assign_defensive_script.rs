@@ -0,0 +1,11 @@
+use std::sync::{Arc, Mutex};
+use bevy::prelude::Event;
+use crate::script::Script;
+use crate::server::Server;
+
+/// Assigns `script` as `server`'s defensive script, or clears it when `script` is `None`.
+#[derive(Event)]
+pub struct RequestAssignDefensiveScriptEvent {
+    pub server: Arc<Mutex<Server>>,
+    pub script: Option<Arc<Mutex<Script>>>,
+}
@@ -0,0 +1,4 @@
+use bevy::prelude::Event;
+
+#[derive(Event)]
+pub struct RequestPurchaseServerEvent;
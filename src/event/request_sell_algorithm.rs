@@ -0,0 +1,8 @@
+use std::sync::{Arc, Mutex};
+use bevy::prelude::Event;
+use crate::algorithm::algorithm::Algorithm;
+
+#[derive(Event)]
+pub struct RequestSellAlgorithmEvent {
+    pub algorithm: Arc<Mutex<Algorithm>>,
+}
@@ -0,0 +1,10 @@
+use std::sync::{Arc, Mutex};
+use bevy::prelude::Event;
+use crate::server::market::ServerUpgrade;
+use crate::server::Server;
+
+#[derive(Event)]
+pub struct RequestUpgradeServerEvent {
+    pub server: Arc<Mutex<Server>>,
+    pub upgrade: ServerUpgrade,
+}
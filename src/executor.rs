@@ -1,15 +1,28 @@
 use crate::algorithm::effect::AlgorithmEffect;
 
+/// What happened during a single call to [Executor::tick].
+#[derive(Debug, Default, PartialEq)]
+pub struct TickResult {
+    pub effects: Vec<AlgorithmEffect>,
+
+    /// Ticks that weren't needed, because execution completed partway through.
+    /// These should be carried over to whatever executes next, so no work is lost between algorithms.
+    pub unused_ticks: u64,
+}
+
 pub trait Executor {
     fn start_execution(&mut self);
     fn stop_execution(&mut self);
-    // ZJ-TODO: this doesn't handle partial ticks
-    //          for example, if tick_count = 5, and we're on the last instruction
-    //          of an algorithm, the next algorithm in the procedure won't be ticked for 4
-    //          We'll instead currently "lose" 4 ticks.
-    //          The ticks should instead be re-applied to the next algorithm
-    fn tick_execution(&mut self, tick_count: u64) -> Vec<AlgorithmEffect>;
+
+    /// Executes up to `tick_count` instructions.
+    fn tick(&mut self, tick_count: u64) -> TickResult;
+
+    /// Executes up to `tick_count` instructions, returning only the effects triggered along the way.
+    fn tick_execution(&mut self, tick_count: u64) -> Vec<AlgorithmEffect> {
+        self.tick(tick_count).effects
+    }
+
     fn is_complete(&self) -> bool;
     fn progress(&self) -> u64;
     fn total_instructions(&self) -> u64;
-}
\ No newline at end of file
+}
@@ -1,9 +1,12 @@
 use std::sync::{Arc, Mutex};
-use uuid::Uuid;
+use rand::Rng;
 use crate::algorithm::algorithm::Algorithm;
 use crate::algorithm::effect::{AlgorithmEffect, target::AlgorithmEffectTarget};
 use crate::algorithm::id::AlgorithmId;
+use crate::algorithm::rarity::AlgorithmRarity;
+use crate::rng::random_uuid;
 use crate::server::ServerStatType;
+use crate::TICKS_PER_SECOND;
 
 pub mod plugin;
 pub mod systems;
@@ -18,11 +21,12 @@ pub struct Inventory {
 }
 
 impl Inventory {
-    pub fn new() -> Inventory {
+    pub fn new(rng: &mut impl Rng) -> Inventory {
         Inventory {
             algorithms: vec![
                 Arc::new(Mutex::new(Algorithm {
-                    id: AlgorithmId::Id(Uuid::new_v4()),
+                    id: AlgorithmId::Id(random_uuid(rng)),
+                    rarity: AlgorithmRarity::Common,
                     instruction_count: 1_000_000.into(),
                     instruction_effects: vec![
                         (1_000_000.into(), vec![
@@ -31,7 +35,8 @@ impl Inventory {
                     ],
                 })),
                 Arc::new(Mutex::new(Algorithm {
-                    id: AlgorithmId::Id(Uuid::new_v4()),
+                    id: AlgorithmId::Id(random_uuid(rng)),
+                    rarity: AlgorithmRarity::Common,
                     instruction_count: 5_000_000.into(),
                     instruction_effects: vec![
                         (5_000_000.into(), vec![
@@ -39,12 +44,14 @@ impl Inventory {
                                 target: AlgorithmEffectTarget::TargetServer,
                                 stat: ServerStatType::SiphonResist,
                                 potency: (-5..-1).into(),
+                                duration: Some(10 * TICKS_PER_SECOND as u32),
                             }
                         ])
                     ]
                 })),
                 Arc::new(Mutex::new(Algorithm {
-                    id: AlgorithmId::Id(Uuid::new_v4()),
+                    id: AlgorithmId::Id(random_uuid(rng)),
+                    rarity: AlgorithmRarity::Common,
                     instruction_count: 3_000_000.into(),
                     instruction_effects: vec![
                         (3_000_000.into(), vec![
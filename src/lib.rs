@@ -0,0 +1,49 @@
+pub mod ui;
+pub mod script;
+pub mod server;
+pub mod inventory;
+pub mod l10n;
+
+mod macros;
+pub mod event;
+pub mod algorithm;
+pub mod executor;
+pub mod active_exploit;
+pub mod player_state;
+pub mod tutorial;
+pub mod save;
+pub mod offline;
+pub mod rng;
+pub mod simulation;
+pub mod target;
+
+use std::time::Duration;
+use crate::active_exploit::ExploitTarget;
+use crate::player_state::state::PlayerState;
+use crate::player_state::unlocks::PlayerUnlock;
+
+pub const TICKS_PER_SECOND: u8 = 20;
+const _: () = assert!(
+    1000 % TICKS_PER_SECOND as u32 == 0,
+    "TICKS_PER_SECOND must cleanly factor into 1000, such that TIME_BETWEEN_TICKS isn't fractional"
+);
+pub const TIME_BETWEEN_TICKS: Duration = Duration::from_millis(1000 / TICKS_PER_SECOND as u64);
+
+/// Splits `elapsed` into whole ticks, and whatever's left over that doesn't make up a full tick.
+pub fn whole_ticks(elapsed: Duration) -> (u32, Duration) {
+    let ticks = (elapsed.as_nanos() / TIME_BETWEEN_TICKS.as_nanos()) as u32;
+
+    (ticks, elapsed - TIME_BETWEEN_TICKS * ticks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whole_ticks_keep_the_remainder() {
+        assert_eq!(whole_ticks(Duration::ZERO), (0, Duration::ZERO));
+        assert_eq!(whole_ticks(TIME_BETWEEN_TICKS - Duration::from_millis(1)), (0, TIME_BETWEEN_TICKS - Duration::from_millis(1)));
+        assert_eq!(whole_ticks(TIME_BETWEEN_TICKS * 3 + Duration::from_millis(7)), (3, Duration::from_millis(7)));
+    }
+}
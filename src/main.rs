@@ -1,101 +1,18 @@
-mod ui;
-mod script;
-mod server;
-mod inventory;
-mod l10n;
-
-mod macros;
-mod event;
-mod algorithm;
-mod executor;
-mod active_exploit;
-mod player_state;
-mod tutorial;
-
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
 use bevy::prelude::*;
 use bevy::DefaultPlugins;
 use bevy::diagnostic::FrameTimeDiagnosticsPlugin;
 #[cfg(debug_assertions)] // debug/dev builds only
 use bevy::diagnostic::LogDiagnosticsPlugin;
 use bevy_egui::EguiPlugin;
-use uuid::Uuid;
-use crate::active_exploit::ExploitTarget;
-use crate::algorithm::algorithm::Algorithm;
-use crate::algorithm::effect::{AlgorithmEffect, target::AlgorithmEffectTarget, value::AlgorithmEffectValue};
-use crate::algorithm::id::AlgorithmId;
-use crate::algorithm::procedure::AlgorithmProcedure;
-use crate::inventory::plugin::InventoryPlugin;
-use crate::player_state::plugin::PlayerStatePlugin;
-use crate::player_state::state::PlayerState;
-use crate::player_state::unlocks::PlayerUnlock;
-use crate::script::id::ScriptId;
-use crate::script::Script;
-use crate::server::{Server, ServerStatInstance, ServerStatInstances, ServerStatSource, ServerStatType};
-use crate::tutorial::plugin::TutorialPlugin;
-use crate::ui::clock_speed::ClockSpeed;
-use crate::ui::plugin::UiPlugin;
-
-const TICKS_PER_SECOND: u8 = 20;
-const _: () = assert!(
-    1000 % TICKS_PER_SECOND as u32 == 0,
-    "TICKS_PER_SECOND must cleanly factor into 1000, such that TIME_BETWEEN_TICKS isn't fractional"
-);
-const TIME_BETWEEN_TICKS: Duration = Duration::from_millis(1000 / TICKS_PER_SECOND as u64);
-
-fn make_exploit_target() -> Arc<Mutex<ExploitTarget>> {
-    Arc::new(Mutex::new(ExploitTarget::new(
-        Arc::new(Mutex::new(Server {
-            name: "<CORP NAME HERE>".to_string(),
-            threads: 1,
-            clock_speed: ClockSpeed::new(1_600_000),
-            stats: ServerStatInstances::from(&[
-                ServerStatInstance::new(ServerStatSource::Innate, ServerStatType::SiphonResist, 3),
-                ServerStatInstance::new(ServerStatSource::Innate, ServerStatType::ExfilResist, 8),
-            ]),
-            running_scripts: vec![],
-        })),
-        Arc::new(Mutex::new(Script {
-            id: ScriptId::Invalid,
-            procedures: vec![
-                Arc::new(Mutex::new(AlgorithmProcedure::from(&[
-                    Arc::new(Mutex::new(Algorithm {
-                        id: AlgorithmId::Id(Uuid::new_v4()),
-                        instruction_count: 1_000_000.into(),
-                        instruction_effects: vec![
-                            (250_000.into(), vec![AlgorithmEffect::Terminate { potency: AlgorithmEffectValue::Static(1) } ]),
-                            (500_000.into(), vec![AlgorithmEffect::Terminate { potency: AlgorithmEffectValue::Static(1) } ]),
-                            (750_000.into(), vec![AlgorithmEffect::Terminate { potency: AlgorithmEffectValue::Static(1) } ]),
-                            (1_000_000.into(), vec![AlgorithmEffect::Terminate { potency: AlgorithmEffectValue::Static(1) } ]),
-                        ],
-                    }))
-                ]))),
-                Arc::new(Mutex::new(AlgorithmProcedure::from(&[
-                    Arc::new(Mutex::new(Algorithm {
-                        id: AlgorithmId::Id(Uuid::new_v4()),
-                        instruction_count: 1_000_000.into(),
-                        instruction_effects: vec![
-                            (1_000_000.into(), vec![
-                                // ZJ-TODO: would be nice to have a PurgeAll
-                                AlgorithmEffect::Purge {
-                                    potency: AlgorithmEffectValue::Static(1),
-                                    target: AlgorithmEffectTarget::SelfServer,
-                                    stat: ServerStatType::SiphonResist,
-                                },
-                                AlgorithmEffect::Purge {
-                                    potency: AlgorithmEffectValue::Static(1),
-                                    target: AlgorithmEffectTarget::SelfServer,
-                                    stat: ServerStatType::ExfilResist,
-                                }
-                            ])
-                        ]
-                    }))
-                ])))
-            ],
-        }))
-    )))
-}
+use serverlooter::inventory::plugin::InventoryPlugin;
+use serverlooter::offline::plugin::OfflinePlugin;
+use serverlooter::player_state::plugin::PlayerStatePlugin;
+use serverlooter::player_state::state::PlayerState;
+use serverlooter::player_state::unlocks::PlayerUnlock;
+use serverlooter::save::plugin::SavePlugin;
+use serverlooter::target::plugin::TargetPlugin;
+use serverlooter::tutorial::plugin::TutorialPlugin;
+use serverlooter::ui::plugin::UiPlugin;
 
 fn main() {
     let mut app = App::new();
@@ -105,7 +22,10 @@ fn main() {
         .add_plugins(FrameTimeDiagnosticsPlugin::default())
         .add_plugins((
             InventoryPlugin,
+            OfflinePlugin,
             PlayerStatePlugin,
+            SavePlugin,
+            TargetPlugin,
             TutorialPlugin,
             UiPlugin,
         ))
@@ -129,4 +49,4 @@ fn handle_keyboard(
     }
 
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,3 @@
+pub mod plugin;
+pub mod state;
+pub(crate) mod systems;
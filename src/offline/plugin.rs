@@ -0,0 +1,14 @@
+use bevy::app::{App, Plugin, Update};
+use crate::offline::systems::*;
+
+pub struct OfflinePlugin;
+
+impl Plugin for OfflinePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_observer(record_offline_credits)
+            .add_observer(record_offline_algorithms)
+            .add_observer(record_offline_algorithm_losses)
+            .add_systems(Update, catch_up_offline_progress);
+    }
+}
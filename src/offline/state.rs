@@ -0,0 +1,38 @@
+use std::time::Duration;
+use bevy::prelude::Resource;
+
+/// Everything the player gained, or lost, while the game was closed.
+#[derive(Clone, Debug, Default)]
+pub struct OfflineSummary {
+    /// How much time was simulated, after capping.
+    pub elapsed: Duration,
+    pub credits_gained: i128,
+    pub algorithms_gained: usize,
+    pub algorithms_lost: usize,
+}
+
+/// Progress beyond this is forfeit, so a week away isn't worth a week of play.
+pub const MAX_OFFLINE_DURATION: Duration = Duration::from_secs(8 * 60 * 60);
+
+/// Present while progress made since the last save is still being simulated.
+/// Live ticking is suspended until this is removed.
+#[derive(Resource)]
+pub struct OfflineCatchUp {
+    /// Simulated time that hasn't been processed yet.
+    pub remaining: Duration,
+    pub summary: OfflineSummary,
+}
+
+impl OfflineCatchUp {
+    /// Starts catching up on `elapsed` wall time, capped to [MAX_OFFLINE_DURATION].
+    pub fn new(elapsed: Duration) -> OfflineCatchUp {
+        let elapsed = elapsed.min(MAX_OFFLINE_DURATION);
+        OfflineCatchUp {
+            remaining: elapsed,
+            summary: OfflineSummary {
+                elapsed,
+                ..OfflineSummary::default()
+            },
+        }
+    }
+}
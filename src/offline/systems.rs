@@ -0,0 +1,216 @@
+use std::time::Duration;
+use bevy::prelude::{Commands, In, On, ResMut, World};
+use crate::event::algorithm_stolen::AlgorithmStolen;
+use crate::event::modify_credits::ModifyCreditsEvent;
+use crate::event::offline_catch_up_finished::OfflineCatchUpFinished;
+use crate::inventory::event::item_added::InventoryItemAdded;
+use crate::inventory::InventoryItem;
+use crate::offline::state::OfflineCatchUp;
+use crate::player_state::state::PlayerState;
+use crate::player_state::systems::advance_active_exploits;
+use crate::TIME_BETWEEN_TICKS;
+
+/// Caps how many ticks are simulated each frame, so catching up doesn't freeze the game.
+const MAX_CATCH_UP_TICKS_PER_FRAME: u32 = 1_000;
+
+pub(crate) fn catch_up_offline_progress(world: &mut World) -> bevy::prelude::Result {
+    for _ in 0..MAX_CATCH_UP_TICKS_PER_FRAME {
+        let Some(mut catch_up) = world.get_resource_mut::<OfflineCatchUp>() else {
+            return Ok(());
+        };
+
+        if catch_up.remaining < TIME_BETWEEN_TICKS {
+            let summary = catch_up.summary.clone();
+            world.remove_resource::<OfflineCatchUp>();
+            world.resource_mut::<PlayerState>().last_tick = web_time::Instant::now();
+            world.trigger(OfflineCatchUpFinished { summary });
+
+            return Ok(());
+        }
+
+        catch_up.remaining -= TIME_BETWEEN_TICKS;
+
+        // Ticks are simulated one at a time, exactly as they would be live, and commands are applied after each.
+        // This way restarts, credits and items land before the next tick, just like they would have.
+        world.run_system_cached_with(tick_offline_exploits, TIME_BETWEEN_TICKS)?;
+    }
+
+    Ok(())
+}
+
+fn tick_offline_exploits(
+    In(elapsed): In<Duration>,
+    mut commands: Commands,
+    mut player_state: ResMut<PlayerState>,
+) {
+    advance_active_exploits(&mut commands, &mut player_state, elapsed);
+}
+
+pub(crate) fn record_offline_credits(
+    evt: On<ModifyCreditsEvent>,
+    catch_up: Option<ResMut<OfflineCatchUp>>,
+) {
+    if let Some(mut catch_up) = catch_up {
+        catch_up.summary.credits_gained += evt.credits as i128;
+    }
+}
+
+pub(crate) fn record_offline_algorithms(
+    evt: On<InventoryItemAdded>,
+    catch_up: Option<ResMut<OfflineCatchUp>>,
+) {
+    let Some(mut catch_up) = catch_up else {
+        return;
+    };
+
+    match evt.item {
+        InventoryItem::Algorithm(_) => catch_up.summary.algorithms_gained += 1,
+    }
+}
+
+/// Only theft counts as a loss; algorithms sold or built into scripts during catch-up weren't lost while away.
+pub(crate) fn record_offline_algorithm_losses(
+    _evt: On<AlgorithmStolen>,
+    catch_up: Option<ResMut<OfflineCatchUp>>,
+) {
+    if let Some(mut catch_up) = catch_up {
+        catch_up.summary.algorithms_lost += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use bevy::app::App;
+    use bevy::prelude::{Resource, ResMut};
+    use crate::active_exploit::ActiveExploit;
+    use crate::algorithm::procedure::AlgorithmProcedure;
+    use crate::event::exploit_event::ExploitEvent;
+    use crate::event::request_sell_algorithm::RequestSellAlgorithmEvent;
+    use crate::inventory::plugin::InventoryPlugin;
+    use crate::offline::plugin::OfflinePlugin;
+    use crate::offline::state::OfflineSummary;
+    use crate::player_state::plugin::PlayerStatePlugin;
+    use crate::script::id::ScriptId;
+    use crate::script::Script;
+    use crate::target::definition::corp_definition;
+    use super::*;
+
+    #[derive(Resource, Default)]
+    struct FinishedSummary(Option<OfflineSummary>);
+
+    #[derive(Resource, Default)]
+    struct ExploitEventLog(Vec<String>);
+
+    /// Makes a fresh game from `seed`, with one exploit running every starting algorithm against the known target.
+    fn make_app(seed: u64) -> App {
+        let mut app = App::new();
+        app
+            .add_plugins((InventoryPlugin, PlayerStatePlugin, OfflinePlugin))
+            .insert_resource(PlayerState::new(seed))
+            .init_resource::<FinishedSummary>()
+            .init_resource::<ExploitEventLog>()
+            .add_observer(|evt: On<OfflineCatchUpFinished>, mut finished: ResMut<FinishedSummary>| {
+                finished.0 = Some(evt.summary.clone());
+            })
+            .add_observer(|evt: On<ExploitEvent>, mut log: ResMut<ExploitEventLog>| {
+                log.0.push(format!("{:?}", evt.event()));
+            });
+
+        let mut player_state = app.world_mut().resource_mut::<PlayerState>();
+        let procedure = AlgorithmProcedure::from(&player_state.inventory.algorithms);
+        let script = Arc::new(Mutex::new(Script::new(ScriptId::Id(1), vec![Arc::new(Mutex::new(procedure))])));
+
+        let rng = player_state.rng_streams.next_stream();
+        let active_exploit = ActiveExploit::new(
+            corp_definition().build(&mut player_state.rng_streams.next_stream()),
+            script,
+            player_state.servers[0].clone(),
+            2_000_000.into(),
+            false,
+            rng,
+        );
+        player_state.active_exploits.push(Arc::new(Mutex::new(active_exploit)));
+
+        app
+    }
+
+    fn run_catch_up(app: &mut App, ticks: u32) {
+        app.insert_resource(OfflineCatchUp::new(TIME_BETWEEN_TICKS * ticks));
+        while app.world().contains_resource::<OfflineCatchUp>() {
+            app.update();
+        }
+    }
+
+    #[test]
+    fn catch_up_simulates_offline_time_across_frames() {
+        let mut app = make_app(1234);
+        let starting_credits = app.world().resource::<PlayerState>().credits;
+
+        // More ticks than fit in a single frame
+        let offline_ticks = MAX_CATCH_UP_TICKS_PER_FRAME + 10;
+        app.insert_resource(OfflineCatchUp::new(TIME_BETWEEN_TICKS * offline_ticks));
+
+        app.update();
+        assert!(app.world().contains_resource::<OfflineCatchUp>());
+        assert!(app.world().resource::<FinishedSummary>().0.is_none());
+
+        app.update();
+        assert!(!app.world().contains_resource::<OfflineCatchUp>());
+
+        let summary = app.world().resource::<FinishedSummary>().0.clone().unwrap();
+        let credits = app.world().resource::<PlayerState>().credits;
+        assert!(summary.credits_gained > 0);
+        assert_eq!(credits as i128, starting_credits as i128 + summary.credits_gained);
+    }
+
+    #[test]
+    fn same_seed_produces_identical_exploit_events() {
+        let mut first = make_app(1234);
+        let mut second = make_app(1234);
+        run_catch_up(&mut first, 400);
+        run_catch_up(&mut second, 400);
+
+        let first_log = &first.world().resource::<ExploitEventLog>().0;
+        let second_log = &second.world().resource::<ExploitEventLog>().0;
+        assert!(!first_log.is_empty());
+        assert_eq!(first_log, second_log);
+    }
+
+    #[test]
+    fn summary_counts_algorithms_lost_while_away() {
+        let mut app = make_app(1234);
+        let stolen = app.world().resource::<PlayerState>().inventory.algorithms[0].clone();
+        let lost_live = app.world().resource::<PlayerState>().inventory.algorithms[1].clone();
+
+        // Losses before catching up happened live, and aren't part of the summary
+        app.world_mut().trigger(AlgorithmStolen { algorithm: lost_live });
+
+        app.insert_resource(OfflineCatchUp::new(TIME_BETWEEN_TICKS * 2));
+        app.world_mut().trigger(AlgorithmStolen { algorithm: stolen });
+        while app.world().contains_resource::<OfflineCatchUp>() {
+            app.update();
+        }
+
+        let summary = app.world().resource::<FinishedSummary>().0.clone().unwrap();
+        assert_eq!(summary.algorithms_lost, 1);
+    }
+
+    #[test]
+    fn selling_during_catch_up_isnt_counted_as_lost() {
+        let mut app = make_app(1234);
+        let sold = app.world().resource::<PlayerState>().inventory.algorithms[0].clone();
+
+        app.insert_resource(OfflineCatchUp::new(TIME_BETWEEN_TICKS * 2));
+        app.world_mut().trigger(RequestSellAlgorithmEvent { algorithm: sold.clone() });
+        while app.world().contains_resource::<OfflineCatchUp>() {
+            app.update();
+        }
+
+        let inventory = &app.world().resource::<PlayerState>().inventory.algorithms;
+        assert!(!inventory.iter().any(|algorithm| Arc::ptr_eq(algorithm, &sold)));
+
+        let summary = app.world().resource::<FinishedSummary>().0.clone().unwrap();
+        assert_eq!(summary.algorithms_lost, 0);
+    }
+}
@@ -1,13 +1,9 @@
-use std::sync::{Arc, Mutex};
 use bevy::app::{App, FixedUpdate, Plugin};
-use crate::inventory::Inventory;
-use crate::server::{Server, ServerStatInstances};
-use crate::make_exploit_target;
+use bevy::prelude::{not, resource_exists, IntoScheduleConfigs};
+use crate::offline::state::OfflineCatchUp;
 use crate::player_state::state::PlayerState;
 use crate::player_state::systems::*;
-use crate::player_state::unlocks::PlayerUnlocks;
-use crate::tutorial::progression::TutorialProgression;
-use crate::ui::clock_speed::ClockSpeed;
+use crate::rng::run_seed;
 
 pub struct PlayerStatePlugin;
 
@@ -22,28 +18,13 @@ impl Plugin for PlayerStatePlugin {
             .add_observer(on_request_resume_exploit)
             .add_observer(on_modify_credits)
             .add_observer(on_request_purchase_unlock)
-            .add_systems(FixedUpdate, tick_active_exploits)
-            .insert_resource(PlayerState {
-                progression: TutorialProgression::None,
-                language_identifier: "en-US".parse().unwrap(),
-                credits: 87,
-                inventory: Inventory::new(),
-                servers: vec![
-                    Arc::new(Mutex::new(Server {
-                        name: "fe80:0070::".to_string(),
-                        threads: 2,
-                        clock_speed: ClockSpeed::new(2_000_000),
-                        stats: ServerStatInstances::new(),
-                        running_scripts: vec![],
-                    }))
-                ],
-                known_targets: vec![
-                    make_exploit_target(),
-                ],
-                active_exploits: vec![],
-                scripts: vec![],
-                last_tick: web_time::Instant::now(),
-                player_unlocks: PlayerUnlocks::empty()
-            });
+            .add_observer(on_request_purchase_server)
+            .add_observer(on_request_upgrade_server)
+            .add_observer(on_request_purchase_algorithm)
+            .add_observer(on_request_sell_algorithm)
+            .add_observer(on_request_assign_defensive_script)
+            .add_systems(FixedUpdate, tick_active_exploits.run_if(not(resource_exists::<OfflineCatchUp>)))
+            .add_systems(FixedUpdate, restock_algorithm_market)
+            .insert_resource(PlayerState::new(run_seed()));
     }
 }
\ No newline at end of file
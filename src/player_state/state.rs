@@ -1,14 +1,26 @@
+use std::time::Duration;
 use std::sync::{Arc, Mutex};
 use bevy::prelude::Resource;
 use fluent_templates::LanguageIdentifier;
 use crate::active_exploit::{ActiveExploit, ExploitTarget};
+use crate::algorithm::market::AlgorithmMarket;
 use crate::inventory::Inventory;
 use crate::l10n::Localizable;
 use crate::loc;
 use crate::player_state::unlocks::PlayerUnlocks;
+use crate::rng::RngStreams;
 use crate::script::Script;
-use crate::server::Server;
+use crate::server::{Server, ServerDefense, ServerStatInstances};
+use crate::server::rack::Rack;
+use crate::server::scheduler::CpuScheduler;
+use crate::server::uplink::Uplink;
+use crate::target::generator::TargetGenerator;
+use crate::target::tier::DifficultyTier;
 use crate::tutorial::progression::TutorialProgression;
+use crate::ui::clock_speed::ClockSpeed;
+
+/// How many tiers of targets above the hardest one the player has connected to are always on offer.
+pub const TARGET_LADDER_LOOKAHEAD: u32 = 2;
 
 #[derive(Resource)]
 pub struct PlayerState {
@@ -17,14 +29,107 @@ pub struct PlayerState {
     pub credits: u128,
     pub inventory: Inventory,
     pub servers: Vec<Arc<Mutex<Server>>>,
+
+    /// The racks every one of `servers` is installed in.
+    pub racks: Vec<Rack>,
     pub known_targets: Vec<Arc<Mutex<ExploitTarget>>>,
     pub active_exploits: Vec<Arc<Mutex<ActiveExploit>>>,
     pub scripts: Vec<Arc<Mutex<Script>>>,
+    pub algorithm_market: AlgorithmMarket,
     pub last_tick: web_time::Instant,
+
+    /// Time since the last tick that didn't add up to a whole tick, carried over into the next.
+    pub tick_remainder: Duration,
     pub player_unlocks: PlayerUnlocks,
+    pub rng_streams: RngStreams,
 }
 
 impl PlayerState {
+    /// Creates the state a brand new player starts with.
+    /// Everything random in the run, including this starting state, derives from `seed`.
+    pub fn new(seed: u64) -> PlayerState {
+        let mut rng_streams = RngStreams::new(seed);
+        let mut rng = rng_streams.next_stream();
+
+        let server = Arc::new(Mutex::new(Server {
+            name: "fe80:0070::".to_string(),
+            threads: 2,
+            clock_speed: ClockSpeed::new(2_000_000),
+            effective_clock_speed: ClockSpeed::new(2_000_000),
+            uplink: Uplink::default(),
+            stats: ServerStatInstances::new(),
+            running_scripts: vec![],
+            scheduler: CpuScheduler::new(),
+            busy_clock_speed: ClockSpeed::new(0),
+            defensive_script: None,
+            defense: ServerDefense::default(),
+        }));
+
+        let mut player_state = PlayerState {
+            progression: TutorialProgression::None,
+            language_identifier: "en-US".parse().unwrap(),
+            credits: 87,
+            inventory: Inventory::new(&mut rng),
+            servers: vec![server.clone()],
+            racks: vec![Rack::starter(vec![server])],
+            // Hand-written targets are added as their definitions load
+            known_targets: vec![],
+            active_exploits: vec![],
+            scripts: vec![],
+            algorithm_market: AlgorithmMarket::new(seed),
+            last_tick: web_time::Instant::now(),
+            tick_remainder: Duration::ZERO,
+            player_unlocks: PlayerUnlocks::empty(),
+            rng_streams,
+        };
+
+        player_state.extend_target_ladder(DifficultyTier::default());
+        player_state
+    }
+
+    /// Generates new targets until there are [TARGET_LADDER_LOOKAHEAD] tiers on offer above `reached`,
+    /// the hardest tier the player has connected to.
+    pub fn extend_target_ladder(&mut self, reached: DifficultyTier) {
+        let mut hardest_known = self
+            .known_targets
+            .iter()
+            .map(|target| target.lock().unwrap().tier)
+            .max()
+            .unwrap_or_default();
+
+        while hardest_known.0 < reached.0 + TARGET_LADDER_LOOKAHEAD {
+            hardest_known = hardest_known.next();
+
+            let mut rng = self.rng_streams.next_stream();
+            let target = TargetGenerator::generate(hardest_known, &mut rng).build(&mut rng);
+            self.known_targets.push(target);
+        }
+    }
+
+    /// How many of `server`'s threads go to the exploits it hosts.
+    pub fn exploit_threads(&self, server: &Arc<Mutex<Server>>) -> u32 {
+        self
+            .active_exploits
+            .iter()
+            .map(|active_exploit| active_exploit.lock().unwrap())
+            .filter(|active_exploit| Arc::ptr_eq(&active_exploit.hosting_server, server))
+            .map(|active_exploit| active_exploit.script.lock().unwrap().threads_required())
+            .sum()
+    }
+
+    /// How many of `server`'s threads are left for another script, once the exploits it hosts
+    /// and its defensive script have theirs.
+    pub fn available_threads(&self, server: &Arc<Mutex<Server>>) -> u32 {
+        let exploit_threads = self.exploit_threads(server);
+        let server = server.lock().unwrap();
+        let defense_threads = server
+            .defensive_script
+            .as_ref()
+            .map_or(0, |script| script.lock().unwrap().threads_required());
+
+        server.threads.saturating_sub(exploit_threads + defense_threads)
+    }
+
     pub fn localize_dyn(&self, localizable: &dyn Localizable) -> String {
         loc!(self, localizable.loc_key(), localizable.loc_args())
     }
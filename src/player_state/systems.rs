@@ -1,24 +1,39 @@
 use std::sync::{Arc, Mutex};
-use bevy::prelude::{Commands, On, ResMut};
+use std::time::Duration;
+use bevy::log::{info, warn};
+use bevy::prelude::{Commands, On, Res, ResMut, Time};
+use uuid::Uuid;
 use crate::event::request_start_exploit::RequestStartExploitEvent;
 use crate::player_state::state::PlayerState;
-use crate::{lock_and_clone, TIME_BETWEEN_TICKS};
-use crate::active_exploit::{ActiveExploit, ActiveExploitStatus};
-use crate::algorithm::effect::{AlgorithmEffect, application::AlgorithmEffectApplication, target::AlgorithmEffectTarget};
-use crate::algorithm::generator::AlgorithmGenerator;
-use crate::event::exploit_event::ExploitEvent;
+use crate::{lock_and_clone, whole_ticks, TIME_BETWEEN_TICKS};
+use crate::active_exploit::{ActiveExploit, ExploitTarget};
+use crate::algorithm::market::AlgorithmMarket;
+use crate::event::algorithm_stolen::AlgorithmStolen;
+use crate::event::exploit_started::ExploitStarted;
+use crate::event::request_assign_defensive_script::RequestAssignDefensiveScriptEvent;
 use crate::event::modify_credits::{ModificationSource, ModifyCreditsEvent};
 use crate::event::request_pause_exploit::RequestPauseExploitEvent;
+use crate::event::request_purchase_algorithm::RequestPurchaseAlgorithmEvent;
+use crate::event::request_purchase_server::RequestPurchaseServerEvent;
 use crate::event::request_purchase_unlock::RequestPurchaseUnlockEvent;
 use crate::event::request_restart_exploit::RequestRestartExploitEvent;
 use crate::event::request_resume_exploit::RequestResumeExploitEvent;
+use crate::event::request_sell_algorithm::RequestSellAlgorithmEvent;
 use crate::event::request_stop_exploit::RequestStopExploitEvent;
+use crate::event::request_upgrade_server::RequestUpgradeServerEvent;
 use crate::inventory::event::item_added::InventoryItemAdded;
+use crate::inventory::event::item_removed::InventoryItemRemoved;
 use crate::inventory::InventoryItem;
 use crate::player_state::unlocks::PlayerUnlock;
 use crate::script::event::script_created::ScriptCreatedEvent;
-use crate::server::{ServerStatInstance, ServerStatSource, ServerStatType, ServerStats};
+use crate::script::Script;
+use crate::server::Server;
+use crate::server::market::ServerMarket;
+use crate::server::rack::Rack;
+use crate::simulation::engine::SimulationEngine;
+use crate::simulation::event::SimulationEvent;
 use crate::tutorial::progression::TutorialProgression;
+use crate::ui::clock_speed::ClockSpeed;
 use crate::ui::state::UiState;
 use crate::ui::window::active_exploit::ActiveExploitWindow;
 
@@ -27,45 +42,49 @@ pub(crate) fn on_request_start_exploit(
     mut player_state: ResMut<PlayerState>,
     mut ui_state: ResMut<UiState>,
 ) -> bevy::prelude::Result {
-    let target = evt.target.clone();
-    let script = evt.script.clone();
-    let server = evt.server.clone();
-
-    // ZJ-TODO: validate server can accommodate another process
-    // ZJ-TODO: validate server can meets thread minimums
-    let running_scripts = lock_and_clone!(server, running_scripts);
-    for running_script in running_scripts {
+    if let Some(active_exploit) = start_exploit(&mut player_state, &evt.target, &evt.script, &evt.server) {
+        ui_state.active_exploit_windows.push(ActiveExploitWindow::new(active_exploit));
     }
 
-    // ZJ-TODO: actually implement way to shift resource allocation
-    //          for now, just time share equally
-    let target_server_name = lock_and_clone!(evt.server, name);
-    let new_total_processes = player_state
-        .active_exploits
-        .iter()
-        .filter(|exploit| lock_and_clone!(exploit, hosting_server, name) == target_server_name)
-        .count() as u64 + 1;
-
-    let new_clock_speed_per_process = *lock_and_clone!(server, clock_speed) / new_total_processes;
+    Ok(())
+}
 
-    for existing_exploit in &mut player_state.active_exploits {
-        let mut existing_exploit = existing_exploit.lock().unwrap();
-        existing_exploit.clock_allocation = new_clock_speed_per_process.into();
+/// Starts exploiting `target` with `script` from `server`, as long as the server has threads to spare for it.
+pub(crate) fn start_exploit(
+    player_state: &mut PlayerState,
+    target: &Arc<Mutex<ExploitTarget>>,
+    script: &Arc<Mutex<Script>>,
+    server: &Arc<Mutex<Server>>,
+) -> Option<Arc<Mutex<ActiveExploit>>> {
+    let threads_required = script.lock().unwrap().threads_required();
+    let available_threads = player_state.available_threads(server);
+    if threads_required > available_threads {
+        warn!(
+            "{} only has {available_threads} threads free, but {} needs {threads_required}; not starting exploit",
+            lock_and_clone!(server, name),
+            lock_and_clone!(script, id),
+        );
+        return None;
     }
 
     let auto_reconnect = player_state.player_unlocks.is_unlocked(PlayerUnlock::ExploitAutoReconnect);
+    let rng = player_state.rng_streams.next_stream();
     let active_exploit = Arc::new(Mutex::new(ActiveExploit::new(
-        target,
-        script,
-        server,
-        new_clock_speed_per_process.into(),
+        target.clone(),
+        script.clone(),
+        server.clone(),
+        ClockSpeed::new(0),
         auto_reconnect,
+        rng,
     )));
 
-    ui_state.active_exploit_windows.push(ActiveExploitWindow::new(active_exploit.clone()));
-    player_state.active_exploits.push(active_exploit);
+    player_state.active_exploits.push(active_exploit.clone());
 
-    Ok(())
+    let mut server = server.lock().unwrap();
+    server.scheduler.add(lock_and_clone!(active_exploit, id));
+    server.reschedule(&player_state.active_exploits);
+
+    Some(active_exploit)
 }
 
 pub(crate) fn on_request_stop_exploit(
@@ -73,9 +92,7 @@ pub(crate) fn on_request_stop_exploit(
     mut player_state: ResMut<PlayerState>,
     mut ui_state: ResMut<UiState>,
 ) -> bevy::prelude::Result {
-    player_state.active_exploits.retain(|exploit| {
-        lock_and_clone!(exploit, id) != evt.exploit_id
-    });
+    stop_exploit(&mut player_state, evt.exploit_id);
 
     ui_state.active_exploit_windows.retain(|window| {
         lock_and_clone!(window.active_exploit, id) != evt.exploit_id
@@ -84,6 +101,27 @@ pub(crate) fn on_request_stop_exploit(
     Ok(())
 }
 
+/// Stops the exploit `exploit_id`, handing its threads and clock speed back to the others on its server.
+pub(crate) fn stop_exploit(player_state: &mut PlayerState, exploit_id: Uuid) {
+    let Some(stopped) = player_state
+        .active_exploits
+        .iter()
+        .find(|exploit| lock_and_clone!(exploit, id) == exploit_id)
+        .cloned()
+    else {
+        return;
+    };
+
+    player_state.active_exploits.retain(|exploit| !Arc::ptr_eq(exploit, &stopped));
+    stopped.lock().unwrap().clear_modifications();
+
+    // Whatever the stopped exploit was using goes back to the others on its server
+    let hosting_server = lock_and_clone!(stopped, hosting_server);
+    let mut hosting_server = hosting_server.lock().unwrap();
+    hosting_server.scheduler.remove(exploit_id);
+    hosting_server.reschedule(&player_state.active_exploits);
+}
+
 pub(crate) fn on_request_pause_exploit(
     evt: On<RequestPauseExploitEvent>,
     mut player_state: ResMut<PlayerState>,
@@ -163,6 +201,124 @@ pub(crate) fn on_request_purchase_unlock(
     Ok(())
 }
 
+pub(crate) fn on_request_purchase_server(
+    _evt: On<RequestPurchaseServerEvent>,
+    mut player_state: ResMut<PlayerState>,
+) -> bevy::prelude::Result {
+    let credit_cost = ServerMarket::server_cost(player_state.servers.len());
+    if credit_cost > player_state.credits {
+        warn!("can't afford a server costing {credit_cost} credits");
+        return Ok(());
+    }
+
+    player_state.credits -= credit_cost;
+
+    let server = ServerMarket::build_server(player_state.servers.len());
+    install_server(&mut player_state, server);
+
+    Ok(())
+}
+
+/// Adds `server` to the player's servers, in the most recently installed rack.
+/// If that rack couldn't power it without underclocking, a new rack is installed for it instead,
+/// so a new server never slows down the ones already running.
+pub(crate) fn install_server(player_state: &mut PlayerState, server: Arc<Mutex<Server>>) {
+    player_state.servers.push(server.clone());
+
+    let rack_count = player_state.racks.len();
+    match player_state.racks.last_mut() {
+        Some(rack) if rack.can_power(&server.lock().unwrap()) => rack.servers.push(server),
+        _ => player_state.racks.push(Rack::numbered(rack_count + 1, vec![server])),
+    }
+}
+
+pub(crate) fn on_request_upgrade_server(
+    evt: On<RequestUpgradeServerEvent>,
+    mut player_state: ResMut<PlayerState>,
+) -> bevy::prelude::Result {
+    let mut server = evt.server.lock().unwrap();
+    let credit_cost = ServerMarket::upgrade_cost(&server, evt.upgrade);
+    if credit_cost > player_state.credits {
+        warn!("can't afford an upgrade costing {credit_cost} credits");
+        return Ok(());
+    }
+
+    player_state.credits -= credit_cost;
+    ServerMarket::upgrade(&mut server, evt.upgrade);
+
+    Ok(())
+}
+
+pub(crate) fn on_request_purchase_algorithm(
+    evt: On<RequestPurchaseAlgorithmEvent>,
+    mut commands: Commands,
+    mut player_state: ResMut<PlayerState>,
+) -> bevy::prelude::Result {
+    let credit_cost = AlgorithmMarket::price(&evt.algorithm.lock().unwrap());
+    if credit_cost > player_state.credits {
+        warn!("can't afford an algorithm costing {credit_cost} credits");
+        return Ok(());
+    }
+
+    if !player_state.algorithm_market.take(&evt.algorithm) {
+        warn!("algorithm {:?} isn't on offer; not purchasing", lock_and_clone!(evt.algorithm, id));
+        return Ok(());
+    }
+
+    player_state.credits -= credit_cost;
+    commands.trigger(InventoryItemAdded {
+        item: InventoryItem::Algorithm(evt.algorithm.clone()),
+    });
+
+    Ok(())
+}
+
+pub(crate) fn on_request_sell_algorithm(
+    evt: On<RequestSellAlgorithmEvent>,
+    mut commands: Commands,
+    mut player_state: ResMut<PlayerState>,
+) -> bevy::prelude::Result {
+    // Algorithms that are part of a script have already left the inventory
+    if !player_state.inventory.algorithms.iter().any(|algorithm| Arc::ptr_eq(algorithm, &evt.algorithm)) {
+        warn!("algorithm {:?} isn't in the inventory; not selling", lock_and_clone!(evt.algorithm, id));
+        return Ok(());
+    }
+
+    let credit_value = AlgorithmMarket::sell_price(&evt.algorithm.lock().unwrap());
+    player_state.credits = player_state.credits.saturating_add(credit_value);
+    commands.trigger(InventoryItemRemoved {
+        item: InventoryItem::Algorithm(evt.algorithm.clone()),
+    });
+
+    Ok(())
+}
+
+pub(crate) fn on_request_assign_defensive_script(
+    evt: On<RequestAssignDefensiveScriptEvent>,
+    player_state: Res<PlayerState>,
+) -> bevy::prelude::Result {
+    let threads_required = evt.script.as_ref().map_or(0, |script| script.lock().unwrap().threads_required());
+    let exploit_threads = player_state.exploit_threads(&evt.server);
+    let mut server = evt.server.lock().unwrap();
+
+    // The old defensive script's threads are freed up by replacing it
+    let available_threads = server.threads.saturating_sub(exploit_threads);
+    if threads_required > available_threads {
+        warn!(
+            "{} only has {available_threads} threads free, but {} needs {threads_required}; not assigning defensive script",
+            server.name,
+            lock_and_clone!(evt.script.as_ref().unwrap(), id),
+        );
+        return Ok(());
+    }
+
+    // The defensive script's share of the clock comes out of the hosted exploits' shares
+    server.set_defensive_script(evt.script.clone());
+    server.reschedule(&player_state.active_exploits);
+
+    Ok(())
+}
+
 pub(crate) fn on_script_created(
     evt: On<ScriptCreatedEvent>,
     mut player_state: ResMut<PlayerState>,
@@ -185,181 +341,257 @@ pub(crate) fn tick_active_exploits(
 
     player_state.last_tick = web_time::Instant::now();
 
-    let mut pending_effects = vec![];
-    for active_exploit in &player_state.active_exploits {
-        let (new_host_effects, new_target_effects) = {
-            let mut active_exploit = active_exploit.lock().unwrap();
-            // ZJ-TODO: compare allocated speed vs server's current capacity
-            //          this should probably be refactored
-            let server_speed = *active_exploit.clock_allocation;
-            let ticks_since_last = (server_speed as f64 * time_since_last_tick.as_secs_f64()).floor() as u64;
+    advance_active_exploits(&mut commands, &mut player_state, time_since_last_tick);
+}
 
-            let target_server_speed = *active_exploit.target.lock().unwrap().server.lock().unwrap().clock_speed;
-            let target_ticks_since_last = (target_server_speed as f64 * time_since_last_tick.as_secs_f64()).floor() as u64;
+pub(crate) fn restock_algorithm_market(
+    time: Res<Time>,
+    mut player_state: ResMut<PlayerState>,
+) {
+    player_state.algorithm_market.advance(time.delta());
+}
 
-            active_exploit.tick(&mut commands, ticks_since_last, target_ticks_since_last)
-        };
+/// Advances every active exploit by `elapsed` of wall time, and passes on everything that happened as events.
+pub(crate) fn advance_active_exploits(
+    commands: &mut Commands,
+    player_state: &mut PlayerState,
+    elapsed: Duration,
+) {
+    // Timers count whole ticks; time that doesn't make up a whole one counts towards the next call
+    let (game_ticks, tick_remainder) = whole_ticks(player_state.tick_remainder + elapsed);
+    player_state.tick_remainder = tick_remainder;
+
+    // Power draw shifts as exploits start and stop, and bandwidth as servers are installed,
+    // so racks re-balance before every tick
+    for server in &player_state.servers {
+        server.lock().unwrap().measure_load(&player_state.active_exploits);
+    }
 
-        pending_effects.push((active_exploit.clone(), new_host_effects));
-        pending_effects.push((active_exploit.clone(), new_target_effects));
+    for rack in &player_state.racks {
+        rack.underclock();
+        rack.share_bandwidth();
     }
 
-    for (active_exploit, pending_effects) in pending_effects {
-        for pending_effect in pending_effects {
-            process_algorithm_effect_application(
-                &mut commands,
-                pending_effect,
-                active_exploit.clone(),
-            );
-        }
+    for server in &player_state.servers {
+        server.lock().unwrap().reschedule(&player_state.active_exploits);
     }
 
-    for active_exploit in &player_state.active_exploits {
-        let active_exploit = active_exploit.lock().unwrap();
-        if matches!(active_exploit.status(), ActiveExploitStatus::Disconnected) {
-            if *active_exploit.auto_reconnect.lock().unwrap() {
-                commands.trigger(RequestRestartExploitEvent { exploit_id: active_exploit.id })
+    // Targets refill their credit pools whether or not anyone is exploiting them
+    let events = SimulationEngine::new(&player_state.active_exploits)
+        .with_targets(&player_state.known_targets)
+        .tick(elapsed, game_ticks);
+
+    for event in events {
+        match event {
+            SimulationEvent::Connected { exploit_id } => {
+                // ZJ-TODO: this should probably be ExploitActive
+                //          as we "start" before we're connected
+                commands.trigger(ExploitStarted { exploit_id });
+            }
+            SimulationEvent::Disconnected { exploit_id } => {
+                info!("exploit {exploit_id} was disconnected by its target");
+            }
+            SimulationEvent::EffectApplied(exploit_event) => {
+                commands.trigger(exploit_event);
+            }
+            SimulationEvent::CreditsSiphoned { script_id, credits } => {
+                commands.trigger(ModifyCreditsEvent {
+                    credits,
+                    source: ModificationSource::Script(script_id),
+                });
+            }
+            SimulationEvent::AlgorithmExfiltrated { algorithm } => {
+                commands.trigger(InventoryItemAdded {
+                    item: InventoryItem::Algorithm(algorithm),
+                });
+            }
+            SimulationEvent::AlgorithmStolen { pick } => {
+                let algorithms = &player_state.inventory.algorithms;
+                if algorithms.is_empty() {
+                    continue;
+                }
+
+                let algorithm = algorithms[(pick % algorithms.len() as u64) as usize].clone();
+                info!("a target exfiltrated algorithm {:?}", lock_and_clone!(algorithm, id));
+                commands.trigger(InventoryItemRemoved {
+                    item: InventoryItem::Algorithm(algorithm.clone()),
+                });
+                commands.trigger(AlgorithmStolen { algorithm });
             }
         }
     }
 }
 
-pub(crate) fn process_algorithm_effect_application(
-    commands: &mut Commands,
-    application: AlgorithmEffectApplication,
-    active_exploit: Arc<Mutex<ActiveExploit>>,
-) {
-    let mut active_exploit = active_exploit.lock().unwrap();
-    let from_player_server = {
-        let host_server_name = lock_and_clone!(active_exploit.hosting_server, name);
-        let application_server_name = lock_and_clone!(application.host_server, name);
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::World;
+    use crate::algorithm::market::STOCK_SIZE;
+    use crate::algorithm::procedure::AlgorithmProcedure;
+    use crate::script::id::ScriptId;
+    use crate::server::market::ServerUpgrade;
+    use crate::target::definition::corp_definition;
+    use crate::TICKS_PER_SECOND;
+    use super::*;
+
+    /// A script with one single-threaded procedure per thread of `threads`.
+    fn script_needing(player_state: &PlayerState, threads: u32) -> Arc<Mutex<Script>> {
+        let procedures = (0..threads)
+            .map(|_| Arc::new(Mutex::new(AlgorithmProcedure::from(&player_state.inventory.algorithms[..1]))))
+            .collect();
+
+        Arc::new(Mutex::new(Script::new(ScriptId::Id(1), procedures)))
+    }
 
-        host_server_name == application_server_name
-    };
-    match application.effect {
-        AlgorithmEffect::Terminate { potency }  => {
-            let value = potency.make_value();
-            let old_health = active_exploit.connection_current_health.lock().unwrap().clone();
-            let new_health = old_health.saturating_sub(value.abs() as u32);
-            *active_exploit.connection_current_health.lock().unwrap() = new_health;
-
-            if new_health == 0 {
-                active_exploit.stop_execution();
-            }
-        }
-        AlgorithmEffect::Siphon { potency } => {
-            let value = potency.make_value();
-            let target_server = application.target_server.lock().unwrap();
-            let target_stats = &target_server.stats;
-
-            let target_defense = target_stats.value_of(ServerStatType::SiphonResist);
-            let siphon_value = (value - target_defense).max(0) as i64;
-
-            commands.trigger(ModifyCreditsEvent {
-                credits: siphon_value,
-                source: ModificationSource::Script(application.script.lock().unwrap().id.clone()),
-            });
-
-            commands.trigger(ExploitEvent {
-                active_exploit_id: active_exploit.id,
-                from_player_server,
-                algorithm_effect: AlgorithmEffect::Siphon { potency },
-                potency_roll: Some(value),
-                value_after_modification: Some(siphon_value as i32),
-            });
-        }
-        AlgorithmEffect::Exfil { potency } => {
-            let value = potency.make_value();
-            let target_server = application.target_server.lock().unwrap();
-            let target_stats = &target_server.stats;
-            let target_defense = target_stats.value_of(ServerStatType::ExfilResist);
-            let exfil_value = value - target_defense;
-            if exfil_value <= 0 {
-                return;
-            }
+    #[test]
+    fn exploits_only_start_on_free_threads() {
+        let mut player_state = PlayerState::new(1234);
+        let target = corp_definition().build(&mut player_state.rng_streams.next_stream());
+        let server = player_state.servers[0].clone();
+        let script = script_needing(&player_state, server.lock().unwrap().threads);
 
-            // ZJ-TODO: pass potency to generator
-            let algorithm = AlgorithmGenerator::generate();
+        let first = start_exploit(&mut player_state, &target, &script, &server).unwrap();
+        assert!(start_exploit(&mut player_state, &target, &script, &server).is_none());
+        assert_eq!(player_state.active_exploits.len(), 1);
 
-            commands.trigger(InventoryItemAdded {
-                item: InventoryItem::Algorithm(algorithm),
-            });
+        let first_id = lock_and_clone!(first, id);
+        stop_exploit(&mut player_state, first_id);
+        assert!(start_exploit(&mut player_state, &target, &script, &server).is_some());
+    }
 
-            commands.trigger(ExploitEvent {
-                active_exploit_id: active_exploit.id,
-                from_player_server,
-                algorithm_effect: AlgorithmEffect::Exfil { potency },
-                potency_roll: Some(value),
-                value_after_modification: Some(exfil_value),
-            });
+    /// The total effective clock speed of every server, once the racks have balanced their power.
+    fn total_effective_clock_speed(player_state: &PlayerState) -> u64 {
+        for rack in &player_state.racks {
+            rack.underclock();
         }
-        AlgorithmEffect::Modify { target, stat, potency } => {
-            let server = match target {
-                AlgorithmEffectTarget::SelfServer => application.host_server,
-                AlgorithmEffectTarget::TargetServer => application.target_server,
-            };
-
-            let script_id = application.script.lock().unwrap().id.clone();
-
-            let potency_roll = potency.make_value();
-
-            let _purged_stats = server.lock().unwrap().stats.apply_and_purge(
-                ServerStatInstance::new(
-                    ServerStatSource::Script(script_id),
-                    stat.to_owned(),
-                    potency_roll
-                )
-            );
-
-            let new_value = server.lock().unwrap().stats.value_of(stat.to_owned());
-
-            commands.trigger(ExploitEvent {
-                active_exploit_id: active_exploit.id,
-                from_player_server,
-                algorithm_effect: AlgorithmEffect::Modify { target, stat, potency },
-                potency_roll: Some(potency_roll),
-                value_after_modification: Some(new_value),
-            });
+
+        player_state.servers.iter().map(|server| *server.lock().unwrap().effective_clock_speed).sum()
+    }
+
+    #[test]
+    fn defensive_scripts_take_threads_from_exploits() {
+        let mut player_state = PlayerState::new(1234);
+        let target = corp_definition().build(&mut player_state.rng_streams.next_stream());
+        let server = player_state.servers[0].clone();
+        let threads = server.lock().unwrap().threads;
+        server.lock().unwrap().set_defensive_script(Some(script_needing(&player_state, 1)));
+
+        let (too_big, fits) = (script_needing(&player_state, threads), script_needing(&player_state, threads - 1));
+        assert!(start_exploit(&mut player_state, &target, &too_big, &server).is_none());
+        assert!(start_exploit(&mut player_state, &target, &fits, &server).is_some());
+    }
+
+    #[test]
+    fn buying_servers_never_slows_the_others_down() {
+        let mut player_state = PlayerState::new(1234);
+        let target = corp_definition().build(&mut player_state.rng_streams.next_stream());
+        let script = script_needing(&player_state, 1);
+
+        for _ in 0..4 {
+            // Every server is busy, so any server sharing an overloaded rack would underclock
+            for server in player_state.servers.clone() {
+                start_exploit(&mut player_state, &target, &script, &server);
+            }
+
+            let before = total_effective_clock_speed(&player_state);
+            let server = ServerMarket::build_server(player_state.servers.len());
+            install_server(&mut player_state, server.clone());
+            start_exploit(&mut player_state, &target, &script, &server);
+
+            assert!(total_effective_clock_speed(&player_state) >= before);
         }
-        AlgorithmEffect::Purge { target, stat, potency } => {
-            let (server, is_self) = match target {
-                AlgorithmEffectTarget::SelfServer => (application.host_server, true),
-                AlgorithmEffectTarget::TargetServer => (application.target_server, false),
-            };
 
+        assert!(player_state.racks.len() > 1);
+    }
+
+    #[test]
+    fn hardware_is_charged_at_the_market_price() {
+        let mut world = World::new();
+        world.add_observer(on_request_purchase_server);
+        world.add_observer(on_request_upgrade_server);
+
+        let player_state = PlayerState::new(1234);
+        let server = player_state.servers[0].clone();
+        let upgrade_cost = ServerMarket::upgrade_cost(&server.lock().unwrap(), ServerUpgrade::Threads);
+        let server_cost = ServerMarket::server_cost(player_state.servers.len());
+        world.insert_resource(player_state);
+
+        // Only enough for the upgrade, so the server purchase is refused
+        world.resource_mut::<PlayerState>().credits = upgrade_cost;
+        world.trigger(RequestPurchaseServerEvent);
+        world.trigger(RequestUpgradeServerEvent { server: server.clone(), upgrade: ServerUpgrade::Threads });
+        assert_eq!(world.resource::<PlayerState>().servers.len(), 1);
+        assert_eq!(server.lock().unwrap().threads, 3);
+        assert_eq!(world.resource::<PlayerState>().credits, 0);
+
+        world.resource_mut::<PlayerState>().credits = server_cost;
+        world.trigger(RequestPurchaseServerEvent);
+        assert_eq!(world.resource::<PlayerState>().servers.len(), 2);
+        assert_eq!(world.resource::<PlayerState>().credits, 0);
+    }
+
+    #[test]
+    fn algorithms_trade_at_the_market_price() {
+        let mut world = World::new();
+        world.add_observer(on_request_purchase_algorithm);
+        world.add_observer(on_request_sell_algorithm);
+
+        let mut player_state = PlayerState::new(1234);
+        player_state.algorithm_market.advance(Duration::ZERO);
+        let stocked = player_state.algorithm_market.stock[0].clone();
+        let owned = player_state.inventory.algorithms[0].clone();
+        let credit_cost = AlgorithmMarket::price(&stocked.lock().unwrap());
+        let credit_value = AlgorithmMarket::sell_price(&owned.lock().unwrap());
+        player_state.credits = credit_cost;
+        world.insert_resource(player_state);
+
+        world.trigger(RequestPurchaseAlgorithmEvent { algorithm: stocked });
+        assert_eq!(world.resource::<PlayerState>().credits, 0);
+        assert_eq!(world.resource::<PlayerState>().algorithm_market.stock.len(), STOCK_SIZE - 1);
+
+        world.trigger(RequestSellAlgorithmEvent { algorithm: owned });
+        assert_eq!(world.resource::<PlayerState>().credits, credit_value);
+    }
+
+    #[test]
+    fn defensive_scripts_take_clock_speed_from_exploits() {
+        let mut player_state = PlayerState::new(1234);
+        let target = corp_definition().build(&mut player_state.rng_streams.next_stream());
+        let server = player_state.servers[0].clone();
+        let script = script_needing(&player_state, 1);
+        let active_exploit = start_exploit(&mut player_state, &target, &script, &server).unwrap();
+        let capacity = *server.lock().unwrap().effective_clock_speed;
+        assert_eq!(*lock_and_clone!(active_exploit, clock_allocation), capacity);
+
+        {
             let mut server = server.lock().unwrap();
-            let script_id = active_exploit.script.lock().unwrap().id.clone();
+            server.set_defensive_script(Some(script.clone()));
+            server.reschedule(&player_state.active_exploits);
+        }
 
-            let server_stat_value = server.stats.modification_of(stat.to_owned());
-            if server_stat_value >= 0 && is_self || server_stat_value <= 0 && !is_self {
-                return;
-            }
+        // The defense gets a share of the same clock, rather than running on top of it
+        let exploit_allocation = *lock_and_clone!(active_exploit, clock_allocation);
+        let defense_allocation = *server.lock().unwrap().defense.clock_allocation;
+        assert!(defense_allocation > 0);
+        assert_eq!(exploit_allocation + defense_allocation, capacity);
+    }
 
-            let potency_roll = {
-                if is_self {
-                    potency.make_value().max(0)
-                } else {
-                    potency.make_value().min(0)
-                }
-            };
-
-            let _purged_stats = server.stats.apply_and_purge(
-                ServerStatInstance::new(
-                    ServerStatSource::Script(script_id),
-                    stat.to_owned(),
-                    potency_roll
-                )
-            );
-
-            let new_value = server.stats.value_of(stat.to_owned());
-
-            commands.trigger(ExploitEvent {
-                active_exploit_id: active_exploit.id,
-                from_player_server,
-                algorithm_effect: AlgorithmEffect::Purge { target, stat, potency },
-                potency_roll: Some(potency_roll),
-                value_after_modification: Some(new_value),
-            });
+    #[test]
+    fn credit_pools_refill_by_time_not_updates() {
+        let mut world = World::new();
+        let mut player_state = PlayerState::new(1234);
+        let target = corp_definition().build(&mut player_state.rng_streams.next_stream());
+        let max_credits = target.lock().unwrap().max_credits;
+        target.lock().unwrap().siphon_credits(max_credits);
+        player_state.known_targets.push(target.clone());
+
+        // Twice as many updates as there are ticks in a second, but only half a tick each
+        let mut commands = world.commands();
+        for _ in 1..2 * TICKS_PER_SECOND {
+            advance_active_exploits(&mut commands, &mut player_state, TIME_BETWEEN_TICKS / 2);
         }
+        assert_eq!(target.lock().unwrap().credits, 0);
+
+        advance_active_exploits(&mut commands, &mut player_state, TIME_BETWEEN_TICKS / 2);
+        assert!(target.lock().unwrap().credits > 0);
     }
-}
\ No newline at end of file
+}
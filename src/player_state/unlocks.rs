@@ -1,13 +1,21 @@
+use serde::{Deserialize, Serialize};
 use crate::l10n::Localizable;
 use crate::l10n::message_id::MessageId;
 
 #[repr(u8)] // this means we can only have 256 unlocks
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum PlayerUnlock {
     ExploitAutoReconnect,
 }
 
 impl PlayerUnlock {
+    /// Returns every unlock, in declaration order.
+    pub fn all() -> Vec<PlayerUnlock> {
+        vec![
+            PlayerUnlock::ExploitAutoReconnect,
+        ]
+    }
+
     /// Returns an ordered vector of market-purchasable unlocks and their credit costs.
     pub fn market_unlockable_unlocks() -> Vec<(PlayerUnlock, u128)> {
         vec![
@@ -49,6 +57,14 @@ impl PlayerUnlocks {
         }
     }
 
+    /// Returns every unlock the player has.
+    pub fn unlocked(&self) -> Vec<PlayerUnlock> {
+        PlayerUnlock::all()
+            .into_iter()
+            .filter(|unlock| self.is_unlocked(*unlock))
+            .collect()
+    }
+
     pub fn unlock(&mut self, unlock: PlayerUnlock) {
         let bit_mask = (1 << unlock as u8) as u128;
         self.unlock_bitfield |= bit_mask;
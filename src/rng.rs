@@ -0,0 +1,95 @@
+use bevy::log::info;
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::{Rng, RngExt, SeedableRng};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The generator behind every random roll in the simulation.
+pub type SimulationRng = Xoshiro256PlusPlus;
+
+/// Hands out reproducible [SimulationRng] substreams derived from a single run seed.
+/// Each consumer (eg. an active exploit) owns its own substream, so the rolls it sees don't depend
+/// on how many rolls anything else has made in the meantime.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RngStreams {
+    seed: u64,
+    next_stream: u64,
+}
+
+impl RngStreams {
+    pub fn new(seed: u64) -> RngStreams {
+        RngStreams {
+            seed,
+            next_stream: 0,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the next substream. The nth substream of a given seed is always the same.
+    pub fn next_stream(&mut self) -> SimulationRng {
+        let stream = self.next_stream;
+        self.next_stream += 1;
+
+        // Multiplying by an odd constant keeps every stream of a seed distinct,
+        // and seed_from_u64 scrambles the result so neighbouring streams aren't correlated
+        SimulationRng::seed_from_u64(self.seed ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+}
+
+/// Picks the seed for a new game, logging it so the run can be reproduced.
+/// On native builds, setting `SERVERLOOTER_SEED` overrides the seed.
+pub fn run_seed() -> u64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    let seed = std::env::var("SERVERLOOTER_SEED")
+        .ok()
+        .and_then(|seed| seed.parse().ok())
+        .unwrap_or_else(rand::random);
+
+    #[cfg(target_arch = "wasm32")]
+    let seed = rand::random();
+
+    info!("run seed: {seed}");
+    seed
+}
+
+/// Generates a v4 uuid from `rng`, rather than from the system's entropy.
+pub fn random_uuid(rng: &mut impl Rng) -> Uuid {
+    uuid::Builder::from_random_bytes(rng.random()).into_uuid()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streams_are_reproducible() {
+        let mut first = RngStreams::new(1234);
+        let mut second = RngStreams::new(1234);
+
+        for _ in 0..4 {
+            let mut first_stream = first.next_stream();
+            let mut second_stream = second.next_stream();
+            assert_eq!(first_stream.random::<[u64; 4]>(), second_stream.random::<[u64; 4]>());
+        }
+    }
+
+    #[test]
+    fn streams_are_independent() {
+        let mut streams = RngStreams::new(1234);
+        let first = streams.next_stream().random::<u64>();
+        let second = streams.next_stream().random::<u64>();
+        assert_ne!(first, second);
+
+        let other_seed = RngStreams::new(4321).next_stream().random::<u64>();
+        assert_ne!(first, other_seed);
+    }
+
+    #[test]
+    fn random_uuid_is_v4() {
+        let uuid = random_uuid(&mut RngStreams::new(0).next_stream());
+        assert_eq!(uuid.get_version_num(), 4);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::save::SaveError;
+
+/// Somewhere a save can be persisted between sessions.
+pub trait SaveBackend: Send + Sync {
+    /// Reads the most recent save, or None if nothing has been saved yet.
+    fn read(&self) -> Result<Option<String>, SaveError>;
+
+    /// Replaces the most recent save with `contents`.
+    fn write(&self, contents: &str) -> Result<(), SaveError>;
+}
+
+/// Returns the save backend appropriate for the platform we're running on.
+pub fn platform_backend() -> Box<dyn SaveBackend> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        Box::new(FileSaveBackend::new("saves/save.json"))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    {
+        Box::new(LocalStorageSaveBackend::new("serverlooter_save"))
+    }
+}
+
+/// Saves to a file on disk.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct FileSaveBackend {
+    path: std::path::PathBuf,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FileSaveBackend {
+    pub fn new(path: impl Into<std::path::PathBuf>) -> FileSaveBackend {
+        FileSaveBackend { path: path.into() }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl SaveBackend for FileSaveBackend {
+    fn read(&self) -> Result<Option<String>, SaveError> {
+        match std::fs::read_to_string(&self.path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    fn write(&self, contents: &str) -> Result<(), SaveError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        // Write next to the real save first, so a crash mid-write can't corrupt the existing save
+        let temp_path = self.path.with_extension("tmp");
+        std::fs::write(&temp_path, contents)?;
+        std::fs::rename(&temp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+/// Saves to the browser's localStorage.
+#[cfg(target_arch = "wasm32")]
+pub struct LocalStorageSaveBackend {
+    key: String,
+}
+
+#[cfg(target_arch = "wasm32")]
+impl LocalStorageSaveBackend {
+    pub fn new(key: impl Into<String>) -> LocalStorageSaveBackend {
+        LocalStorageSaveBackend { key: key.into() }
+    }
+
+    fn storage() -> Result<web_sys::Storage, SaveError> {
+        web_sys::window()
+            .ok_or_else(|| SaveError::Storage("no window".to_string()))?
+            .local_storage()
+            .map_err(|err| SaveError::Storage(format!("{err:?}")))?
+            .ok_or_else(|| SaveError::Storage("localStorage unavailable".to_string()))
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl SaveBackend for LocalStorageSaveBackend {
+    fn read(&self) -> Result<Option<String>, SaveError> {
+        LocalStorageSaveBackend::storage()?
+            .get_item(&self.key)
+            .map_err(|err| SaveError::Storage(format!("{err:?}")))
+    }
+
+    fn write(&self, contents: &str) -> Result<(), SaveError> {
+        LocalStorageSaveBackend::storage()?
+            .set_item(&self.key, contents)
+            .map_err(|err| SaveError::Storage(format!("{err:?}")))
+    }
+}
@@ -0,0 +1,823 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::active_exploit::{ActiveExploit, ExploitTarget};
+use crate::algorithm::algorithm::Algorithm;
+use crate::algorithm::effect::AlgorithmEffect;
+use crate::algorithm::id::AlgorithmId;
+use crate::algorithm::market::AlgorithmMarket;
+use crate::algorithm::rarity::AlgorithmRarity;
+use crate::algorithm::procedure::{AlgorithmProcedure, ProcedureError};
+use crate::inventory::Inventory;
+use crate::player_state::state::PlayerState;
+use crate::player_state::unlocks::{PlayerUnlock, PlayerUnlocks};
+use crate::rng::RngStreams;
+use crate::save::{migration, SaveError, SAVE_FORMAT_VERSION};
+use crate::script::id::ScriptId;
+use crate::script::Script;
+use crate::server::{Server, ServerDefense, ServerStatInstance, ServerStatInstances};
+use crate::server::rack::Rack;
+use crate::server::scheduler::{Allocation, CpuScheduler, DEFENSE_ID};
+use crate::server::uplink::Uplink;
+use crate::target::tier::DifficultyTier;
+use crate::tutorial::progression::TutorialProgression;
+use crate::ui::clock_speed::ClockSpeed;
+
+/// Index of an object within its table in a [SaveFile].
+/// Objects shared between several owners (eg. an algorithm used by two scripts) are only
+/// stored once, and every owner refers to them by the same id.
+pub type SaveId = u32;
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+
+    /// Seconds since the unix epoch when this save was written.
+    pub saved_at: u64,
+
+    pub player: PlayerSave,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PlayerSave {
+    pub progression: TutorialProgression,
+    pub language_identifier: String,
+    #[serde(with = "u128_string")]
+    pub credits: u128,
+
+    /// Unlocks are saved by name, as their bit positions change whenever [PlayerUnlock] is reordered.
+    pub unlocks: Vec<PlayerUnlock>,
+    pub rng_streams: RngStreams,
+
+    pub algorithms: Vec<AlgorithmSave>,
+    pub scripts: Vec<ScriptSave>,
+    pub servers: Vec<ServerSave>,
+    pub targets: Vec<ExploitTargetSave>,
+
+    pub inventory: Vec<SaveId>,
+    pub player_scripts: Vec<SaveId>,
+    pub player_servers: Vec<SaveId>,
+    pub racks: Vec<RackSave>,
+    pub known_targets: Vec<SaveId>,
+    pub active_exploits: Vec<ActiveExploitSave>,
+    pub algorithm_market: AlgorithmMarketSave,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AlgorithmSave {
+    pub id: AlgorithmId,
+    pub rarity: AlgorithmRarity,
+    pub instruction_count: u64,
+    pub instruction_effects: Vec<(u64, Vec<AlgorithmEffect>)>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ScriptSave {
+    pub id: ScriptId,
+
+    /// Each procedure is the graph of algorithms it executes.
+    pub procedures: Vec<Vec<ProcedureNodeSave>>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ProcedureNodeSave {
+    pub algorithm: SaveId,
+
+    /// Indices of the nodes within the same procedure that must finish first.
+    pub predecessors: Vec<usize>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ServerSave {
+    pub name: String,
+    pub threads: u32,
+    pub clock_speed: u64,
+    pub stats: Vec<ServerStatInstance>,
+
+    /// Every exploit the server's scheduler has a share for, in schedule order.
+    pub scheduled: Vec<(Uuid, Allocation)>,
+
+    pub defensive_script: Option<SaveId>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct AlgorithmMarketSave {
+    pub stock: Vec<SaveId>,
+    pub time_until_restock_ms: u64,
+    pub rng_streams: RngStreams,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RackSave {
+    pub name: String,
+    pub power_capacity: u32,
+    pub bandwidth: u32,
+    pub servers: Vec<SaveId>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExploitTargetSave {
+    pub id: Uuid,
+    pub definition: Option<String>,
+    pub server: SaveId,
+    pub script: SaveId,
+    pub connection_health: u32,
+    pub credits: u64,
+    pub max_credits: u64,
+    pub tier: DifficultyTier,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ActiveExploitSave {
+    pub id: Uuid,
+    pub target: SaveId,
+    pub script: SaveId,
+    pub hosting_server: SaveId,
+    pub clock_allocation: u64,
+    pub connection_max_health: u32,
+    pub connection_current_health: u32,
+    pub auto_reconnect: bool,
+    pub has_connected: bool,
+    pub reconnect_delay: u32,
+}
+
+/// Deduplicates shared objects while capturing, handing out a stable [SaveId] per object.
+struct SaveTable<T> {
+    objects: Vec<Arc<Mutex<T>>>,
+}
+
+impl<T> SaveTable<T> {
+    fn new() -> SaveTable<T> {
+        SaveTable { objects: vec![] }
+    }
+
+    /// Returns the id of `object`, adding it to the table if it hasn't been seen before.
+    /// The second value is true if the object was newly added.
+    fn insert(&mut self, object: &Arc<Mutex<T>>) -> (SaveId, bool) {
+        match self.objects.iter().position(|existing| Arc::ptr_eq(existing, object)) {
+            Some(index) => (index as SaveId, false),
+            None => {
+                self.objects.push(object.clone());
+                ((self.objects.len() - 1) as SaveId, true)
+            }
+        }
+    }
+}
+
+/// Looks up a previously restored object by id.
+fn resolve<T>(objects: &[Arc<Mutex<T>>], kind: &'static str, id: SaveId) -> Result<Arc<Mutex<T>>, SaveError> {
+    objects
+        .get(id as usize)
+        .cloned()
+        .ok_or(SaveError::InvalidReference { kind, id })
+}
+
+struct Capture {
+    algorithms: SaveTable<Algorithm>,
+    algorithm_saves: Vec<AlgorithmSave>,
+    scripts: SaveTable<Script>,
+    script_saves: Vec<ScriptSave>,
+    servers: SaveTable<Server>,
+    server_saves: Vec<ServerSave>,
+    targets: SaveTable<ExploitTarget>,
+    target_saves: Vec<ExploitTargetSave>,
+}
+
+impl Capture {
+    fn algorithm(&mut self, algorithm: &Arc<Mutex<Algorithm>>) -> SaveId {
+        let (id, is_new) = self.algorithms.insert(algorithm);
+        if is_new {
+            let algorithm = algorithm.lock().unwrap();
+            self.algorithm_saves.push(AlgorithmSave {
+                id: algorithm.id.clone(),
+                rarity: algorithm.rarity,
+                instruction_count: *algorithm.instruction_count,
+                instruction_effects: algorithm
+                    .instruction_effects
+                    .iter()
+                    .map(|(instruction, effects)| (**instruction, effects.clone()))
+                    .collect(),
+            });
+        }
+
+        id
+    }
+
+    fn script(&mut self, script: &Arc<Mutex<Script>>) -> SaveId {
+        let (id, is_new) = self.scripts.insert(script);
+        if is_new {
+            let script = script.lock().unwrap().clone();
+            // Reserve our slot before visiting algorithms, as ids are table indices
+            self.script_saves.push(ScriptSave { id: script.id.clone(), procedures: vec![] });
+
+            let procedures = script
+                .procedures
+                .iter()
+                .map(|procedure| {
+                    procedure
+                        .lock()
+                        .unwrap()
+                        .nodes()
+                        .iter()
+                        .map(|node| ProcedureNodeSave {
+                            algorithm: self.algorithm(&node.algorithm),
+                            predecessors: node.predecessors.clone(),
+                        })
+                        .collect()
+                })
+                .collect();
+
+            self.script_saves[id as usize].procedures = procedures;
+        }
+
+        id
+    }
+
+    fn server(&mut self, server: &Arc<Mutex<Server>>) -> SaveId {
+        let (id, is_new) = self.servers.insert(server);
+        if is_new {
+            let server = server.lock().unwrap();
+            // Reserve our slot before visiting the defensive script, as ids are table indices
+            self.server_saves.push(ServerSave {
+                name: server.name.clone(),
+                threads: server.threads,
+                clock_speed: *server.clock_speed,
+                stats: server.stats.instances().to_vec(),
+                scheduled: server.scheduler.entries().to_vec(),
+                defensive_script: None,
+            });
+
+            self.server_saves[id as usize].defensive_script = server.defensive_script
+                .as_ref()
+                .map(|script| self.script(script));
+        }
+
+        id
+    }
+
+    fn target(&mut self, target: &Arc<Mutex<ExploitTarget>>) -> SaveId {
+        let (id, is_new) = self.targets.insert(target);
+        if is_new {
+            let (target_id, definition, server, script, connection_health, credits, max_credits, tier) = {
+                let target = target.lock().unwrap();
+                (
+                    target.id,
+                    target.definition.clone(),
+                    target.server.clone(),
+                    target.script.clone(),
+                    target.connection_health,
+                    target.credits,
+                    target.max_credits,
+                    target.tier,
+                )
+            };
+
+            let server = self.server(&server);
+            let script = self.script(&script);
+            self.target_saves.push(ExploitTargetSave {
+                id: target_id,
+                definition,
+                server,
+                script,
+                connection_health,
+                credits,
+                max_credits,
+                tier,
+            });
+        }
+
+        id
+    }
+}
+
+impl SaveFile {
+    /// Captures the entire player state, including every object reachable from it.
+    pub fn capture(player_state: &PlayerState) -> SaveFile {
+        let mut capture = Capture {
+            algorithms: SaveTable::new(),
+            algorithm_saves: vec![],
+            scripts: SaveTable::new(),
+            script_saves: vec![],
+            servers: SaveTable::new(),
+            server_saves: vec![],
+            targets: SaveTable::new(),
+            target_saves: vec![],
+        };
+
+        let inventory = player_state.inventory.algorithms
+            .iter()
+            .map(|algorithm| capture.algorithm(algorithm))
+            .collect();
+
+        let player_scripts = player_state.scripts
+            .iter()
+            .map(|script| capture.script(script))
+            .collect();
+
+        let player_servers = player_state.servers
+            .iter()
+            .map(|server| capture.server(server))
+            .collect();
+
+        let algorithm_market = AlgorithmMarketSave {
+            stock: player_state.algorithm_market.stock
+                .iter()
+                .map(|algorithm| capture.algorithm(algorithm))
+                .collect(),
+            time_until_restock_ms: player_state.algorithm_market.time_until_restock.as_millis() as u64,
+            rng_streams: player_state.algorithm_market.rng_streams.clone(),
+        };
+
+        let racks = player_state.racks
+            .iter()
+            .map(|rack| RackSave {
+                name: rack.name.clone(),
+                power_capacity: rack.power_capacity,
+                bandwidth: rack.bandwidth,
+                servers: rack.servers.iter().map(|server| capture.server(server)).collect(),
+            })
+            .collect();
+
+        let known_targets = player_state.known_targets
+            .iter()
+            .map(|target| capture.target(target))
+            .collect();
+
+        let active_exploits = player_state.active_exploits
+            .iter()
+            .map(|exploit| {
+                let exploit = exploit.lock().unwrap();
+                ActiveExploitSave {
+                    id: exploit.id,
+                    target: capture.target(&exploit.target),
+                    script: capture.script(&exploit.script),
+                    hosting_server: capture.server(&exploit.hosting_server),
+                    clock_allocation: *exploit.clock_allocation,
+                    connection_max_health: *exploit.connection_max_health.lock().unwrap(),
+                    connection_current_health: *exploit.connection_current_health.lock().unwrap(),
+                    auto_reconnect: *exploit.auto_reconnect.lock().unwrap(),
+                    has_connected: *exploit.has_connected.lock().unwrap(),
+                    reconnect_delay: *exploit.reconnect_delay.lock().unwrap(),
+                }
+            })
+            .collect();
+
+        let saved_at = web_time::SystemTime::now()
+            .duration_since(web_time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        SaveFile {
+            version: SAVE_FORMAT_VERSION,
+            saved_at,
+            player: PlayerSave {
+                progression: player_state.progression.clone(),
+                language_identifier: player_state.language_identifier.to_string(),
+                credits: player_state.credits,
+                unlocks: player_state.player_unlocks.unlocked(),
+                rng_streams: player_state.rng_streams.clone(),
+                algorithms: capture.algorithm_saves,
+                scripts: capture.script_saves,
+                servers: capture.server_saves,
+                targets: capture.target_saves,
+                inventory,
+                player_scripts,
+                player_servers,
+                racks,
+                known_targets,
+                active_exploits,
+                algorithm_market,
+            },
+        }
+    }
+
+    /// Parses a save, upgrading it from older formats as needed.
+    pub fn parse(contents: &str) -> Result<SaveFile, SaveError> {
+        let mut value: serde_json::Value = serde_json::from_str(contents)?;
+        migration::migrate(&mut value)?;
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    /// Rebuilds the player state described by this save, reconnecting all shared references.
+    pub fn restore(self) -> Result<PlayerState, SaveError> {
+        let player = self.player;
+
+        let algorithms = player.algorithms
+            .into_iter()
+            .map(|algorithm| Arc::new(Mutex::new(Algorithm {
+                id: algorithm.id,
+                rarity: algorithm.rarity,
+                instruction_count: algorithm.instruction_count.into(),
+                instruction_effects: algorithm.instruction_effects
+                    .into_iter()
+                    .map(|(instruction, effects)| (instruction.into(), effects))
+                    .collect(),
+            })))
+            .collect::<Vec<_>>();
+
+        let scripts = player.scripts
+            .into_iter()
+            .map(|script| {
+                let procedures = script.procedures
+                    .into_iter()
+                    .map(|procedure| {
+                        let mut restored_procedure = AlgorithmProcedure::from(&[]);
+                        for node in procedure {
+                            let algorithm = resolve(&algorithms, "algorithm", node.algorithm)?;
+                            restored_procedure
+                                .add_node(algorithm, &node.predecessors)
+                                .map_err(|ProcedureError::UnknownPredecessor { predecessor }| {
+                                    SaveError::InvalidReference { kind: "procedure node", id: predecessor as SaveId }
+                                })?;
+                        }
+
+                        Ok(Arc::new(Mutex::new(restored_procedure)))
+                    })
+                    .collect::<Result<Vec<_>, SaveError>>()?;
+
+                Ok(Arc::new(Mutex::new(Script::new(script.id, procedures))))
+            })
+            .collect::<Result<Vec<_>, SaveError>>()?;
+
+        let servers = player.servers
+            .into_iter()
+            .map(|server| Ok(Arc::new(Mutex::new(Server {
+                name: server.name,
+                threads: server.threads,
+                clock_speed: ClockSpeed::new(server.clock_speed),
+                effective_clock_speed: ClockSpeed::new(server.clock_speed),
+                uplink: Uplink::default(),
+                stats: ServerStatInstances::from(&server.stats),
+                running_scripts: vec![],
+                scheduler: CpuScheduler::from(&server.scheduled),
+                busy_clock_speed: ClockSpeed::new(0),
+                defensive_script: server.defensive_script
+                    .map(|id| resolve(&scripts, "script", id))
+                    .transpose()?,
+                defense: ServerDefense::default(),
+            }))))
+            .collect::<Result<Vec<_>, SaveError>>()?;
+
+        let targets = player.targets
+            .into_iter()
+            .map(|target| {
+                let mut exploit_target = ExploitTarget::new(
+                    target.id,
+                    resolve(&servers, "server", target.server)?,
+                    resolve(&scripts, "script", target.script)?,
+                    target.connection_health,
+                    target.max_credits,
+                );
+                exploit_target.credits = target.credits;
+                exploit_target.definition = target.definition;
+                exploit_target.tier = target.tier;
+
+                Ok(Arc::new(Mutex::new(exploit_target)))
+            })
+            .collect::<Result<Vec<_>, SaveError>>()?;
+
+        let mut rng_streams = player.rng_streams;
+        let active_exploits = player.active_exploits
+            .into_iter()
+            .map(|exploit| {
+                let mut active_exploit = ActiveExploit::new(
+                    resolve(&targets, "target", exploit.target)?,
+                    resolve(&scripts, "script", exploit.script)?,
+                    resolve(&servers, "server", exploit.hosting_server)?,
+                    ClockSpeed::new(exploit.clock_allocation),
+                    exploit.auto_reconnect,
+                    // Exploits pick up a fresh substream, as their position in the old one isn't saved
+                    rng_streams.next_stream(),
+                );
+                active_exploit.id = exploit.id;
+                active_exploit.restore_connection(
+                    exploit.connection_max_health,
+                    exploit.connection_current_health,
+                    exploit.has_connected,
+                );
+                *active_exploit.reconnect_delay.lock().unwrap() = exploit.reconnect_delay;
+
+                Ok(Arc::new(Mutex::new(active_exploit)))
+            })
+            .collect::<Result<Vec<_>, SaveError>>()?;
+
+        // Shares for exploits that no longer exist are dropped, and any exploit without one gets the default.
+        // The defensive script counts as one more hosted exploit.
+        for server in &servers {
+            let mut hosted = active_exploits
+                .iter()
+                .map(|exploit| exploit.lock().unwrap())
+                .filter(|exploit| Arc::ptr_eq(&exploit.hosting_server, server))
+                .map(|exploit| exploit.id)
+                .collect::<Vec<_>>();
+
+            let mut server = server.lock().unwrap();
+            if server.defensive_script.is_some() {
+                hosted.push(DEFENSE_ID);
+            }
+
+            let stale = server
+                .scheduler
+                .entries()
+                .iter()
+                .map(|(id, _)| *id)
+                .filter(|id| !hosted.contains(id))
+                .collect::<Vec<_>>();
+
+            for id in stale {
+                server.scheduler.remove(id);
+            }
+
+            for id in hosted {
+                server.scheduler.add(id);
+            }
+
+            server.reschedule(&active_exploits);
+        }
+
+        let language_identifier = player.language_identifier
+            .parse()
+            .map_err(|_| SaveError::InvalidLanguage(player.language_identifier.clone()))?;
+
+        let mut player_unlocks = PlayerUnlocks::empty();
+        for unlock in player.unlocks {
+            player_unlocks.unlock(unlock);
+        }
+
+        Ok(PlayerState {
+            progression: player.progression,
+            language_identifier,
+            credits: player.credits,
+            inventory: Inventory {
+                algorithms: player.inventory
+                    .into_iter()
+                    .map(|id| resolve(&algorithms, "algorithm", id))
+                    .collect::<Result<_, _>>()?,
+            },
+            servers: player.player_servers
+                .into_iter()
+                .map(|id| resolve(&servers, "server", id))
+                .collect::<Result<_, _>>()?,
+            racks: player.racks
+                .into_iter()
+                .map(|rack| Ok(Rack {
+                    name: rack.name,
+                    power_capacity: rack.power_capacity,
+                    bandwidth: rack.bandwidth,
+                    servers: rack.servers
+                        .into_iter()
+                        .map(|id| resolve(&servers, "server", id))
+                        .collect::<Result<_, _>>()?,
+                }))
+                .collect::<Result<_, SaveError>>()?,
+            known_targets: player.known_targets
+                .into_iter()
+                .map(|id| resolve(&targets, "target", id))
+                .collect::<Result<_, _>>()?,
+            active_exploits,
+            scripts: player.player_scripts
+                .into_iter()
+                .map(|id| resolve(&scripts, "script", id))
+                .collect::<Result<_, _>>()?,
+            algorithm_market: AlgorithmMarket {
+                stock: player.algorithm_market.stock
+                    .into_iter()
+                    .map(|id| resolve(&algorithms, "algorithm", id))
+                    .collect::<Result<_, _>>()?,
+                time_until_restock: Duration::from_millis(player.algorithm_market.time_until_restock_ms),
+                rng_streams: player.algorithm_market.rng_streams,
+            },
+            last_tick: web_time::Instant::now(),
+            tick_remainder: Duration::ZERO,
+            player_unlocks,
+            rng_streams,
+        })
+    }
+}
+
+/// JSON numbers can't hold a `u128` without losing precision (and browsers certainly can't),
+/// so large counters are stored as strings.
+mod u128_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algorithm::market::RESTOCK_INTERVAL;
+    use crate::save;
+    use crate::target::definition::corp_definition;
+
+    fn make_algorithm(instruction_count: u64) -> Arc<Mutex<Algorithm>> {
+        Arc::new(Mutex::new(Algorithm {
+            id: Uuid::new_v4().into(),
+            rarity: AlgorithmRarity::Rare,
+            instruction_count: instruction_count.into(),
+            instruction_effects: vec![
+                (instruction_count.into(), vec![AlgorithmEffect::Siphon { potency: (1..4).into() }]),
+            ],
+        }))
+    }
+
+    fn make_player_state() -> PlayerState {
+        let mut player_state = PlayerState::new(1234);
+        let target = corp_definition().build(&mut player_state.rng_streams.next_stream());
+        target.lock().unwrap().definition = Some("targets/corp.target.ron".to_string());
+        player_state.known_targets.push(target);
+        player_state.credits = u128::MAX - 7;
+        player_state.player_unlocks.unlock(PlayerUnlock::ExploitAutoReconnect);
+
+        let shared_algorithm = make_algorithm(10);
+        let mut forked_procedure = AlgorithmProcedure::from(&[shared_algorithm.clone(), make_algorithm(20)]);
+        forked_procedure.add_node(make_algorithm(5), &[0]).unwrap();
+        forked_procedure.add_algorithm(make_algorithm(15));
+        let script = Arc::new(Mutex::new(Script::new(
+            ScriptId::Id(1),
+            vec![
+                Arc::new(Mutex::new(forked_procedure)),
+                Arc::new(Mutex::new(AlgorithmProcedure::from(&[shared_algorithm]))),
+            ],
+        )));
+        player_state.scripts.push(script.clone());
+        player_state.servers[0].lock().unwrap().set_defensive_script(Some(script.clone()));
+
+        let active_exploit = ActiveExploit::new(
+            player_state.known_targets[0].clone(),
+            script,
+            player_state.servers[0].clone(),
+            ClockSpeed::new(1_000),
+            true,
+            player_state.rng_streams.next_stream(),
+        );
+        {
+            let mut server = player_state.servers[0].lock().unwrap();
+            server.scheduler.add(active_exploit.id);
+            let capacity = *server.clock_speed;
+            server.scheduler.set_allocation(active_exploit.id, Allocation::Pinned(1_000), capacity).unwrap();
+        }
+        player_state.active_exploits.push(Arc::new(Mutex::new(active_exploit)));
+
+        // Stocked, and partway to the next restock
+        player_state.algorithm_market.advance(Duration::ZERO);
+        player_state.algorithm_market.advance(Duration::from_secs(1));
+
+        player_state
+    }
+
+    fn roundtrip(player_state: &PlayerState) -> PlayerState {
+        let contents = save::serialize(player_state).unwrap();
+        save::deserialize(&contents).unwrap().0
+    }
+
+    #[test]
+    fn roundtrip_preserves_player_values() {
+        let player_state = make_player_state();
+        let restored = roundtrip(&player_state);
+
+        assert_eq!(restored.credits, player_state.credits);
+        assert!(restored.player_unlocks.is_unlocked(PlayerUnlock::ExploitAutoReconnect));
+        assert_eq!(restored.language_identifier, player_state.language_identifier);
+        assert_eq!(restored.inventory.algorithms.len(), player_state.inventory.algorithms.len());
+        for (restored_algorithm, algorithm) in restored.inventory.algorithms.iter().zip(&player_state.inventory.algorithms) {
+            let restored_algorithm = restored_algorithm.lock().unwrap();
+            let algorithm = algorithm.lock().unwrap();
+            assert_eq!(restored_algorithm.id, algorithm.id);
+            assert_eq!(restored_algorithm.rarity, algorithm.rarity);
+            assert_eq!(restored_algorithm.instruction_effects.len(), algorithm.instruction_effects.len());
+        }
+    }
+
+    #[test]
+    fn roundtrip_preserves_reconnect_delays() {
+        let player_state = make_player_state();
+        *player_state.active_exploits[0].lock().unwrap().reconnect_delay.lock().unwrap() = 7;
+        let restored = roundtrip(&player_state);
+
+        assert_eq!(*restored.active_exploits[0].lock().unwrap().reconnect_delay.lock().unwrap(), 7);
+    }
+
+    #[test]
+    fn roundtrip_preserves_target_credit_pools() {
+        let player_state = make_player_state();
+        player_state.known_targets[0].lock().unwrap().siphon_credits(123);
+        let restored = roundtrip(&player_state);
+
+        for (restored_target, target) in restored.known_targets.iter().zip(&player_state.known_targets) {
+            let restored_target = restored_target.lock().unwrap();
+            let target = target.lock().unwrap();
+            assert_eq!(restored_target.credits, target.credits);
+            assert_eq!(restored_target.max_credits, target.max_credits);
+        }
+    }
+
+    #[test]
+    fn roundtrip_shares_algorithms_between_procedures() {
+        let restored = roundtrip(&make_player_state());
+
+        let script = restored.scripts[0].lock().unwrap();
+        let first_procedure = script.procedures[0].lock().unwrap().iterator().next().unwrap();
+        let second_procedure = script.procedures[1].lock().unwrap().iterator().next().unwrap();
+
+        assert!(first_procedure.ptr_eq(&second_procedure));
+    }
+
+    #[test]
+    fn roundtrip_preserves_procedure_graphs() {
+        let restored = roundtrip(&make_player_state());
+
+        let script = restored.scripts[0].lock().unwrap();
+        let procedure = script.procedures[0].lock().unwrap();
+        let predecessors = procedure.nodes().iter().map(|node| node.predecessors.clone()).collect::<Vec<_>>();
+
+        assert_eq!(predecessors, vec![vec![], vec![0], vec![0], vec![1, 2]]);
+    }
+
+    #[test]
+    fn roundtrip_shares_exploit_references() {
+        let restored = roundtrip(&make_player_state());
+
+        let active_exploit = restored.active_exploits[0].lock().unwrap();
+        assert!(Arc::ptr_eq(&active_exploit.target, &restored.known_targets[0]));
+        assert!(Arc::ptr_eq(&active_exploit.script, &restored.scripts[0]));
+        assert!(Arc::ptr_eq(&active_exploit.hosting_server, &restored.servers[0]));
+        assert!(Arc::ptr_eq(&restored.racks[0].servers[0], &restored.servers[0]));
+
+        let defensive_script = restored.servers[0].lock().unwrap().defensive_script.clone();
+        assert!(Arc::ptr_eq(&defensive_script.unwrap(), &restored.scripts[0]));
+    }
+
+    #[test]
+    fn roundtrip_preserves_schedules() {
+        let restored = roundtrip(&make_player_state());
+
+        let active_exploit = restored.active_exploits[0].lock().unwrap();
+        let server = restored.servers[0].lock().unwrap();
+        assert_eq!(server.scheduler.allocation_of(active_exploit.id), Some(Allocation::Pinned(1_000)));
+        assert_eq!(*active_exploit.clock_allocation, 1_000);
+
+        // The defensive script shares whatever isn't pinned
+        assert_eq!(server.scheduler.allocation_of(DEFENSE_ID), Some(Allocation::default()));
+        assert_eq!(*server.defense.clock_allocation, *server.clock_speed - 1_000);
+    }
+
+    #[test]
+    fn roundtrip_preserves_algorithm_market() {
+        let mut player_state = make_player_state();
+        let mut restored = roundtrip(&player_state);
+
+        assert_eq!(restored.algorithm_market.time_until_restock, player_state.algorithm_market.time_until_restock);
+        let stock_ids = |state: &PlayerState| state.algorithm_market.stock
+            .iter()
+            .map(|algorithm| algorithm.lock().unwrap().id.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(stock_ids(&restored), stock_ids(&player_state));
+
+        // The next restock is the same one either way
+        player_state.algorithm_market.advance(RESTOCK_INTERVAL);
+        restored.algorithm_market.advance(RESTOCK_INTERVAL);
+        assert_eq!(stock_ids(&restored), stock_ids(&player_state));
+    }
+
+    #[test]
+    fn unsupported_version_is_rejected() {
+        let mut save_file: serde_json::Value = serde_json::from_str(
+            &save::serialize(&make_player_state()).unwrap()
+        ).unwrap();
+        save_file["version"] = (SAVE_FORMAT_VERSION + 1).into();
+
+        let result = save::deserialize(&save_file.to_string());
+        assert!(matches!(result, Err(SaveError::UnsupportedVersion { .. })));
+    }
+
+    #[test]
+    fn missing_reference_is_rejected() {
+        let mut save_file = SaveFile::capture(&make_player_state());
+        save_file.player.inventory.push(save_file.player.algorithms.len() as SaveId);
+
+        let result = save_file.restore();
+        assert!(matches!(result, Err(SaveError::InvalidReference { kind: "algorithm", .. })));
+    }
+
+    /// Freezes a save in the current format into the fixture corpus.
+    /// Run this once whenever [SAVE_FORMAT_VERSION] is bumped, and never edit the fixtures it writes.
+    #[test]
+    #[ignore]
+    fn write_current_fixture() {
+        let save_file = SaveFile::capture(&make_player_state());
+        let contents = serde_json::to_string_pretty(&save_file).unwrap();
+
+        let path = format!("{}/src/save/fixtures/v{SAVE_FORMAT_VERSION}.json", env!("CARGO_MANIFEST_DIR"));
+        std::fs::write(path, contents + "\n").unwrap();
+    }
+}
@@ -0,0 +1,582 @@
+use serde_json::{json, Value};
+use crate::save::{SaveError, SAVE_FORMAT_VERSION};
+
+/// Upgrades a save by exactly one version, in place.
+type Migration = fn(&mut Value) -> Result<(), SaveError>;
+
+/// Every migration, in order. The first entry upgrades saves from [OLDEST_SUPPORTED_VERSION].
+/// Migrations must only rely on the shape of the save they're given, never on current game types,
+/// as those are free to change after the migration is written.
+const MIGRATIONS: &[Migration] = &[
+    v1_to_v2,
+    v2_to_v3,
+    v3_to_v4,
+    v4_to_v5,
+    v5_to_v6,
+    v6_to_v7,
+    v7_to_v8,
+    v8_to_v9,
+    v9_to_v10,
+    v10_to_v11,
+    v11_to_v12,
+    v12_to_v13,
+    v13_to_v14,
+    v14_to_v15,
+];
+
+/// The oldest save version that can still be upgraded to [SAVE_FORMAT_VERSION].
+pub const OLDEST_SUPPORTED_VERSION: u32 = SAVE_FORMAT_VERSION - MIGRATIONS.len() as u32;
+
+/// Upgrades a save to [SAVE_FORMAT_VERSION] by running every migration newer than its version.
+pub fn migrate(save: &mut Value) -> Result<(), SaveError> {
+    let version = save
+        .get("version")
+        .and_then(Value::as_u64)
+        .unwrap_or_default() as u32;
+
+    if !(OLDEST_SUPPORTED_VERSION..=SAVE_FORMAT_VERSION).contains(&version) {
+        return Err(SaveError::UnsupportedVersion { found: version, supported: SAVE_FORMAT_VERSION });
+    }
+
+    let pending_migrations = &MIGRATIONS[(version - OLDEST_SUPPORTED_VERSION) as usize..];
+    for (from, migration) in (version..).zip(pending_migrations) {
+        migration(save)?;
+        save["version"] = (from + 1).into();
+    }
+
+    Ok(())
+}
+
+fn player_mut(save: &mut Value, from: u32) -> Result<&mut Value, SaveError> {
+    save.get_mut("player").ok_or_else(|| SaveError::Migration {
+        from,
+        reason: "save has no player".to_string(),
+    })
+}
+
+/// Unlocks were saved as a bitfield indexed by [PlayerUnlock](crate::player_state::unlocks::PlayerUnlock)'s
+/// declaration order; they're now saved by name.
+fn v1_to_v2(save: &mut Value) -> Result<(), SaveError> {
+    // The order of PlayerUnlock when v1 was current
+    const V1_UNLOCKS: [&str; 1] = ["ExploitAutoReconnect"];
+
+    let player = player_mut(save, 1)?;
+    let unlock_bitfield = player
+        .get("unlocks")
+        .and_then(Value::as_str)
+        .and_then(|bits| bits.parse::<u128>().ok())
+        .ok_or_else(|| SaveError::Migration { from: 1, reason: "unlocks aren't a bitfield".to_string() })?;
+
+    if unlock_bitfield >> V1_UNLOCKS.len() != 0 {
+        return Err(SaveError::Migration { from: 1, reason: format!("unknown unlocks in {unlock_bitfield:#b}") });
+    }
+
+    let unlocks = V1_UNLOCKS
+        .iter()
+        .enumerate()
+        .filter(|(bit, _)| unlock_bitfield & (1 << bit) != 0)
+        .map(|(_, unlock)| Value::from(*unlock))
+        .collect();
+
+    player["unlocks"] = Value::Array(unlocks);
+    Ok(())
+}
+
+/// Saves now carry the seed their random rolls are drawn from.
+/// Older saves never had one, so they're seeded from when they were written.
+fn v2_to_v3(save: &mut Value) -> Result<(), SaveError> {
+    let saved_at = save
+        .get("saved_at")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| SaveError::Migration { from: 2, reason: "save has no saved_at".to_string() })?;
+
+    let player = player_mut(save, 2)?;
+    player["rng_streams"] = json!({ "seed": saved_at, "next_stream": 0 });
+
+    Ok(())
+}
+
+/// Targets are now built from definition assets, and carry their own connection health.
+/// Every target in older saves was the hard-coded corporation, which became `targets/corp.target.ron`.
+fn v3_to_v4(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 3)?;
+    let targets = player
+        .get_mut("targets")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Migration { from: 3, reason: "save has no targets".to_string() })?;
+
+    for target in targets {
+        target["definition"] = "targets/corp.target.ron".into();
+        target["connection_health"] = 50.into();
+    }
+
+    Ok(())
+}
+
+/// Targets now have a difficulty tier. Every target in older saves was the starting corporation, at tier 0.
+fn v4_to_v5(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 4)?;
+    let targets = player
+        .get_mut("targets")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Migration { from: 4, reason: "save has no targets".to_string() })?;
+
+    for target in targets {
+        target["tier"] = 0.into();
+    }
+
+    Ok(())
+}
+
+/// Procedures are now graphs rather than lists. Each saved list becomes a chain, with every algorithm
+/// following the one before it.
+fn v5_to_v6(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 5)?;
+    let scripts = player
+        .get_mut("scripts")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Migration { from: 5, reason: "save has no scripts".to_string() })?;
+
+    for script in scripts {
+        let procedures = script
+            .get_mut("procedures")
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| SaveError::Migration { from: 5, reason: "script has no procedures".to_string() })?;
+
+        for procedure in procedures {
+            let algorithms = procedure
+                .as_array()
+                .ok_or_else(|| SaveError::Migration { from: 5, reason: "procedure isn't a list".to_string() })?;
+
+            let nodes = algorithms
+                .iter()
+                .enumerate()
+                .map(|(index, algorithm)| {
+                    let predecessors = if index == 0 { vec![] } else { vec![index - 1] };
+                    json!({ "algorithm": algorithm, "predecessors": predecessors })
+                })
+                .collect();
+
+            *procedure = Value::Array(nodes);
+        }
+    }
+
+    Ok(())
+}
+
+/// Servers now schedule the exploits they host. Older saves split clock speed evenly,
+/// so every exploit is scheduled on its host with the default weight, in the order they were started.
+fn v6_to_v7(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 6)?;
+    let hosted = player
+        .get("active_exploits")
+        .and_then(Value::as_array)
+        .ok_or_else(|| SaveError::Migration { from: 6, reason: "save has no active exploits".to_string() })?
+        .iter()
+        .map(|exploit| {
+            let id = exploit.get("id").cloned();
+            let host = exploit.get("hosting_server").and_then(Value::as_u64);
+            id.zip(host)
+                .ok_or_else(|| SaveError::Migration { from: 6, reason: "active exploit has no id or host".to_string() })
+        })
+        .collect::<Result<Vec<_>, SaveError>>()?;
+
+    let servers = player
+        .get_mut("servers")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Migration { from: 6, reason: "save has no servers".to_string() })?;
+
+    for (index, server) in servers.iter_mut().enumerate() {
+        let scheduled = hosted
+            .iter()
+            .filter(|(_, host)| *host == index as u64)
+            .map(|(id, _)| json!([id, { "Weighted": 1 }]))
+            .collect();
+
+        server["scheduled"] = Value::Array(scheduled);
+    }
+
+    Ok(())
+}
+
+/// Servers are now installed in racks that share power and bandwidth.
+/// Older saves only ever had the starting server, so every player server goes into the starter rack.
+fn v7_to_v8(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 7)?;
+    let player_servers = player
+        .get("player_servers")
+        .cloned()
+        .ok_or_else(|| SaveError::Migration { from: 7, reason: "save has no player servers".to_string() })?;
+
+    player["racks"] = json!([{
+        "name": "rack-01",
+        "power_capacity": 150,
+        "bandwidth": 100,
+        "servers": player_servers,
+    }]);
+
+    Ok(())
+}
+
+/// The algorithm market's stock is now saved. Older saves never had any, so the market restocks on its first tick,
+/// from streams of its own seeded from the run seed the same way as a new game's.
+fn v8_to_v9(save: &mut Value) -> Result<(), SaveError> {
+    // The salt the market's seed was mixed with when v9 was current
+    const V9_MARKET_SEED_SALT: u64 = 0x6D61_726B_6574;
+
+    let player = player_mut(save, 8)?;
+    let seed = player["rng_streams"]["seed"]
+        .as_u64()
+        .ok_or_else(|| SaveError::Migration { from: 8, reason: "save has no run seed".to_string() })?;
+
+    player["algorithm_market"] = json!({
+        "stock": [],
+        "time_until_restock_ms": 0,
+        "rng_streams": { "seed": seed ^ V9_MARKET_SEED_SALT, "next_stream": 0 },
+    });
+
+    Ok(())
+}
+
+/// Algorithms now have a rarity. Nothing generated before rarities existed was any better than common.
+fn v9_to_v10(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 9)?;
+    let algorithms = player
+        .get_mut("algorithms")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Migration { from: 9, reason: "save has no algorithms".to_string() })?;
+
+    for algorithm in algorithms {
+        algorithm["rarity"] = "Common".into();
+    }
+
+    Ok(())
+}
+
+/// Servers can now have a defensive script. Nothing defended player servers before.
+fn v10_to_v11(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 10)?;
+    let servers = player
+        .get_mut("servers")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Migration { from: 10, reason: "save has no servers".to_string() })?;
+
+    for server in servers {
+        server["defensive_script"] = Value::Null;
+    }
+
+    Ok(())
+}
+
+/// Reconnect delays now survive saving. Older saves didn't record one, so exploits waiting to reconnect
+/// start connecting as soon as they're loaded, just as they did before.
+fn v11_to_v12(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 11)?;
+    let active_exploits = player
+        .get_mut("active_exploits")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Migration { from: 11, reason: "save has no active exploits".to_string() })?;
+
+    for active_exploit in active_exploits {
+        active_exploit["reconnect_delay"] = 0.into();
+    }
+
+    Ok(())
+}
+
+/// Stat modifications can now wear off. Everything applied before then was permanent.
+fn v12_to_v13(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 12)?;
+    let servers = player
+        .get_mut("servers")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Migration { from: 12, reason: "save has no servers".to_string() })?;
+
+    for server in servers {
+        let stats = server
+            .get_mut("stats")
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| SaveError::Migration { from: 12, reason: "server has no stats".to_string() })?;
+
+        for stat in stats.iter_mut().filter_map(Value::as_array_mut) {
+            stat.push(Value::Null);
+        }
+    }
+
+    Ok(())
+}
+
+/// Script modifications now remember the exploit that made them, so they can be cleaned up when it ends.
+/// Which exploit made older ones wasn't recorded, so they're left for purges to deal with.
+fn v13_to_v14(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 13)?;
+    let servers = player
+        .get_mut("servers")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Migration { from: 13, reason: "save has no servers".to_string() })?;
+
+    for server in servers {
+        let stats = server
+            .get_mut("stats")
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| SaveError::Migration { from: 13, reason: "server has no stats".to_string() })?;
+
+        for stat in stats {
+            if let Some(script_id) = stat[0].get_mut("Script").map(Value::take) {
+                stat[0] = json!({ "Script": { "script_id": script_id, "exploit_id": null } });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Targets now have a pool of credits to siphon from. Older targets start out with a full pool,
+/// sized by their tier the same way as when the pool was introduced.
+fn v14_to_v15(save: &mut Value) -> Result<(), SaveError> {
+    let player = player_mut(save, 14)?;
+    let targets = player
+        .get_mut("targets")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| SaveError::Migration { from: 14, reason: "save has no targets".to_string() })?;
+
+    for target in targets {
+        let tier = target["tier"]
+            .as_u64()
+            .ok_or_else(|| SaveError::Migration { from: 14, reason: "target has no tier".to_string() })?;
+
+        let credit_pool = 1_000 * (1 + tier);
+        target["credits"] = credit_pool.into();
+        target["max_credits"] = credit_pool.into();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::player_state::unlocks::PlayerUnlock;
+    use crate::save;
+    use super::*;
+
+    /// Saves frozen from every supported version, written by `save::data::tests::write_current_fixture`.
+    /// These must never be edited; a save that loads today has to keep loading after every release.
+    const FIXTURES: &[(u32, &str)] = &[
+        (1, include_str!("fixtures/v1.json")),
+        (2, include_str!("fixtures/v2.json")),
+        (3, include_str!("fixtures/v3.json")),
+        (4, include_str!("fixtures/v4.json")),
+        (5, include_str!("fixtures/v5.json")),
+        (6, include_str!("fixtures/v6.json")),
+        (7, include_str!("fixtures/v7.json")),
+        (8, include_str!("fixtures/v8.json")),
+        (9, include_str!("fixtures/v9.json")),
+        (10, include_str!("fixtures/v10.json")),
+        (11, include_str!("fixtures/v11.json")),
+        (12, include_str!("fixtures/v12.json")),
+        (13, include_str!("fixtures/v13.json")),
+        (14, include_str!("fixtures/v14.json")),
+        (15, include_str!("fixtures/v15.json")),
+    ];
+
+    #[test]
+    fn fixtures_cover_every_supported_version() {
+        let versions = FIXTURES.iter().map(|(version, _)| *version).collect::<Vec<_>>();
+        let supported_versions = (OLDEST_SUPPORTED_VERSION..=SAVE_FORMAT_VERSION).collect::<Vec<_>>();
+
+        assert_eq!(versions, supported_versions);
+    }
+
+    #[test]
+    fn fixtures_load() {
+        for (version, fixture) in FIXTURES {
+            let (player_state, _) = save::deserialize(fixture)
+                .unwrap_or_else(|err| panic!("v{version} fixture failed to load: {err}"));
+
+            assert_eq!(player_state.credits, u128::MAX - 7, "v{version}");
+            assert!(player_state.player_unlocks.is_unlocked(PlayerUnlock::ExploitAutoReconnect), "v{version}");
+            assert_eq!(player_state.scripts.len(), 1, "v{version}");
+            assert_eq!(player_state.active_exploits.len(), 1, "v{version}");
+        }
+    }
+
+    #[test]
+    fn migrate_updates_version() {
+        let mut save = serde_json::from_str(FIXTURES[0].1).unwrap();
+        migrate(&mut save).unwrap();
+
+        assert_eq!(save["version"], json!(SAVE_FORMAT_VERSION));
+    }
+
+    #[test]
+    fn unsupported_versions_are_rejected() {
+        for version in [0, OLDEST_SUPPORTED_VERSION - 1, SAVE_FORMAT_VERSION + 1] {
+            let mut save = json!({ "version": version, "player": {} });
+            let result = migrate(&mut save);
+
+            assert!(matches!(result, Err(SaveError::UnsupportedVersion { found, .. }) if found == version));
+        }
+    }
+
+    #[test]
+    fn v1_unlock_bitfield_becomes_names() {
+        let mut save = json!({ "version": 1, "player": { "unlocks": "1" } });
+        v1_to_v2(&mut save).unwrap();
+        assert_eq!(save["player"]["unlocks"], json!(["ExploitAutoReconnect"]));
+
+        let mut save = json!({ "version": 1, "player": { "unlocks": "0" } });
+        v1_to_v2(&mut save).unwrap();
+        assert_eq!(save["player"]["unlocks"], json!([]));
+    }
+
+    #[test]
+    fn v1_unknown_unlock_bits_are_rejected() {
+        let mut save = json!({ "version": 1, "player": { "unlocks": "2" } });
+        assert!(matches!(v1_to_v2(&mut save), Err(SaveError::Migration { from: 1, .. })));
+    }
+
+    #[test]
+    fn v2_saves_are_seeded_from_saved_at() {
+        let mut save = json!({ "version": 2, "saved_at": 1234, "player": {} });
+        v2_to_v3(&mut save).unwrap();
+
+        assert_eq!(save["player"]["rng_streams"], json!({ "seed": 1234, "next_stream": 0 }));
+    }
+
+    #[test]
+    fn v3_targets_become_the_corp_definition() {
+        let mut save = json!({ "version": 3, "player": { "targets": [{ "id": "x", "server": 0, "script": 0 }] } });
+        v3_to_v4(&mut save).unwrap();
+
+        let target = &save["player"]["targets"][0];
+        assert_eq!(target["definition"], json!("targets/corp.target.ron"));
+        assert_eq!(target["connection_health"], json!(50));
+    }
+
+    #[test]
+    fn v4_targets_start_at_tier_zero() {
+        let mut save = json!({ "version": 4, "player": { "targets": [{ "id": "x" }, { "id": "y" }] } });
+        v4_to_v5(&mut save).unwrap();
+
+        assert_eq!(save["player"]["targets"][0]["tier"], json!(0));
+        assert_eq!(save["player"]["targets"][1]["tier"], json!(0));
+    }
+
+    #[test]
+    fn v5_procedures_become_chains() {
+        let mut save = json!({ "version": 5, "player": { "scripts": [{ "id": { "Id": 1 }, "procedures": [[3, 4, 5], [6]] }] } });
+        v5_to_v6(&mut save).unwrap();
+
+        assert_eq!(save["player"]["scripts"][0]["procedures"], json!([
+            [
+                { "algorithm": 3, "predecessors": [] },
+                { "algorithm": 4, "predecessors": [0] },
+                { "algorithm": 5, "predecessors": [1] },
+            ],
+            [
+                { "algorithm": 6, "predecessors": [] },
+            ],
+        ]));
+    }
+
+    #[test]
+    fn v6_exploits_are_scheduled_on_their_hosts() {
+        let mut save = json!({ "version": 6, "player": {
+            "servers": [{ "name": "a" }, { "name": "b" }],
+            "active_exploits": [
+                { "id": "x", "hosting_server": 1 },
+                { "id": "y", "hosting_server": 0 },
+                { "id": "z", "hosting_server": 1 },
+            ],
+        } });
+        v6_to_v7(&mut save).unwrap();
+
+        assert_eq!(save["player"]["servers"][0]["scheduled"], json!([["y", { "Weighted": 1 }]]));
+        assert_eq!(save["player"]["servers"][1]["scheduled"], json!([["x", { "Weighted": 1 }], ["z", { "Weighted": 1 }]]));
+    }
+
+    #[test]
+    fn v7_servers_go_into_the_starter_rack() {
+        let mut save = json!({ "version": 7, "player": { "player_servers": [0, 2] } });
+        v7_to_v8(&mut save).unwrap();
+
+        assert_eq!(save["player"]["racks"], json!([
+            { "name": "rack-01", "power_capacity": 150, "bandwidth": 100, "servers": [0, 2] },
+        ]));
+    }
+
+    #[test]
+    fn v8_algorithm_markets_start_empty() {
+        let mut save = json!({ "version": 8, "player": { "rng_streams": { "seed": 1234, "next_stream": 6 } } });
+        v8_to_v9(&mut save).unwrap();
+
+        assert_eq!(save["player"]["algorithm_market"], json!({
+            "stock": [],
+            "time_until_restock_ms": 0,
+            "rng_streams": { "seed": 1234 ^ 0x6D61_726B_6574_u64, "next_stream": 0 },
+        }));
+        assert_eq!(save["player"]["rng_streams"], json!({ "seed": 1234, "next_stream": 6 }));
+    }
+
+    #[test]
+    fn v9_algorithms_are_common() {
+        let mut save = json!({ "version": 9, "player": { "algorithms": [{ "id": "Invalid" }, { "id": "Invalid" }] } });
+        v9_to_v10(&mut save).unwrap();
+
+        assert_eq!(save["player"]["algorithms"][0]["rarity"], json!("Common"));
+        assert_eq!(save["player"]["algorithms"][1]["rarity"], json!("Common"));
+    }
+
+    #[test]
+    fn v10_servers_are_undefended() {
+        let mut save = json!({ "version": 10, "player": { "servers": [{ "name": "a" }, { "name": "b" }] } });
+        v10_to_v11(&mut save).unwrap();
+
+        assert_eq!(save["player"]["servers"][0].get("defensive_script"), Some(&Value::Null));
+        assert_eq!(save["player"]["servers"][1].get("defensive_script"), Some(&Value::Null));
+    }
+
+    #[test]
+    fn v11_exploits_have_no_reconnect_delay() {
+        let mut save = json!({ "version": 11, "player": { "active_exploits": [{ "has_connected": true }] } });
+        v11_to_v12(&mut save).unwrap();
+
+        assert_eq!(save["player"]["active_exploits"][0]["reconnect_delay"], json!(0));
+    }
+
+    #[test]
+    fn v12_stat_modifications_are_permanent() {
+        let mut save = json!({ "version": 12, "player": { "servers": [
+            { "stats": [["Innate", "SiphonResist", 3], [{ "Script": { "Id": 1 } }, "ExfilResist", -2]] },
+        ] } });
+        v12_to_v13(&mut save).unwrap();
+
+        assert_eq!(save["player"]["servers"][0]["stats"], json!([
+            ["Innate", "SiphonResist", 3, null],
+            [{ "Script": { "Id": 1 } }, "ExfilResist", -2, null],
+        ]));
+    }
+
+    #[test]
+    fn v13_script_modifications_have_no_exploit() {
+        let mut save = json!({ "version": 13, "player": { "servers": [
+            { "stats": [["Innate", "SiphonResist", 3, null], [{ "Script": { "Id": 1 } }, "ExfilResist", -2, 40]] },
+        ] } });
+        v13_to_v14(&mut save).unwrap();
+
+        assert_eq!(save["player"]["servers"][0]["stats"], json!([
+            ["Innate", "SiphonResist", 3, null],
+            [{ "Script": { "script_id": { "Id": 1 }, "exploit_id": null } }, "ExfilResist", -2, 40],
+        ]));
+    }
+
+    #[test]
+    fn v14_targets_have_full_credit_pools() {
+        let mut save = json!({ "version": 14, "player": { "targets": [{ "tier": 0 }, { "tier": 4 }] } });
+        v14_to_v15(&mut save).unwrap();
+
+        assert_eq!(save["player"]["targets"][0]["credits"], json!(1000));
+        assert_eq!(save["player"]["targets"][0]["max_credits"], json!(1000));
+        assert_eq!(save["player"]["targets"][1]["credits"], json!(5000));
+    }
+}
@@ -0,0 +1,84 @@
+pub mod backend;
+pub mod data;
+pub mod migration;
+pub mod plugin;
+pub mod state;
+pub(crate) mod systems;
+
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
+use crate::player_state::state::PlayerState;
+use crate::save::data::{SaveFile, SaveId};
+
+/// The version written into every new save.
+/// Bump this whenever the shape of [SaveFile] changes, and add a migration from the previous version.
+pub const SAVE_FORMAT_VERSION: u32 = 15;
+
+#[derive(Debug)]
+pub enum SaveError {
+    /// The save backend failed to read or write.
+    Io(std::io::Error),
+
+    /// The browser's storage is unavailable or rejected the operation.
+    #[cfg_attr(not(target_arch = "wasm32"), allow(dead_code))]
+    Storage(String),
+
+    /// The save contents aren't valid for the expected format.
+    Format(serde_json::Error),
+
+    /// The save was written by a version of the game we don't know how to read.
+    UnsupportedVersion { found: u32, supported: u32 },
+
+    /// A save couldn't be upgraded from an older version.
+    Migration { from: u32, reason: String },
+
+    /// An object in the save references another object that doesn't exist.
+    InvalidReference { kind: &'static str, id: SaveId },
+
+    /// The saved language identifier couldn't be parsed.
+    InvalidLanguage(String),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(err) => write!(f, "save i/o failed: {err}"),
+            SaveError::Storage(err) => write!(f, "save storage failed: {err}"),
+            SaveError::Format(err) => write!(f, "save is malformed: {err}"),
+            SaveError::UnsupportedVersion { found, supported } => {
+                write!(f, "save version {found} is not supported (expected {supported})")
+            }
+            SaveError::Migration { from, reason } => write!(f, "save migration from version {from} failed: {reason}"),
+            SaveError::InvalidReference { kind, id } => write!(f, "save references missing {kind} {id}"),
+            SaveError::InvalidLanguage(language) => write!(f, "save has invalid language {language}"),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<std::io::Error> for SaveError {
+    fn from(value: std::io::Error) -> Self {
+        SaveError::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(value: serde_json::Error) -> Self {
+        SaveError::Format(value)
+    }
+}
+
+/// Serializes the entire player state into the current save format.
+pub fn serialize(player_state: &PlayerState) -> Result<String, SaveError> {
+    let save_file = SaveFile::capture(player_state);
+    Ok(serde_json::to_string(&save_file)?)
+}
+
+/// Parses a save and rebuilds the player state it describes, along with when the save was written.
+pub fn deserialize(contents: &str) -> Result<(PlayerState, web_time::SystemTime), SaveError> {
+    let save_file = SaveFile::parse(contents)?;
+    let saved_at = web_time::UNIX_EPOCH + Duration::from_secs(save_file.saved_at);
+
+    Ok((save_file.restore()?, saved_at))
+}
@@ -0,0 +1,23 @@
+use bevy::app::{App, Last, Plugin, Startup, Update};
+use bevy::prelude::{not, resource_exists, IntoScheduleConfigs};
+use crate::offline::state::OfflineCatchUp;
+use crate::save::backend::platform_backend;
+use crate::save::state::SaveState;
+use crate::save::systems::*;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .add_systems(Startup, load_player_state)
+            // Saving mid catch-up would stamp the save with the current time, forfeiting whatever's left to simulate
+            .add_systems(Update, autosave_player_state.run_if(not(resource_exists::<OfflineCatchUp>)))
+            .add_systems(Last, save_player_state_on_exit.run_if(not(resource_exists::<OfflineCatchUp>)))
+            .insert_resource(SaveState {
+                backend: platform_backend(),
+                last_save: web_time::Instant::now(),
+                autosave_enabled: true,
+            });
+    }
+}
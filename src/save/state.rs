@@ -0,0 +1,11 @@
+use bevy::prelude::Resource;
+use crate::save::backend::SaveBackend;
+
+#[derive(Resource)]
+pub struct SaveState {
+    pub backend: Box<dyn SaveBackend>,
+    pub last_save: web_time::Instant,
+
+    /// Cleared if an existing save failed to load, so we never overwrite it with a fresh game.
+    pub autosave_enabled: bool,
+}
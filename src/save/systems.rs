@@ -0,0 +1,89 @@
+use std::time::Duration;
+use bevy::app::AppExit;
+use bevy::log::{error, info};
+use bevy::prelude::{Commands, MessageReader, Res, ResMut};
+use crate::offline::state::OfflineCatchUp;
+use crate::player_state::state::PlayerState;
+use crate::save;
+use crate::save::state::SaveState;
+use crate::target::tier::DifficultyTier;
+use crate::ui::state::UiState;
+use crate::ui::window::active_exploit::ActiveExploitWindow;
+
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+pub(crate) fn load_player_state(
+    mut commands: Commands,
+    mut player_state: ResMut<PlayerState>,
+    mut ui_state: ResMut<UiState>,
+    mut save_state: ResMut<SaveState>,
+) {
+    let contents = match save_state.backend.read() {
+        Ok(Some(contents)) => contents,
+        Ok(None) => return,
+        Err(err) => {
+            error!("failed to read save: {err}");
+            save_state.autosave_enabled = false;
+            return;
+        }
+    };
+
+    match save::deserialize(&contents) {
+        Ok((restored_state, saved_at)) => {
+            *player_state = restored_state;
+
+            // Saves from before targets were generated have none on offer
+            player_state.extend_target_ladder(DifficultyTier::default());
+
+            // Exploits kept running while the game was closed
+            let offline_duration = web_time::SystemTime::now()
+                .duration_since(saved_at)
+                .unwrap_or_default();
+            commands.insert_resource(OfflineCatchUp::new(offline_duration));
+
+            for active_exploit in &player_state.active_exploits {
+                ui_state.active_exploit_windows.push(ActiveExploitWindow::new(active_exploit.clone()));
+            }
+
+            info!("loaded save with seed {}", player_state.rng_streams.seed());
+        }
+        Err(err) => {
+            error!("failed to load save, autosave disabled: {err}");
+            save_state.autosave_enabled = false;
+        }
+    }
+}
+
+pub(crate) fn autosave_player_state(
+    player_state: Res<PlayerState>,
+    mut save_state: ResMut<SaveState>,
+) {
+    if !save_state.autosave_enabled || save_state.last_save.elapsed() < AUTOSAVE_INTERVAL {
+        return;
+    }
+
+    write_save(&player_state, &mut save_state);
+}
+
+pub(crate) fn save_player_state_on_exit(
+    mut exits: MessageReader<AppExit>,
+    player_state: Res<PlayerState>,
+    mut save_state: ResMut<SaveState>,
+) {
+    if exits.read().next().is_none() || !save_state.autosave_enabled {
+        return;
+    }
+
+    write_save(&player_state, &mut save_state);
+}
+
+fn write_save(player_state: &PlayerState, save_state: &mut SaveState) {
+    save_state.last_save = web_time::Instant::now();
+
+    let result = save::serialize(player_state)
+        .and_then(|contents| save_state.backend.write(&contents));
+
+    if let Err(err) = result {
+        error!("failed to save: {err}");
+    }
+}
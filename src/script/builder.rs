@@ -2,16 +2,29 @@ use std::sync::{Arc, Mutex};
 use crate::algorithm::algorithm::Algorithm;
 use crate::algorithm::id::AlgorithmId;
 use crate::algorithm::procedure::AlgorithmProcedure;
+use crate::script::id::ScriptId;
 use crate::script::Script;
 
 pub struct ScriptBuilder {
     script: Script,
+
+    /// The procedure new algorithms are added to.
+    selected_procedure: usize,
+
+    /// Nodes of the selected procedure that the next algorithm follows.
+    /// When there are none, it follows the end of every branch.
+    branch_points: Vec<usize>,
 }
 
 impl ScriptBuilder {
     pub fn new() -> ScriptBuilder {
         ScriptBuilder {
-            script: Script::empty(),
+            script: Script::new(
+                ScriptId::Invalid,
+                vec![Arc::new(Mutex::new(AlgorithmProcedure::from(&[])))],
+            ),
+            selected_procedure: 0,
+            branch_points: vec![],
         }
     }
 
@@ -20,31 +33,194 @@ impl ScriptBuilder {
             .script
             .procedures
             .iter()
-            .all(|proc| proc.lock().unwrap().iterator().next().is_none())
+            .all(|proc| proc.lock().unwrap().is_empty())
     }
 
+    /// Adds an algorithm to the selected procedure, after the selected branch points if there are any,
+    /// or at the end otherwise.
     pub fn add_algorithm(&mut self, algorithm: Arc<Mutex<Algorithm>>) {
-        // ZJ-TODO: handle multiple procedures
-        match self.script.procedures.first() {
-            Some(procedure) => procedure.lock().unwrap().add_algorithm(algorithm),
-            None => self.script.procedures.push(Arc::new(Mutex::new(
-                AlgorithmProcedure::from(&[algorithm]))
-            )),
+        let mut procedure = self.script.procedures[self.selected_procedure].lock().unwrap();
+        if self.branch_points.is_empty() {
+            procedure.add_algorithm(algorithm);
+        } else {
+            procedure
+                .add_node(algorithm, &self.branch_points)
+                .expect("branch points are nodes of the selected procedure");
         }
+
+        self.branch_points.clear();
     }
 
     pub fn remove_algorithm(&mut self, algorithm_id: AlgorithmId) {
+        self.branch_points.clear();
+
         for procedure in self.script.procedures.iter_mut() {
             let mut procedure = procedure.lock().unwrap();
             procedure.remove_algorithm(algorithm_id.clone());
         }
     }
 
+    /// Adds a new, empty procedure that runs in parallel with the others, and selects it.
+    pub fn add_procedure(&mut self) {
+        self.script.procedures.push(Arc::new(Mutex::new(AlgorithmProcedure::from(&[]))));
+        self.selected_procedure = self.script.procedures.len() - 1;
+        self.branch_points.clear();
+    }
+
+    /// Removes the procedure at `index`, returning the algorithms it held.
+    /// The last remaining procedure is emptied rather than removed, so there's always one to add algorithms to.
+    pub fn remove_procedure(&mut self, index: usize) -> Vec<Arc<Mutex<Algorithm>>> {
+        if index >= self.script.procedures.len() {
+            return vec![];
+        }
+
+        self.branch_points.clear();
+
+        let procedure = if self.script.procedures.len() == 1 {
+            std::mem::replace(
+                &mut self.script.procedures[0],
+                Arc::new(Mutex::new(AlgorithmProcedure::from(&[]))),
+            )
+        } else {
+            self.script.procedures.remove(index)
+        };
+
+        if self.selected_procedure >= self.script.procedures.len() || self.selected_procedure > index {
+            self.selected_procedure = self.selected_procedure.saturating_sub(1);
+        }
+
+        let procedure = procedure.lock().unwrap();
+        procedure
+            .iterator()
+            .filter_map(|algorithm| algorithm.upgrade())
+            .collect()
+    }
+
+    pub fn select_procedure(&mut self, index: usize) {
+        if index < self.script.procedures.len() && index != self.selected_procedure {
+            self.selected_procedure = index;
+            self.branch_points.clear();
+        }
+    }
+
+    pub fn selected_procedure(&self) -> usize {
+        self.selected_procedure
+    }
+
+    /// Selects or deselects a node of the selected procedure for the next algorithm to follow.
+    /// Following a node that already has a successor forks a new branch; following several joins them.
+    pub fn toggle_branch_point(&mut self, node: usize) {
+        if let Some(position) = self.branch_points.iter().position(|&branch_point| branch_point == node) {
+            self.branch_points.remove(position);
+        } else if node < self.script.procedures[self.selected_procedure].lock().unwrap().nodes().len() {
+            self.branch_points.push(node);
+        }
+    }
+
+    pub fn branch_points(&self) -> &[usize] {
+        &self.branch_points
+    }
+
     pub fn current_script(&self) -> &Script {
         &self.script
     }
 
-    pub fn finish(self) -> Script {
+    /// Finishes the script, dropping any procedures that were left empty.
+    pub fn finish(mut self) -> Script {
+        self.script.procedures.retain(|procedure| !procedure.lock().unwrap().is_empty());
         self.script
     }
-}
\ No newline at end of file
+}
+
+impl Default for ScriptBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+    use super::*;
+
+    fn make_algorithm() -> Arc<Mutex<Algorithm>> {
+        Arc::new(Mutex::new(Algorithm {
+            id: Uuid::new_v4().into(),
+            rarity: Default::default(),
+            instruction_count: 3.into(),
+            instruction_effects: Default::default(),
+        }))
+    }
+
+    #[test]
+    fn algorithms_are_added_to_the_selected_procedure() {
+        let mut builder = ScriptBuilder::new();
+        builder.add_algorithm(make_algorithm());
+        builder.add_procedure();
+        builder.add_algorithm(make_algorithm());
+        builder.add_algorithm(make_algorithm());
+        builder.select_procedure(0);
+        builder.add_algorithm(make_algorithm());
+
+        let script = builder.finish();
+        let lengths = script
+            .procedures
+            .iter()
+            .map(|procedure| procedure.lock().unwrap().iterator().count())
+            .collect::<Vec<_>>();
+        assert_eq!(lengths, vec![2, 2]);
+        assert_eq!(script.threads_required(), 2);
+    }
+
+    #[test]
+    fn removed_procedures_return_their_algorithms() {
+        let mut builder = ScriptBuilder::new();
+        builder.add_algorithm(make_algorithm());
+        builder.add_procedure();
+        builder.add_algorithm(make_algorithm());
+        builder.add_algorithm(make_algorithm());
+
+        assert_eq!(builder.remove_procedure(1).len(), 2);
+        assert_eq!(builder.selected_procedure(), 0);
+        assert_eq!(builder.current_script().procedures.len(), 1);
+
+        // The last procedure is only emptied
+        assert_eq!(builder.remove_procedure(0).len(), 1);
+        assert_eq!(builder.current_script().procedures.len(), 1);
+        assert!(builder.is_empty());
+    }
+
+    #[test]
+    fn empty_procedures_are_dropped_when_finished() {
+        let mut builder = ScriptBuilder::new();
+        builder.add_procedure();
+        builder.add_algorithm(make_algorithm());
+        builder.add_procedure();
+
+        let script = builder.finish();
+        assert_eq!(script.procedures.len(), 1);
+        assert_eq!(script.threads_required(), 1);
+    }
+
+    #[test]
+    fn branch_points_fork_and_join_the_selected_procedure() {
+        let mut builder = ScriptBuilder::new();
+        builder.add_algorithm(make_algorithm());
+        builder.add_algorithm(make_algorithm());
+
+        builder.toggle_branch_point(0);
+        builder.add_algorithm(make_algorithm());
+        assert!(builder.branch_points().is_empty());
+
+        builder.toggle_branch_point(1);
+        builder.toggle_branch_point(2);
+        builder.add_algorithm(make_algorithm());
+
+        let script = builder.finish();
+        assert_eq!(script.threads_required(), 2);
+
+        let procedure = script.procedures[0].lock().unwrap();
+        let predecessors = procedure.nodes().iter().map(|node| node.predecessors.clone()).collect::<Vec<_>>();
+        assert_eq!(predecessors, vec![vec![], vec![0], vec![0], vec![1, 2]]);
+    }
+}
@@ -1,7 +1,6 @@
 use std::sync::{Arc, Mutex, Weak};
-use crate::algorithm::effect::AlgorithmEffect;
 use crate::algorithm::procedure::executor::AlgorithmProcedureExecutor;
-use crate::executor::Executor;
+use crate::executor::{Executor, TickResult};
 use crate::script::Script;
 
 #[derive(Clone, Default)]
@@ -31,6 +30,10 @@ impl ScriptExecutor {
     pub fn from_arc(script: &Arc<Mutex<Script>>) -> ScriptExecutor {
         ScriptExecutor::from(Arc::downgrade(script))
     }
+
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
 }
 
 impl Executor for ScriptExecutor {
@@ -48,18 +51,23 @@ impl Executor for ScriptExecutor {
         }
     }
 
-    fn tick_execution(&mut self, tick_count: u64) -> Vec<AlgorithmEffect> {
+    fn tick(&mut self, tick_count: u64) -> TickResult {
         if self.is_paused {
-            return vec![];
+            return TickResult { effects: vec![], unused_ticks: tick_count };
         }
 
-        let mut new_effects = vec![];
+        let mut effects = vec![];
+        let mut unused_ticks = tick_count;
 
         for procedure_executor in &mut self.algorithm_procedure_executors {
-            new_effects.extend(procedure_executor.tick_execution(tick_count));
+            let result = procedure_executor.tick(tick_count);
+            effects.extend(result.effects);
+
+            // Procedures run in parallel, so the script finishes with its slowest procedure
+            unused_ticks = unused_ticks.min(result.unused_ticks);
         }
 
-        new_effects
+        TickResult { effects, unused_ticks }
     }
 
     fn is_complete(&self) -> bool {
@@ -1,6 +1,7 @@
 use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Default, PartialEq, Eq, Debug, Hash)]
+#[derive(Clone, Default, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
 pub enum ScriptId {
     #[default]
     Invalid,
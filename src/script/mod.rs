@@ -22,6 +22,16 @@ impl Script {
         Script { id, procedures }
     }
 
+    /// How many threads a server needs to run this script; procedures run in parallel with each other,
+    /// and each needs a thread for every one of its branches that can run at once.
+    pub fn threads_required(&self) -> u32 {
+        self
+            .procedures
+            .iter()
+            .map(|procedure| procedure.lock().unwrap().peak_parallelism())
+            .sum()
+    }
+
     pub fn instruction_count(&self) -> u64 {
         self
             .procedures
@@ -36,9 +46,11 @@ mod tests {
     use super::*;
     use std::collections::BTreeMap;
     use std::sync::{Arc, Mutex};
+    use proptest::prelude::*;
     use uuid::Uuid;
     use crate::algorithm::algorithm::Algorithm;
     use crate::algorithm::effect::AlgorithmEffect;
+    use crate::algorithm::effect::value::AlgorithmEffectValue;
     use crate::algorithm::executor::AlgorithmExecutor;
     use crate::algorithm::id::AlgorithmId;
     use crate::algorithm::procedure::executor::AlgorithmProcedureExecutor;
@@ -53,6 +65,7 @@ mod tests {
     fn algorithm_executor_can_complete() {
         let algorithm = Arc::new(Mutex::new(Algorithm {
             id: make_id(),
+            rarity: Default::default(),
             instruction_count: 3.into(),
             instruction_effects: Default::default(),
         }));
@@ -75,12 +88,14 @@ mod tests {
     fn algorithm_procedure_executor_can_complete() {
         let algorithm1 = Arc::new(Mutex::new(Algorithm {
             id: make_id(),
+            rarity: Default::default(),
             instruction_count: 3.into(),
             instruction_effects: Default::default(),
         }));
 
         let algorithm2 = Arc::new(Mutex::new(Algorithm {
             id: make_id(),
+            rarity: Default::default(),
             instruction_count: 3.into(),
             instruction_effects: Default::default(),
         }));
@@ -104,6 +119,7 @@ mod tests {
     fn script_executor_can_complete() {
         let algorithm1 = Arc::new(Mutex::new(Algorithm {
             id: make_id(),
+            rarity: Default::default(),
             instruction_count: 5.into(),
             instruction_effects: vec![
                 (1.into(), vec![
@@ -114,6 +130,7 @@ mod tests {
 
         let algorithm2 = Arc::new(Mutex::new(Algorithm {
             id: make_id(),
+            rarity: Default::default(),
             instruction_count: 10.into(),
             instruction_effects: vec![
                 (5.into(), vec![
@@ -167,4 +184,186 @@ mod tests {
 
         assert!(executor.is_complete());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn parallel_procedures_of_different_lengths_complete() {
+        let short_procedure = Arc::new(Mutex::new(AlgorithmProcedure::from(&[
+            Arc::new(Mutex::new(Algorithm {
+                id: make_id(),
+                rarity: Default::default(),
+                instruction_count: 2.into(),
+                instruction_effects: vec![(2.into(), vec![AlgorithmEffect::Siphon { potency: 1.into() }])],
+            })),
+        ])));
+
+        let long_procedure = Arc::new(Mutex::new(AlgorithmProcedure::from(&[
+            Arc::new(Mutex::new(Algorithm {
+                id: make_id(),
+                rarity: Default::default(),
+                instruction_count: 3.into(),
+                instruction_effects: Default::default(),
+            })),
+            Arc::new(Mutex::new(Algorithm {
+                id: make_id(),
+                rarity: Default::default(),
+                instruction_count: 3.into(),
+                instruction_effects: vec![(3.into(), vec![AlgorithmEffect::Siphon { potency: 2.into() }])],
+            })),
+        ])));
+
+        let script = Arc::new(Mutex::new(Script::new(ScriptId::Id(1), vec![short_procedure, long_procedure])));
+        assert_eq!(script.lock().unwrap().threads_required(), 2);
+
+        let mut executor = ScriptExecutor::from_arc(&script);
+        executor.start_execution();
+
+        let mut effects = vec![];
+        for _ in 0..6 {
+            assert!(!executor.is_complete());
+            effects.extend(executor.tick_execution(1));
+        }
+
+        assert!(executor.is_complete());
+        assert_eq!(executor.progress(), executor.total_instructions());
+        assert_eq!(effects, vec![
+            AlgorithmEffect::Siphon { potency: 1.into() },
+            AlgorithmEffect::Siphon { potency: 2.into() },
+        ]);
+    }
+
+    #[test]
+    fn forked_branches_execute_concurrently_and_join() {
+        let make_algorithm = |instruction_count: u64, potency: i32| Arc::new(Mutex::new(Algorithm {
+            id: make_id(),
+            rarity: Default::default(),
+            instruction_count: instruction_count.into(),
+            instruction_effects: vec![(instruction_count.into(), vec![AlgorithmEffect::Siphon { potency: potency.into() }])],
+        }));
+
+        // 0 forks into 1 and 2, which join into 3
+        let mut procedure = AlgorithmProcedure::from(&[make_algorithm(1, 1), make_algorithm(2, 2)]);
+        procedure.add_node(make_algorithm(4, 3), &[0]).unwrap();
+        procedure.add_algorithm(make_algorithm(1, 4));
+        let procedure = Arc::new(Mutex::new(procedure));
+
+        let mut executor = AlgorithmProcedureExecutor::from(&procedure).unwrap();
+        executor.start_execution();
+        assert_eq!(executor.total_instructions(), 8);
+
+        // Both branches start as soon as the first algorithm finishes, and run side by side
+        assert_eq!(executor.tick_execution(1), vec![AlgorithmEffect::Siphon { potency: 1.into() }]);
+        assert_eq!(executor.running_branches(), 2);
+        executor.tick_execution(1);
+        assert_eq!(executor.running_branches(), 2);
+        assert_eq!(executor.tick_execution(1), vec![AlgorithmEffect::Siphon { potency: 2.into() }]);
+        assert_eq!(executor.running_branches(), 1);
+        assert_eq!(executor.progress(), 1 + 2 + 2);
+
+        // The join waits for the longer branch
+        executor.tick_execution(1);
+        assert_eq!(executor.tick_execution(1), vec![AlgorithmEffect::Siphon { potency: 3.into() }]);
+        assert!(!executor.is_complete());
+        assert_eq!(executor.tick_execution(1), vec![AlgorithmEffect::Siphon { potency: 4.into() }]);
+        assert!(executor.is_complete());
+        assert_eq!(executor.progress(), executor.total_instructions());
+    }
+
+    /// An algorithm's instruction count, the instructions its effects trigger on, and which earlier nodes it follows.
+    type NodeSpec = (u64, Vec<u64>, Vec<proptest::sample::Index>);
+
+    fn node_spec() -> impl Strategy<Value = NodeSpec> {
+        (1u64..40, prop::collection::vec(1u64..40, 0..3), prop::collection::vec(any::<proptest::sample::Index>(), 0..3))
+    }
+
+    /// Builds a script from specs, giving every effect a distinct potency so effects can be told apart.
+    fn build_script(procedure_specs: &[Vec<NodeSpec>]) -> Arc<Mutex<Script>> {
+        let mut next_potency = 0;
+        let procedures = procedure_specs
+            .iter()
+            .map(|node_specs| {
+                let mut procedure = AlgorithmProcedure::from(&[]);
+                for (node, (instruction_count, effect_instructions, predecessors)) in node_specs.iter().enumerate() {
+                    let instruction_effects = effect_instructions
+                        .iter()
+                        .map(|instruction| {
+                            next_potency += 1;
+                            let instruction = (instruction - 1) % instruction_count + 1;
+                            (instruction.into(), vec![AlgorithmEffect::Siphon { potency: next_potency.into() }])
+                        })
+                        .collect();
+
+                    let algorithm = Arc::new(Mutex::new(Algorithm {
+                        id: make_id(),
+                        rarity: Default::default(),
+                        instruction_count: (*instruction_count).into(),
+                        instruction_effects,
+                    }));
+
+                    let predecessors = match node {
+                        0 => vec![],
+                        _ => predecessors.iter().map(|index| index.index(node)).collect(),
+                    };
+                    procedure.add_node(algorithm, &predecessors).unwrap();
+                }
+
+                Arc::new(Mutex::new(procedure))
+            })
+            .collect();
+
+        Arc::new(Mutex::new(Script::new(ScriptId::Id(1), procedures)))
+    }
+
+    /// Ticks a fresh executor for `script` in the given batches, returning the potencies of every effect triggered,
+    /// the executor's final progress, and the ticks reported unused.
+    fn run_batches(script: &Arc<Mutex<Script>>, batches: impl IntoIterator<Item = u64>) -> (Vec<i32>, u64, u64) {
+        let mut executor = ScriptExecutor::from_arc(script);
+        executor.start_execution();
+
+        let mut potencies = vec![];
+        let mut unused_ticks = 0;
+        for batch in batches {
+            let result = executor.tick(batch);
+            unused_ticks += result.unused_ticks;
+            potencies.extend(result.effects.into_iter().map(|effect| match effect {
+                AlgorithmEffect::Siphon { potency: AlgorithmEffectValue::Static(potency) } => potency,
+                other => panic!("unexpected effect {other:?}"),
+            }));
+        }
+
+        // Effects triggered within the same batch come out in node order rather than instruction order
+        potencies.sort_unstable();
+        (potencies, executor.progress(), unused_ticks)
+    }
+
+    proptest! {
+        #[test]
+        fn effects_are_independent_of_tick_batching(
+            procedure_specs in prop::collection::vec(prop::collection::vec(node_spec(), 1..6), 1..3),
+            batches in prop::collection::vec(1u64..50, 1..30),
+        ) {
+            let script = build_script(&procedure_specs);
+            let total_ticks = batches.iter().sum::<u64>();
+
+            let batched = run_batches(&script, batches);
+            let single_ticks = run_batches(&script, std::iter::repeat_n(1, total_ticks as usize));
+
+            prop_assert_eq!(batched, single_ticks);
+        }
+
+        #[test]
+        fn one_batch_is_enough_for_any_script(
+            procedure_specs in prop::collection::vec(prop::collection::vec(node_spec(), 1..6), 1..3),
+        ) {
+            let script = build_script(&procedure_specs);
+            let mut executor = ScriptExecutor::from_arc(&script);
+            let total_instructions = executor.total_instructions();
+            executor.start_execution();
+
+            let result = executor.tick(total_instructions + 7);
+
+            prop_assert!(executor.is_complete());
+            prop_assert_eq!(executor.progress(), total_instructions);
+            prop_assert!(result.unused_ticks >= 7);
+        }
+    }
+}
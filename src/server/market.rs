@@ -0,0 +1,147 @@
+use std::sync::{Arc, Mutex};
+use crate::server::{Server, ServerDefense, ServerStatInstances, ServerStatType};
+use crate::server::scheduler::CpuScheduler;
+use crate::server::uplink::Uplink;
+use crate::ui::clock_speed::ClockSpeed;
+
+/// How much clock speed a single clock speed upgrade adds, in Hz.
+pub const CLOCK_SPEED_UPGRADE: u64 = 500_000;
+
+/// What a newly bought server comes with.
+const NEW_SERVER_CLOCK_SPEED: u64 = 1_000_000;
+const NEW_SERVER_THREADS: u32 = 2;
+
+/// What the second server costs; every server after it costs more than the last.
+const NEW_SERVER_BASE_COST: f64 = 250.0;
+const NEW_SERVER_COST_GROWTH: f64 = 2.5;
+
+const CLOCK_SPEED_UPGRADE_BASE_COST: f64 = 40.0;
+const CLOCK_SPEED_UPGRADE_COST_GROWTH: f64 = 1.35;
+
+const THREAD_UPGRADE_BASE_COST: f64 = 150.0;
+const THREAD_UPGRADE_COST_GROWTH: f64 = 2.2;
+
+const CONNECTION_STRENGTH_UPGRADE_BASE_COST: f64 = 100.0;
+const CONNECTION_STRENGTH_UPGRADE_COST_GROWTH: f64 = 1.6;
+
+const STEALTH_UPGRADE_BASE_COST: f64 = 120.0;
+const STEALTH_UPGRADE_COST_GROWTH: f64 = 1.8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServerUpgrade {
+    /// Adds [CLOCK_SPEED_UPGRADE] to the server's clock speed.
+    ClockSpeed,
+
+    /// Adds a thread to the server.
+    Threads,
+
+    /// Adds a point of innate [ConnectionStrength](ServerStatType::ConnectionStrength) to the server.
+    ConnectionStrength,
+
+    /// Adds a point of innate [Stealth](ServerStatType::Stealth) to the server.
+    Stealth,
+}
+
+/// Prices and builds the hardware the player can buy.
+pub struct ServerMarket;
+
+impl ServerMarket {
+    /// What buying another server costs, when the player already owns `owned_servers`.
+    pub fn server_cost(owned_servers: usize) -> u128 {
+        price(NEW_SERVER_BASE_COST, NEW_SERVER_COST_GROWTH, owned_servers.saturating_sub(1) as u64)
+    }
+
+    /// What applying `upgrade` to `server` costs, given everything it already has.
+    pub fn upgrade_cost(server: &Server, upgrade: ServerUpgrade) -> u128 {
+        match upgrade {
+            ServerUpgrade::ClockSpeed => price(
+                CLOCK_SPEED_UPGRADE_BASE_COST,
+                CLOCK_SPEED_UPGRADE_COST_GROWTH,
+                *server.clock_speed / CLOCK_SPEED_UPGRADE,
+            ),
+            ServerUpgrade::Threads => price(
+                THREAD_UPGRADE_BASE_COST,
+                THREAD_UPGRADE_COST_GROWTH,
+                server.threads as u64,
+            ),
+            ServerUpgrade::ConnectionStrength => price(
+                CONNECTION_STRENGTH_UPGRADE_BASE_COST,
+                CONNECTION_STRENGTH_UPGRADE_COST_GROWTH,
+                server.stats.innate_value_of(ServerStatType::ConnectionStrength).max(0) as u64,
+            ),
+            ServerUpgrade::Stealth => price(
+                STEALTH_UPGRADE_BASE_COST,
+                STEALTH_UPGRADE_COST_GROWTH,
+                server.stats.innate_value_of(ServerStatType::Stealth).max(0) as u64,
+            ),
+        }
+    }
+
+    /// Builds the server bought when the player already owns `owned_servers`.
+    pub fn build_server(owned_servers: usize) -> Arc<Mutex<Server>> {
+        Arc::new(Mutex::new(Server {
+            // Counting up from the starting server, fe80:0070::
+            name: format!("fe80:{:04x}::", 0x70 + owned_servers),
+            threads: NEW_SERVER_THREADS,
+            clock_speed: ClockSpeed::new(NEW_SERVER_CLOCK_SPEED),
+            effective_clock_speed: ClockSpeed::new(NEW_SERVER_CLOCK_SPEED),
+            uplink: Uplink::default(),
+            stats: ServerStatInstances::new(),
+            running_scripts: vec![],
+            scheduler: CpuScheduler::new(),
+            busy_clock_speed: ClockSpeed::new(0),
+            defensive_script: None,
+            defense: ServerDefense::default(),
+        }))
+    }
+
+    pub fn upgrade(server: &mut Server, upgrade: ServerUpgrade) {
+        match upgrade {
+            ServerUpgrade::ClockSpeed => *server.clock_speed += CLOCK_SPEED_UPGRADE,
+            ServerUpgrade::Threads => server.threads += 1,
+            ServerUpgrade::ConnectionStrength => server.stats.add_innate(ServerStatType::ConnectionStrength, 1),
+            ServerUpgrade::Stealth => server.stats.add_innate(ServerStatType::Stealth, 1),
+        }
+    }
+}
+
+/// `base * growth^level`, rounded to whole credits.
+fn price(base: f64, growth: f64, level: u64) -> u128 {
+    (base * growth.powi(level.min(i32::MAX as u64) as i32)).round() as u128
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::ServerStats;
+    use super::*;
+
+    #[test]
+    fn every_server_costs_more_than_the_last() {
+        let costs = (1..6).map(ServerMarket::server_cost).collect::<Vec<_>>();
+
+        assert_eq!(costs[0], 250);
+        assert!(costs.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn upgrades_get_pricier_as_servers_grow() {
+        let mut server = ServerMarket::build_server(1).lock().unwrap().clone();
+        assert_eq!(server.name, "fe80:0071::");
+
+        for upgrade in [ServerUpgrade::ClockSpeed, ServerUpgrade::Threads, ServerUpgrade::ConnectionStrength, ServerUpgrade::Stealth] {
+            let before = ServerMarket::upgrade_cost(&server, upgrade);
+            ServerMarket::upgrade(&mut server, upgrade);
+            assert!(ServerMarket::upgrade_cost(&server, upgrade) > before);
+        }
+
+        assert_eq!(*server.clock_speed, NEW_SERVER_CLOCK_SPEED + CLOCK_SPEED_UPGRADE);
+        assert_eq!(server.threads, NEW_SERVER_THREADS + 1);
+        assert_eq!(server.stats.value_of(ServerStatType::ConnectionStrength), 1);
+        assert_eq!(server.stats.value_of(ServerStatType::Stealth), 1);
+    }
+
+    #[test]
+    fn prices_saturate_instead_of_overflowing() {
+        assert_eq!(price(1.0, 10.0, u64::MAX), u128::MAX);
+    }
+}
@@ -1,8 +1,22 @@
+pub mod market;
+pub mod rack;
+pub mod scheduler;
+pub mod uplink;
+
 use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::active_exploit::ActiveExploit;
+use crate::algorithm::effect::AlgorithmEffect;
+use crate::executor::Executor;
 use crate::script::executor::ScriptExecutor;
 use crate::script::id::ScriptId;
+use crate::script::Script;
+use crate::server::rack::{IDLE_POWER_DRAW, POWER_PER_MHZ};
+use crate::server::scheduler::{CpuScheduler, DEFENSE_ID};
+use crate::server::uplink::Uplink;
 use crate::ui::clock_speed::ClockSpeed;
 
 #[derive(Clone)]
@@ -14,9 +28,54 @@ pub struct Server {
 
     pub clock_speed: ClockSpeed,
 
+    /// The clock speed this server is actually running at, after underclocking to fit its rack's power supply.
+    /// Servers outside of a rack always run at their full `clock_speed`.
+    pub effective_clock_speed: ClockSpeed,
+
+    /// How much this server can take from targets, from its share of its rack's bandwidth.
+    pub uplink: Uplink,
+
     pub stats: ServerStatInstances,
 
     pub running_scripts: Vec<Arc<Mutex<ScriptExecutor>>>,
+
+    /// Decides how much of `clock_speed` each hosted exploit gets.
+    pub scheduler: CpuScheduler,
+
+    /// How much of `clock_speed` is handed to work that's actually running, as of the last
+    /// [measure_load](Server::measure_load).
+    pub busy_clock_speed: ClockSpeed,
+
+    /// Runs against the opponents of the connected exploits hosted on this server, on its own share of the clock.
+    /// Assign it with [set_defensive_script](Server::set_defensive_script) so the scheduler makes room for it.
+    pub defensive_script: Option<Arc<Mutex<Script>>>,
+
+    /// How far through its defensive script this server is.
+    pub defense: ServerDefense,
+}
+
+/// A server's defensive script in progress.
+/// There's only ever one per server, however many exploits the server hosts.
+#[derive(Clone)]
+pub struct ServerDefense {
+    /// The clock speed the scheduler hands the defensive script, under [DEFENSE_ID].
+    pub clock_allocation: ClockSpeed,
+
+    /// The script being run, and its executor. Follows whichever script the server currently has assigned.
+    execution: Option<(Arc<Mutex<Script>>, ScriptExecutor)>,
+
+    /// How many effects have been aimed at hosted exploits so far, so that each takes its turn.
+    turns: usize,
+}
+
+impl Default for ServerDefense {
+    fn default() -> Self {
+        ServerDefense {
+            clock_allocation: ClockSpeed::new(0),
+            execution: None,
+            turns: 0,
+        }
+    }
 }
 
 impl Server {
@@ -25,16 +84,118 @@ impl Server {
             name: "".to_string(),
             threads: 0,
             clock_speed: ClockSpeed::new(0),
+            effective_clock_speed: ClockSpeed::new(0),
+            uplink: Uplink::default(),
             stats: ServerStatInstances::new(),
             running_scripts: vec![],
+            scheduler: CpuScheduler::new(),
+            busy_clock_speed: ClockSpeed::new(0),
+            defensive_script: None,
+            defense: ServerDefense::default(),
         }
     }
 
-    // pub fn available_compute(&self) -> ClockSpeed {
-    //     for running_script in &self.running_scripts {
-    //         let executor = running_script.lock().unwrap()
-    //     }
-    // }
+    /// Assigns (or with `None`, clears) the defensive script, giving it its own entry in the scheduler.
+    /// Takes effect on the hosted exploits' clock allocations the next time the server is rescheduled.
+    pub fn set_defensive_script(&mut self, script: Option<Arc<Mutex<Script>>>) {
+        match script {
+            Some(_) => self.scheduler.add(DEFENSE_ID),
+            None => self.scheduler.remove(DEFENSE_ID),
+        }
+
+        self.defensive_script = script;
+    }
+
+    /// Runs the defensive script for `ticks`, starting it over each time it completes, and returns the effects it produced.
+    pub fn tick_defense(&mut self, ticks: u64) -> Vec<AlgorithmEffect> {
+        let Some(defensive_script) = self.defensive_script.clone() else {
+            self.defense.execution = None;
+            return vec![];
+        };
+
+        let (defensive_script, mut executor) = match self.defense.execution.take() {
+            Some((script, executor)) if Arc::ptr_eq(&script, &defensive_script) => (script, executor),
+            _ => {
+                let mut executor = ScriptExecutor::from_arc(&defensive_script);
+                executor.start_execution();
+                (defensive_script, executor)
+            }
+        };
+
+        let effects = ActiveExploit::tick_repeating(&mut executor, &defensive_script, ticks);
+        self.defense.execution = Some((defensive_script, executor));
+
+        effects
+    }
+
+    /// Picks which of `count` hosted exploits the next defensive effect is aimed at, taking each in turn.
+    pub fn next_defense_turn(&mut self, count: usize) -> usize {
+        let turn = self.defense.turns % count.max(1);
+        self.defense.turns = self.defense.turns.wrapping_add(1);
+
+        turn
+    }
+
+    /// Hands out this server's effective clock speed to the exploits it hosts, and its defensive script,
+    /// according to its scheduler. Exploits hosted elsewhere are left untouched.
+    pub fn reschedule(&mut self, active_exploits: &[Arc<Mutex<ActiveExploit>>]) {
+        self.measure_load(active_exploits);
+        self.scheduler.apply(*self.effective_clock_speed, active_exploits);
+
+        let defense_allocation = self
+            .scheduler
+            .allocations(*self.effective_clock_speed)
+            .into_iter()
+            .find(|(id, _)| *id == DEFENSE_ID)
+            .map_or(0, |(_, clock_speed)| clock_speed);
+        self.defense.clock_allocation = defense_allocation.into();
+    }
+
+    /// How many times faster than its clock speed this server runs scripts, after its
+    /// [ClockSpeedMultiplier](ServerStatType::ClockSpeedMultiplier). Never drops below stopping entirely.
+    pub fn clock_speed_multiplier(&self) -> f64 {
+        (100 + self.stats.value_of(ServerStatType::ClockSpeedMultiplier)).max(0) as f64 / 100.0
+    }
+
+    /// Works out how much of the full clock speed goes to the hosted exploits that are executing,
+    /// and to the defensive script while it runs against them. Exploits that are connecting, paused
+    /// or disconnected keep their share of the clock, but leave it idle.
+    pub fn measure_load(&mut self, active_exploits: &[Arc<Mutex<ActiveExploit>>]) {
+        let executing = active_exploits
+            .iter()
+            .map(|active_exploit| active_exploit.lock().unwrap())
+            .filter(|active_exploit| self.scheduler.is_scheduled(active_exploit.id) && active_exploit.is_executing())
+            .map(|active_exploit| active_exploit.id)
+            .collect::<Vec<_>>();
+
+        let busy_clock_speed = self
+            .scheduler
+            .allocations(*self.clock_speed)
+            .into_iter()
+            .filter(|(id, _)| executing.contains(id) || (*id == DEFENSE_ID && !executing.is_empty()))
+            .map(|(_, clock_speed)| clock_speed)
+            .sum::<u64>();
+        self.busy_clock_speed = busy_clock_speed.into();
+    }
+
+    /// How much of this server's full clock speed is busy, from 0.0 to 1.0.
+    pub fn load(&self) -> f64 {
+        if *self.clock_speed == 0 {
+            return 0.0;
+        }
+
+        *self.busy_clock_speed as f64 / *self.clock_speed as f64
+    }
+
+    /// How much power this server draws running its load at full clock speed, in watts.
+    pub fn power_draw(&self) -> f64 {
+        IDLE_POWER_DRAW + POWER_PER_MHZ * (*self.clock_speed as f64 / 1_000_000.0) * self.load()
+    }
+
+    /// How much power this server draws with all of its clock speed handed out, in watts.
+    pub fn max_power_draw(&self) -> f64 {
+        IDLE_POWER_DRAW + POWER_PER_MHZ * (*self.clock_speed as f64 / 1_000_000.0)
+    }
 }
 
 pub trait ServerStats {
@@ -50,13 +211,13 @@ pub trait ServerStats {
 
     /// Applies a server stat instance to this server, purging modifications that would sum to zero.
     /// For example, say the server only has a single +1 exfil resist stat applied from a script.
-    /// ```
+    /// ```text
     /// let server_stat_instances = [
     ///     ServerStatInstance(Script(_), ExfilResist, 1),
     /// ];
     /// ```
     /// Then, `apply_and_purge` is called with `ServerStatInstance(Script(_), ExfilResist, -1)`.
-    /// ```
+    /// ```text
     ///
     /// let server_stat_instances = [
     ///     ServerStatInstance(Script(_), ExfilResist, 1),
@@ -65,13 +226,20 @@ pub trait ServerStats {
     /// ```
     /// `apply_and_purge` will remove both modifications such that `server_stat_instances` is empty.
     /// The removed modifications are returned by the function.
+    /// Whatever is left of the applied modification after cancelling out opposing ones is kept on the server.
     fn apply_and_purge(&mut self, stat_instance: ServerStatInstance) -> Vec<ServerStatInstance>;
 
+    /// Removes up to `potency` worth of purgable modifications of `stat_type` pointing away from `potency`,
+    /// oldest first. A positive `potency` undoes debuffs, and a negative `potency` undoes buffs;
+    /// either way, the stat is never pushed past its baseline and innate stats are never touched.
+    /// Modifications that were only partly purged are reduced, rather than removed.
+    /// The purged modifications (or purged parts of them) are returned by the function.
+    fn purge(&mut self, stat_type: ServerStatType, potency: i32) -> Vec<ServerStatInstance>;
+
     /// Returns the value of each stat type owned by this server.
     fn stat_values(&self) -> BTreeMap<ServerStatType, i32>;
 
     /// Returns each individual stat instance on this server.
-    #[allow(dead_code)]
     fn stat_instances(&self) -> BTreeMap<ServerStatType, Vec<ServerStatInstance>>;
 }
 
@@ -88,6 +256,60 @@ impl ServerStatInstances {
     pub fn from(stats: &[ServerStatInstance]) -> ServerStatInstances {
         ServerStatInstances { stats: stats.to_vec() }
     }
+
+    /// Returns every stat instance, in the order they were applied.
+    pub fn instances(&self) -> &[ServerStatInstance] {
+        &self.stats
+    }
+
+    /// Returns the value a stat has without any modifications applied.
+    pub fn innate_value_of(&self, stat_type: ServerStatType) -> i32 {
+        self.value_of(stat_type.clone()) - self.modification_of(stat_type)
+    }
+
+    /// Permanently raises (or lowers) the innate value of a stat, eg. from a hardware upgrade.
+    pub fn add_innate(&mut self, stat_type: ServerStatType, value: i32) {
+        let existing = self
+            .stats
+            .iter_mut()
+            .find(|stat| stat.source() == &ServerStatSource::Innate && stat.stat_type() == &stat_type);
+
+        match existing {
+            Some(stat) => stat.2 += value,
+            None => self.stats.push(ServerStatInstance::new(ServerStatSource::Innate, stat_type, value)),
+        }
+    }
+
+    /// Removes and returns every modification made by scripts running as part of the exploit `exploit_id`.
+    pub fn remove_from_exploit(&mut self, exploit_id: Uuid) -> Vec<ServerStatInstance> {
+        let (removed, remaining) = self.stats.drain(..).partition(|stat| matches!(
+            stat.source(),
+            ServerStatSource::Script { exploit_id: Some(id), .. } if *id == exploit_id
+        ));
+        self.stats = remaining;
+
+        removed
+    }
+
+    /// Counts down every timed modification by `ticks`, removing and returning those that wore off.
+    pub fn expire(&mut self, ticks: u32) -> Vec<ServerStatInstance> {
+        for stat in &mut self.stats {
+            if let Some(remaining_ticks) = &mut stat.3 {
+                *remaining_ticks = remaining_ticks.saturating_sub(ticks);
+            }
+        }
+
+        let (expired, remaining) = self.stats.drain(..).partition(|stat| stat.3 == Some(0));
+        self.stats = remaining;
+
+        expired
+    }
+}
+
+impl Default for ServerStatInstances {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl ServerStats for ServerStatInstances {
@@ -112,11 +334,42 @@ impl ServerStats for ServerStatInstances {
 
     fn apply_and_purge(
         &mut self,
-        stat_instance: ServerStatInstance
+        mut stat_instance: ServerStatInstance
     ) -> Vec<ServerStatInstance> {
-        // ZJ-TODO: deprecate purging
-        self.stats.push(stat_instance);
-        vec![]
+        let purged = self.purge(stat_instance.stat_type().to_owned(), stat_instance.value());
+
+        // Purged modifications point the opposite way, so adding them back leaves what wasn't cancelled out
+        stat_instance.2 += purged.iter().map(|stat| stat.value()).sum::<i32>();
+        if stat_instance.value() != 0 {
+            self.stats.push(stat_instance);
+        }
+
+        purged
+    }
+
+    fn purge(&mut self, stat_type: ServerStatType, potency: i32) -> Vec<ServerStatInstance> {
+        let mut remaining_potency = potency.abs();
+        let mut purged = vec![];
+        for stat in &mut self.stats {
+            if remaining_potency == 0 {
+                break;
+            }
+
+            let opposes_potency = stat.value().signum() == -potency.signum();
+            if stat.stat_type() != &stat_type || !stat.source().purgable() || !opposes_potency {
+                continue;
+            }
+
+            let purged_value = stat.value().abs().min(remaining_potency) * stat.value().signum();
+            remaining_potency -= purged_value.abs();
+            stat.2 -= purged_value;
+
+            purged.push(ServerStatInstance(stat.0.clone(), stat.1.clone(), purged_value, stat.3));
+        }
+
+        self.stats.retain(|stat| !stat.source().purgable() || stat.value() != 0);
+
+        purged
     }
 
     fn stat_values(&self) -> BTreeMap<ServerStatType, i32> {
@@ -152,34 +405,77 @@ impl ServerStats for ServerStatInstances {
     }
 }
 
-#[derive(Hash, Eq, PartialEq, Clone, Debug)]
+#[derive(Hash, Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum ServerStatSource {
     /// The stat is innate to the server.
     Innate,
 
-    /// The stat is being modified by a script.
-    Script(ScriptId),
+    /// The stat is being modified by a script, run as part of the exploit `exploit_id`.
+    /// Modifications made before exploits were tracked have no exploit, and are only ever purged.
+    Script { script_id: ScriptId, exploit_id: Option<Uuid> },
 }
 
 impl ServerStatSource {
     /// Determines if the stat source is purgable by the `Purge` algorithm effect.
     pub fn purgable(&self) -> bool {
-        matches!(self, ServerStatSource::Script(_))
+        matches!(self, ServerStatSource::Script { .. })
     }
 }
 
 /// All stats that can exist on a server.
-#[derive(Hash, Eq, PartialEq, Clone, Debug, Ord, PartialOrd)]
+#[derive(Hash, Eq, PartialEq, Clone, Debug, Ord, PartialOrd, Serialize, Deserialize)]
 pub enum ServerStatType {
     SiphonResist,
     ExfilResist,
+
+    /// How sturdy the connections this server makes are.
+    /// Adds to the health of exploits it hosts, and subtracts from the health of exploits against it.
+    ConnectionStrength,
+
+    /// How quietly this server connects to targets, and how quickly it gets back in after being kicked out.
+    Stealth,
+
+    /// Takes away from the potency of `Terminate` effects run against this server.
+    TerminateResist,
+
+    /// How much connection health exploits hosted on this server recover each second while running.
+    ConnectionRegen,
+
+    /// How much faster (or slower) this server runs scripts, as a percentage of its clock speed.
+    ClockSpeedMultiplier,
+
+    /// Adds to the potency of every `Terminate`, `Siphon` and `Exfil` effect this server runs.
+    EffectPotencyBonus,
+
+    /// How easily this server notices incoming connections. Cancels out the [Stealth](ServerStatType::Stealth) of servers exploiting it.
+    Detection,
+}
+
+impl ServerStatType {
+    pub const ALL: [ServerStatType; 9] = [
+        ServerStatType::SiphonResist,
+        ServerStatType::ExfilResist,
+        ServerStatType::ConnectionStrength,
+        ServerStatType::Stealth,
+        ServerStatType::TerminateResist,
+        ServerStatType::ConnectionRegen,
+        ServerStatType::ClockSpeedMultiplier,
+        ServerStatType::EffectPotencyBonus,
+        ServerStatType::Detection,
+    ];
 }
 
-#[derive(Hash, Eq, PartialEq, Clone, Debug)]
-pub struct ServerStatInstance(ServerStatSource, ServerStatType, i32);
+/// A single modification of a stat, and how many ticks it has left, if it ever wears off.
+#[derive(Hash, Eq, PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ServerStatInstance(ServerStatSource, ServerStatType, i32, Option<u32>);
 impl ServerStatInstance {
     pub fn new(source: ServerStatSource, stat_type: ServerStatType, value: i32) -> ServerStatInstance {
-        ServerStatInstance(source, stat_type, value)
+        ServerStatInstance(source, stat_type, value, None)
+    }
+
+    /// Makes a modification that wears off after `duration` ticks.
+    pub fn timed(source: ServerStatSource, stat_type: ServerStatType, value: i32, duration: u32) -> ServerStatInstance {
+        ServerStatInstance(source, stat_type, value, Some(duration))
     }
 
     pub fn source(&self) -> &ServerStatSource {
@@ -193,4 +489,142 @@ impl ServerStatInstance {
     pub fn value(&self) -> i32 {
         self.2
     }
-}
\ No newline at end of file
+
+    /// How many more ticks this modification lasts, or `None` if it's permanent.
+    pub fn remaining_ticks(&self) -> Option<u32> {
+        self.3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rng::RngStreams;
+    use crate::target::definition::corp_definition;
+    use super::*;
+
+    fn script_source(exploit_id: Option<Uuid>) -> ServerStatSource {
+        ServerStatSource::Script { script_id: ScriptId::Id(1), exploit_id }
+    }
+
+    #[test]
+    fn only_running_work_loads_a_server() {
+        let mut rng_streams = RngStreams::new(1234);
+        let server = Arc::new(Mutex::new(Server::empty()));
+        server.lock().unwrap().clock_speed = ClockSpeed::new(2_000_000);
+
+        let script = Arc::new(Mutex::new(Script::new(ScriptId::Id(1), vec![])));
+        let active_exploits = (0..2)
+            .map(|_| {
+                let target = corp_definition().build(&mut rng_streams.next_stream());
+                let active_exploit = ActiveExploit::new(target, script.clone(), server.clone(), 0.into(), false, rng_streams.next_stream());
+                server.lock().unwrap().scheduler.add(active_exploit.id);
+                Arc::new(Mutex::new(active_exploit))
+            })
+            .collect::<Vec<_>>();
+
+        // Both exploits are still connecting, so their shares of the clock sit idle
+        let mut server = server.lock().unwrap();
+        server.reschedule(&active_exploits);
+        assert_eq!(server.load(), 0.0);
+
+        active_exploits[0].lock().unwrap().restore_connection(50, 50, true);
+        server.reschedule(&active_exploits);
+        assert_eq!(server.load(), 0.5);
+        assert_eq!(server.power_draw(), IDLE_POWER_DRAW + POWER_PER_MHZ);
+
+        // The defense only runs against connected exploits, so it's busy too
+        server.set_defensive_script(Some(script.clone()));
+        server.reschedule(&active_exploits);
+        assert_eq!(*server.busy_clock_speed, 1_333_333);
+    }
+
+    #[test]
+    fn timed_modifications_wear_off() {
+        let mut stats = ServerStatInstances::from(&[
+            ServerStatInstance::new(ServerStatSource::Innate, ServerStatType::SiphonResist, 5),
+            ServerStatInstance::timed(script_source(None), ServerStatType::SiphonResist, 3, 2),
+            ServerStatInstance::timed(script_source(None), ServerStatType::SiphonResist, -1, 1),
+        ]);
+
+        assert_eq!(stats.expire(1).len(), 1);
+        assert_eq!(stats.value_of(ServerStatType::SiphonResist), 8);
+        assert_eq!(stats.instances()[1].remaining_ticks(), Some(1));
+
+        assert_eq!(stats.expire(1).len(), 1);
+        assert_eq!(stats.value_of(ServerStatType::SiphonResist), 5);
+        assert!(stats.expire(100).is_empty());
+    }
+
+    #[test]
+    fn innate_stats_stack_into_one_instance() {
+        let mut stats = ServerStatInstances::new();
+        stats.add_innate(ServerStatType::Stealth, 1);
+        stats.add_innate(ServerStatType::Stealth, 2);
+
+        assert_eq!(stats.instances().len(), 1);
+        assert_eq!(stats.innate_value_of(ServerStatType::Stealth), 3);
+    }
+
+    #[test]
+    fn exploits_only_remove_their_own_modifications() {
+        let (exploit_id, other_exploit_id) = (Uuid::from_u128(1), Uuid::from_u128(2));
+        let mut stats = ServerStatInstances::from(&[
+            ServerStatInstance::new(ServerStatSource::Innate, ServerStatType::SiphonResist, 5),
+            ServerStatInstance::new(script_source(Some(exploit_id)), ServerStatType::SiphonResist, 3),
+            ServerStatInstance::timed(script_source(Some(exploit_id)), ServerStatType::ExfilResist, 2, 10),
+            ServerStatInstance::new(script_source(Some(other_exploit_id)), ServerStatType::SiphonResist, 1),
+            ServerStatInstance::new(script_source(None), ServerStatType::SiphonResist, -1),
+        ]);
+
+        assert_eq!(stats.remove_from_exploit(exploit_id).len(), 2);
+        assert_eq!(stats.value_of(ServerStatType::SiphonResist), 5);
+        assert_eq!(stats.value_of(ServerStatType::ExfilResist), 0);
+        assert!(stats.remove_from_exploit(exploit_id).is_empty());
+    }
+
+    #[test]
+    fn purging_removes_the_oldest_opposing_modifications() {
+        let mut stats = ServerStatInstances::from(&[
+            ServerStatInstance::new(ServerStatSource::Innate, ServerStatType::SiphonResist, -5),
+            ServerStatInstance::new(script_source(None), ServerStatType::SiphonResist, -2),
+            ServerStatInstance::new(script_source(None), ServerStatType::SiphonResist, 4),
+            ServerStatInstance::new(script_source(None), ServerStatType::ExfilResist, -3),
+            ServerStatInstance::timed(script_source(None), ServerStatType::SiphonResist, -3, 10),
+        ]);
+
+        let purged = stats.purge(ServerStatType::SiphonResist, 3);
+        assert_eq!(purged, vec![
+            ServerStatInstance::new(script_source(None), ServerStatType::SiphonResist, -2),
+            ServerStatInstance::timed(script_source(None), ServerStatType::SiphonResist, -1, 10),
+        ]);
+        assert_eq!(stats.value_of(ServerStatType::SiphonResist), -5 + 4 - 2);
+        assert_eq!(stats.value_of(ServerStatType::ExfilResist), -3);
+
+        // Innate stats are never purged, no matter how strong the purge
+        assert_eq!(stats.purge(ServerStatType::SiphonResist, 100).len(), 1);
+        assert_eq!(stats.value_of(ServerStatType::SiphonResist), -5 + 4);
+        assert!(stats.purge(ServerStatType::SiphonResist, 100).is_empty());
+
+        assert_eq!(stats.purge(ServerStatType::SiphonResist, -100).len(), 1);
+        assert_eq!(stats.value_of(ServerStatType::SiphonResist), -5);
+    }
+
+    #[test]
+    fn applied_modifications_cancel_out_opposing_ones() {
+        let mut stats = ServerStatInstances::from(&[
+            ServerStatInstance::new(ServerStatSource::Innate, ServerStatType::ExfilResist, 2),
+            ServerStatInstance::new(script_source(None), ServerStatType::ExfilResist, 1),
+        ]);
+
+        let purged = stats.apply_and_purge(ServerStatInstance::new(script_source(None), ServerStatType::ExfilResist, -1));
+        assert_eq!(purged.len(), 1);
+        assert_eq!(stats.instances().len(), 1);
+
+        assert!(stats.apply_and_purge(ServerStatInstance::new(script_source(None), ServerStatType::ExfilResist, -3)).is_empty());
+        assert_eq!(stats.modification_of(ServerStatType::ExfilResist), -3);
+
+        assert_eq!(stats.apply_and_purge(ServerStatInstance::new(script_source(None), ServerStatType::ExfilResist, 5)).len(), 1);
+        assert_eq!(stats.modification_of(ServerStatType::ExfilResist), 2);
+        assert_eq!(stats.instances().len(), 2);
+    }
+}
@@ -0,0 +1,176 @@
+use std::sync::{Arc, Mutex};
+use crate::server::Server;
+
+/// Power every server draws just by being switched on, in watts.
+pub const IDLE_POWER_DRAW: f64 = 20.0;
+
+/// Extra power a server draws for each MHz of clock speed it's using, in watts.
+pub const POWER_PER_MHZ: f64 = 40.0;
+
+/// A rack of servers sharing one power supply and one uplink.
+/// When the servers would draw more power than the rack can supply, they underclock until they fit.
+#[derive(Clone)]
+pub struct Rack {
+    pub name: String,
+
+    /// Watts shared by every server in the rack.
+    pub power_capacity: u32,
+
+    /// Megabits per second shared by every server in the rack.
+    pub bandwidth: u32,
+
+    pub servers: Vec<Arc<Mutex<Server>>>,
+}
+
+impl Rack {
+    /// The rack every player starts with.
+    pub fn starter(servers: Vec<Arc<Mutex<Server>>>) -> Rack {
+        Rack::numbered(1, servers)
+    }
+
+    /// A standard rack, named for being the `number`th one installed.
+    pub fn numbered(number: usize, servers: Vec<Arc<Mutex<Server>>>) -> Rack {
+        Rack {
+            name: format!("rack-{number:02}"),
+            power_capacity: 150,
+            bandwidth: 100,
+            servers,
+        }
+    }
+
+    /// Whether the rack could run `server` alongside the servers it already has, with every one of them
+    /// fully loaded, without anything underclocking.
+    pub fn can_power(&self, server: &Server) -> bool {
+        let max_power_draw = self
+            .servers
+            .iter()
+            .map(|server| server.lock().unwrap().max_power_draw())
+            .sum::<f64>();
+
+        max_power_draw + server.max_power_draw() <= self.power_capacity as f64
+    }
+
+    /// How much power every server in the rack would draw at its full clock speed, in watts.
+    pub fn power_draw(&self) -> f64 {
+        self
+            .servers
+            .iter()
+            .map(|server| server.lock().unwrap().power_draw())
+            .sum()
+    }
+
+    /// How much of the rack's power supply its servers want, where 1.0 is all of it.
+    /// Anything above 1.0 is made up for by underclocking.
+    pub fn power_utilization(&self) -> f64 {
+        self.power_draw() / self.power_capacity as f64
+    }
+
+    /// Each server's share of the rack's bandwidth, in megabits per second.
+    pub fn bandwidth_per_server(&self) -> u32 {
+        self.bandwidth.checked_div(self.servers.len() as u32).unwrap_or(self.bandwidth)
+    }
+
+    /// How much each server's clock speed is scaled by to keep the rack within its power supply.
+    /// Idle draw can't be clocked away, so a rack that can't even idle its servers runs them at nothing.
+    pub fn underclock_factor(&self) -> f64 {
+        let idle_draw = IDLE_POWER_DRAW * self.servers.len() as f64;
+        let load_draw = self.power_draw() - idle_draw;
+        let power_capacity = self.power_capacity as f64;
+
+        if idle_draw + load_draw <= power_capacity {
+            1.0
+        } else {
+            ((power_capacity - idle_draw) / load_draw).max(0.0)
+        }
+    }
+
+    /// Gives every server its share of the rack's bandwidth.
+    pub fn share_bandwidth(&self) {
+        let bandwidth = self.bandwidth_per_server();
+        for server in &self.servers {
+            server.lock().unwrap().uplink.bandwidth = Some(bandwidth);
+        }
+    }
+
+    /// Sets every server's effective clock speed from the rack's current power draw.
+    pub fn underclock(&self) {
+        let factor = self.underclock_factor();
+        for server in &self.servers {
+            let mut server = server.lock().unwrap();
+            let effective_clock_speed = (*server.clock_speed as f64 * factor).floor() as u64;
+            server.effective_clock_speed = effective_clock_speed.into();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::clock_speed::ClockSpeed;
+
+    /// A server with every Hz of its clock speed busy.
+    fn busy_server(clock_speed: u64) -> Arc<Mutex<Server>> {
+        let mut server = Server::empty();
+        server.clock_speed = ClockSpeed::new(clock_speed);
+        server.effective_clock_speed = ClockSpeed::new(clock_speed);
+        server.busy_clock_speed = ClockSpeed::new(clock_speed);
+        Arc::new(Mutex::new(server))
+    }
+
+    fn rack(power_capacity: u32, servers: &[Arc<Mutex<Server>>]) -> Rack {
+        Rack {
+            name: "test".to_string(),
+            power_capacity,
+            bandwidth: 100,
+            servers: servers.to_vec(),
+        }
+    }
+
+    #[test]
+    fn idle_servers_only_draw_idle_power() {
+        let mut server = Server::empty();
+        server.clock_speed = ClockSpeed::new(2_000_000);
+
+        assert_eq!(server.power_draw(), IDLE_POWER_DRAW);
+    }
+
+    #[test]
+    fn racks_within_capacity_run_at_full_speed() {
+        let servers = [busy_server(2_000_000)];
+        let rack = rack(100, &servers);
+        rack.underclock();
+
+        assert_eq!(rack.power_draw(), 100.0);
+        assert_eq!(*servers[0].lock().unwrap().effective_clock_speed, 2_000_000);
+    }
+
+    #[test]
+    fn overloaded_racks_underclock_to_their_capacity() {
+        let servers = [busy_server(2_000_000), busy_server(1_000_000)];
+        let rack = rack(100, &servers);
+        assert_eq!(rack.power_utilization(), 1.6);
+
+        // 40 W goes to idling, leaving 60 W of the 120 W the servers want under load
+        rack.underclock();
+        assert_eq!(*servers[0].lock().unwrap().effective_clock_speed, 1_000_000);
+        assert_eq!(*servers[1].lock().unwrap().effective_clock_speed, 500_000);
+        assert_eq!(rack.bandwidth_per_server(), 50);
+    }
+
+    #[test]
+    fn racks_only_power_servers_they_can_run_flat_out() {
+        let rack = rack(150, &[busy_server(2_000_000)]);
+
+        // 100 W for the existing server under full load, and another 60 W or 40 W for the new one
+        assert!(!rack.can_power(&busy_server(1_000_000).lock().unwrap()));
+        assert!(rack.can_power(&busy_server(500_000).lock().unwrap()));
+    }
+
+    #[test]
+    fn racks_that_cant_idle_their_servers_stop_them() {
+        let servers = [busy_server(2_000_000)];
+        rack(10, &servers).underclock();
+
+        assert_eq!(*servers[0].lock().unwrap().effective_clock_speed, 0);
+    }
+}
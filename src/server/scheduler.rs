@@ -0,0 +1,282 @@
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+use crate::active_exploit::ActiveExploit;
+
+/// The scheduler entry a server's defensive script runs under, alongside the exploits it hosts.
+pub const DEFENSE_ID: Uuid = Uuid::nil();
+
+/// How an exploit's share of its server's clock speed is decided.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Allocation {
+    /// Shares whatever clock speed isn't pinned with the other weighted exploits, in proportion to its weight.
+    Weighted(u32),
+
+    /// Always gets exactly this clock speed, in Hz, ahead of any weighted exploits.
+    Pinned(u64),
+}
+
+impl Default for Allocation {
+    fn default() -> Self {
+        Allocation::Weighted(1)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SchedulerError {
+    /// The exploit isn't scheduled on this server.
+    NotScheduled(Uuid),
+
+    /// Pinning would reserve more clock speed than the server has.
+    OverCapacity { requested: u64, available: u64 },
+}
+
+impl Display for SchedulerError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchedulerError::NotScheduled(exploit_id) => write!(f, "exploit {exploit_id} isn't scheduled on this server"),
+            SchedulerError::OverCapacity { requested, available } => {
+                write!(f, "can't pin {requested} Hz, only {available} Hz is available")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SchedulerError {}
+
+/// Divides a server's clock speed between the exploits it hosts.
+/// The sum of all allocations never exceeds the server's capacity.
+#[derive(Clone, Debug, Default)]
+pub struct CpuScheduler {
+    /// Every scheduled exploit, in the order they were scheduled.
+    entries: Vec<(Uuid, Allocation)>,
+}
+
+impl CpuScheduler {
+    pub fn new() -> CpuScheduler {
+        CpuScheduler { entries: vec![] }
+    }
+
+    pub fn from(entries: &[(Uuid, Allocation)]) -> CpuScheduler {
+        CpuScheduler { entries: entries.to_vec() }
+    }
+
+    pub fn entries(&self) -> &[(Uuid, Allocation)] {
+        &self.entries
+    }
+
+    pub fn is_scheduled(&self, exploit_id: Uuid) -> bool {
+        self.entries.iter().any(|(id, _)| *id == exploit_id)
+    }
+
+    pub fn allocation_of(&self, exploit_id: Uuid) -> Option<Allocation> {
+        self
+            .entries
+            .iter()
+            .find(|(id, _)| *id == exploit_id)
+            .map(|(_, allocation)| *allocation)
+    }
+
+    /// Schedules an exploit with the default weight. Does nothing if it's already scheduled.
+    pub fn add(&mut self, exploit_id: Uuid) {
+        if !self.is_scheduled(exploit_id) {
+            self.entries.push((exploit_id, Allocation::default()));
+        }
+    }
+
+    pub fn remove(&mut self, exploit_id: Uuid) {
+        self.entries.retain(|(id, _)| *id != exploit_id);
+    }
+
+    /// Changes how an exploit's share is decided.
+    /// Pinned allocations are rejected if they'd reserve more than `capacity` between them.
+    pub fn set_allocation(&mut self, exploit_id: Uuid, allocation: Allocation, capacity: u64) -> Result<(), SchedulerError> {
+        if !self.is_scheduled(exploit_id) {
+            return Err(SchedulerError::NotScheduled(exploit_id));
+        }
+
+        if let Allocation::Pinned(requested) = allocation {
+            let available = capacity.saturating_sub(self.pinned_except(exploit_id));
+            if requested > available {
+                return Err(SchedulerError::OverCapacity { requested, available });
+            }
+        }
+
+        for (id, existing) in &mut self.entries {
+            if *id == exploit_id {
+                *existing = allocation;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// How much clock speed is pinned between every scheduled exploit.
+    pub fn pinned(&self) -> u64 {
+        self
+            .entries
+            .iter()
+            .map(|(_, allocation)| match allocation {
+                Allocation::Pinned(clock_speed) => *clock_speed,
+                Allocation::Weighted(_) => 0,
+            })
+            .sum()
+    }
+
+    /// How much clock speed is pinned by exploits other than `exploit_id`.
+    pub fn pinned_except(&self, exploit_id: Uuid) -> u64 {
+        self.pinned() - match self.allocation_of(exploit_id) {
+            Some(Allocation::Pinned(clock_speed)) => clock_speed,
+            _ => 0,
+        }
+    }
+
+    /// Divides `capacity` between every scheduled exploit, in schedule order.
+    /// Pinned exploits are served first; if capacity has dropped below what's pinned, pins are scaled down to fit.
+    /// Weighted exploits split the rest in proportion to their weights.
+    pub fn allocations(&self, capacity: u64) -> Vec<(Uuid, u64)> {
+        let pinned = self.pinned();
+        let pinned_capacity = pinned.min(capacity);
+        let weighted_capacity = capacity - pinned_capacity;
+        let total_weight = self
+            .entries
+            .iter()
+            .map(|(_, allocation)| match allocation {
+                Allocation::Weighted(weight) => *weight as u64,
+                Allocation::Pinned(_) => 0,
+            })
+            .sum::<u64>();
+
+        let mut pinned_leftover = pinned_capacity;
+        let mut weighted_leftover = weighted_capacity;
+        let mut allocations = self
+            .entries
+            .iter()
+            .map(|(id, allocation)| {
+                let clock_speed = match *allocation {
+                    Allocation::Pinned(clock_speed) => {
+                        let share = proportion(pinned_capacity, clock_speed, pinned);
+                        pinned_leftover -= share;
+                        share
+                    }
+                    Allocation::Weighted(weight) => {
+                        let share = proportion(weighted_capacity, weight as u64, total_weight);
+                        weighted_leftover -= share;
+                        share
+                    }
+                };
+
+                (*id, clock_speed)
+            })
+            .collect::<Vec<_>>();
+
+        // Rounding down leaves a few Hz behind in each pool; hand them out one at a time from the front
+        for ((_, allocation), (_, clock_speed)) in self.entries.iter().zip(&mut allocations) {
+            let leftover = match allocation {
+                Allocation::Pinned(0) | Allocation::Weighted(0) => continue,
+                Allocation::Pinned(_) => &mut pinned_leftover,
+                Allocation::Weighted(_) => &mut weighted_leftover,
+            };
+
+            if *leftover > 0 {
+                *leftover -= 1;
+                *clock_speed += 1;
+            }
+        }
+
+        allocations
+    }
+
+    /// Sets the clock allocation of every exploit scheduled here from the server's `capacity`.
+    pub fn apply(&self, capacity: u64, active_exploits: &[Arc<Mutex<ActiveExploit>>]) {
+        let allocations = self.allocations(capacity);
+        for exploit in active_exploits {
+            let mut exploit = exploit.lock().unwrap();
+            if let Some((_, clock_speed)) = allocations.iter().find(|(id, _)| *id == exploit.id) {
+                exploit.clock_allocation = (*clock_speed).into();
+            }
+        }
+    }
+}
+
+/// `total * part / whole`, without overflowing.
+fn proportion(total: u64, part: u64, whole: u64) -> u64 {
+    (total as u128 * part as u128)
+        .checked_div(whole as u128)
+        .unwrap_or(0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler_of(allocations: &[Allocation]) -> (CpuScheduler, Vec<Uuid>) {
+        let ids = allocations.iter().map(|_| Uuid::new_v4()).collect::<Vec<_>>();
+        let entries = ids.iter().copied().zip(allocations.iter().copied()).collect::<Vec<_>>();
+        (CpuScheduler::from(&entries), ids)
+    }
+
+    fn clock_speeds(scheduler: &CpuScheduler, capacity: u64) -> Vec<u64> {
+        scheduler.allocations(capacity).into_iter().map(|(_, clock_speed)| clock_speed).collect()
+    }
+
+    #[test]
+    fn weighted_exploits_share_all_of_the_capacity() {
+        let (scheduler, _) = scheduler_of(&[Allocation::Weighted(1), Allocation::Weighted(2), Allocation::Weighted(1)]);
+
+        assert_eq!(clock_speeds(&scheduler, 1000), vec![250, 500, 250]);
+
+        // Rounding never loses or invents clock speed
+        assert_eq!(clock_speeds(&scheduler, 1001).iter().sum::<u64>(), 1001);
+        assert_eq!(clock_speeds(&scheduler, 3).iter().sum::<u64>(), 3);
+    }
+
+    #[test]
+    fn pinned_exploits_are_served_first() {
+        let (scheduler, _) = scheduler_of(&[Allocation::Weighted(1), Allocation::Pinned(700), Allocation::Weighted(3)]);
+
+        assert_eq!(clock_speeds(&scheduler, 1000), vec![75, 700, 225]);
+    }
+
+    #[test]
+    fn pins_are_scaled_down_when_capacity_shrinks() {
+        let (scheduler, _) = scheduler_of(&[Allocation::Pinned(600), Allocation::Pinned(200), Allocation::Weighted(1)]);
+
+        assert_eq!(clock_speeds(&scheduler, 400), vec![300, 100, 0]);
+    }
+
+    #[test]
+    fn pinning_over_capacity_is_rejected() {
+        let (mut scheduler, ids) = scheduler_of(&[Allocation::Pinned(600), Allocation::Weighted(1)]);
+
+        assert_eq!(
+            scheduler.set_allocation(ids[1], Allocation::Pinned(500), 1000),
+            Err(SchedulerError::OverCapacity { requested: 500, available: 400 })
+        );
+        assert_eq!(scheduler.allocation_of(ids[1]), Some(Allocation::Weighted(1)));
+
+        // An exploit's own pin doesn't count against it
+        assert_eq!(scheduler.set_allocation(ids[0], Allocation::Pinned(1000), 1000), Ok(()));
+
+        let unscheduled = Uuid::new_v4();
+        assert_eq!(
+            scheduler.set_allocation(unscheduled, Allocation::Weighted(1), 1000),
+            Err(SchedulerError::NotScheduled(unscheduled))
+        );
+    }
+
+    #[test]
+    fn removing_an_exploit_hands_its_share_to_the_rest() {
+        let (mut scheduler, ids) = scheduler_of(&[Allocation::Weighted(1), Allocation::Weighted(1)]);
+        assert_eq!(clock_speeds(&scheduler, 1000), vec![500, 500]);
+
+        scheduler.remove(ids[0]);
+        assert_eq!(clock_speeds(&scheduler, 1000), vec![1000]);
+
+        // Adding is idempotent
+        scheduler.add(ids[1]);
+        scheduler.add(ids[0]);
+        assert_eq!(clock_speeds(&scheduler, 1000), vec![500, 500]);
+    }
+}
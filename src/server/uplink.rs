@@ -0,0 +1,105 @@
+use std::time::Duration;
+
+/// Megabits of bandwidth each credit siphoned through a server takes.
+const MEGABITS_PER_CREDIT: f64 = 1.0;
+
+/// Megabits of bandwidth an exfiltrated algorithm takes.
+const MEGABITS_PER_EXFIL: f64 = 50.0;
+
+/// A server's connection to the outside world, which everything it takes from targets has to fit through.
+#[derive(Clone, Default)]
+pub struct Uplink {
+    /// This server's share of its rack's bandwidth, in megabits per second.
+    /// Servers outside of a rack have no limit.
+    pub bandwidth: Option<u32>,
+
+    /// Megabits that can still be transferred. Refills with bandwidth as time passes, up to a second's worth,
+    /// and goes negative when an exfil takes more than was left.
+    budget: f64,
+}
+
+impl Uplink {
+    /// Refills the budget with `elapsed` worth of bandwidth.
+    pub fn refill(&mut self, elapsed: Duration) {
+        if let Some(bandwidth) = self.bandwidth {
+            let bandwidth = bandwidth as f64;
+            self.budget = (self.budget + bandwidth * elapsed.as_secs_f64()).min(bandwidth);
+        }
+    }
+
+    /// Takes as many of `credits` as there's bandwidth left for, returning how many that was.
+    pub fn take_credits(&mut self, credits: u64) -> u64 {
+        if self.bandwidth.is_none() {
+            return credits;
+        }
+
+        let transferable = credits.min((self.budget.max(0.0) / MEGABITS_PER_CREDIT).floor() as u64);
+        self.budget -= transferable as f64 * MEGABITS_PER_CREDIT;
+
+        transferable
+    }
+
+    /// Takes bandwidth for an exfiltrated algorithm, returning false if there's none left.
+    /// Any bandwidth left is enough to start an exfil, so the uplink may be left owing.
+    pub fn take_exfil(&mut self) -> bool {
+        if self.bandwidth.is_none() {
+            return true;
+        }
+
+        if self.budget <= 0.0 {
+            return false;
+        }
+
+        self.budget -= MEGABITS_PER_EXFIL;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TIME_BETWEEN_TICKS;
+    use super::*;
+
+    fn uplink(bandwidth: u32) -> Uplink {
+        let mut uplink = Uplink { bandwidth: Some(bandwidth), budget: 0.0 };
+        uplink.refill(Duration::from_secs(1));
+        uplink
+    }
+
+    #[test]
+    fn servers_outside_racks_transfer_everything() {
+        let mut uplink = Uplink::default();
+
+        assert_eq!(uplink.take_credits(u64::MAX), u64::MAX);
+        assert!(uplink.take_exfil());
+    }
+
+    #[test]
+    fn bandwidth_caps_credits_and_refills_over_time() {
+        let mut uplink = uplink(100);
+        assert_eq!(uplink.take_credits(60), 60);
+        assert_eq!(uplink.take_credits(60), 40);
+        assert_eq!(uplink.take_credits(60), 0);
+
+        uplink.refill(Duration::from_millis(500));
+        assert_eq!(uplink.take_credits(60), 50);
+
+        // Unused bandwidth only builds up to a second's worth
+        uplink.refill(Duration::from_secs(10));
+        assert_eq!(uplink.take_credits(1_000), 100);
+    }
+
+    #[test]
+    fn exfils_wait_for_bandwidth() {
+        let mut uplink = uplink(20);
+        assert!(uplink.take_exfil());
+        assert!(!uplink.take_exfil());
+        assert_eq!(uplink.take_credits(10), 0);
+
+        // Paying off the 30 Mb it went over takes a second and a half
+        uplink.refill(Duration::from_millis(1_500));
+        assert!(!uplink.take_exfil());
+        uplink.refill(TIME_BETWEEN_TICKS);
+        assert!(uplink.take_exfil());
+    }
+}
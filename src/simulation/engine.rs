@@ -0,0 +1,990 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::active_exploit::{ActiveExploit, ActiveExploitStatus, ExploitTarget};
+use rand::{Rng, RngExt};
+use crate::algorithm::effect::{AlgorithmEffect, application::AlgorithmEffectApplication, target::AlgorithmEffectTarget, value::{AlgorithmEffectValue, AlgorithmEffectValueT}};
+use crate::algorithm::generator::{AlgorithmGenerator, GenerationBudget};
+use crate::event::exploit_event::ExploitEvent;
+use crate::lock_and_clone;
+use crate::server::{Server, ServerStatInstance, ServerStatSource, ServerStatType, ServerStats};
+use crate::simulation::event::SimulationEvent;
+
+/// The share of a target's remaining credits each point of siphon potency past its resist takes.
+const SIPHON_SHARE_PER_POTENCY: f64 = 0.001;
+
+/// Advances exploits through time, independently of Bevy.
+/// The engine only mutates the exploits (and the servers and scripts they reference);
+/// everything that should happen to the player as a result is returned as [SimulationEvent]s.
+pub struct SimulationEngine<'a> {
+    active_exploits: &'a [Arc<Mutex<ActiveExploit>>],
+
+    /// Targets that carry on refilling their credit pools, on top of those being exploited.
+    targets: &'a [Arc<Mutex<ExploitTarget>>],
+}
+
+impl<'a> SimulationEngine<'a> {
+    pub fn new(active_exploits: &'a [Arc<Mutex<ActiveExploit>>]) -> SimulationEngine<'a> {
+        SimulationEngine {
+            active_exploits,
+            targets: &[],
+        }
+    }
+
+    /// Also refills the credit pools of `targets`, whether or not anyone is exploiting them.
+    pub fn with_targets(mut self, targets: &'a [Arc<Mutex<ExploitTarget>>]) -> SimulationEngine<'a> {
+        self.targets = targets;
+        self
+    }
+
+    /// Advances every exploit by `elapsed` of wall time, as though a single tick covered all of it.
+    /// Scripts run for as long as their clock speed allows in `elapsed`, while timers count down `game_ticks`;
+    /// the caller decides how many whole ticks `elapsed` makes up.
+    pub fn tick(&self, elapsed: Duration, game_ticks: u32) -> Vec<SimulationEvent> {
+        let mut events = vec![];
+
+        let mut pending_effects = vec![];
+        let mut hosting_servers: Vec<Arc<Mutex<Server>>> = vec![];
+        for active_exploit in self.active_exploits {
+            let (new_host_effects, new_target_effects) = {
+                let mut active_exploit = active_exploit.lock().unwrap();
+                // Servers hosting several exploits share one uplink, so it only refills once per tick
+                if !hosting_servers.iter().any(|existing| Arc::ptr_eq(existing, &active_exploit.hosting_server)) {
+                    active_exploit.hosting_server.lock().unwrap().uplink.refill(elapsed);
+                    hosting_servers.push(active_exploit.hosting_server.clone());
+                }
+
+                // Allocations are handed out from the host's effective clock speed, so they already account for underclocking
+                let server_speed = *active_exploit.clock_allocation as f64
+                    * active_exploit.hosting_server.lock().unwrap().clock_speed_multiplier();
+                let ticks_since_last = (server_speed * elapsed.as_secs_f64()).floor() as u64;
+
+                let target_server_speed = {
+                    let target = active_exploit.target.lock().unwrap();
+                    let target_server = target.server.lock().unwrap();
+                    *target_server.effective_clock_speed as f64 * target_server.clock_speed_multiplier()
+                };
+                let target_ticks_since_last = (target_server_speed * elapsed.as_secs_f64()).floor() as u64;
+
+                active_exploit.tick(&mut events, ticks_since_last, target_ticks_since_last, game_ticks)
+            };
+
+            pending_effects.push((active_exploit, new_host_effects));
+            pending_effects.push((active_exploit, new_target_effects));
+        }
+
+        // Each server runs its defensive script once, on its own share of the clock, however many exploits it hosts.
+        // Its effects take turns between the exploits making progress.
+        for server in &hosting_servers {
+            let executing = self
+                .active_exploits
+                .iter()
+                .filter_map(|active_exploit| {
+                    let exploit = active_exploit.lock().unwrap();
+                    let target_server = exploit.target.lock().unwrap().server.clone();
+                    (Arc::ptr_eq(&exploit.hosting_server, server) && exploit.is_executing())
+                        .then_some((active_exploit, target_server))
+                })
+                .collect::<Vec<_>>();
+            if executing.is_empty() {
+                continue;
+            }
+
+            let mut host = server.lock().unwrap();
+            let Some(defensive_script) = host.defensive_script.clone() else {
+                continue;
+            };
+
+            let ticks = (*host.defense.clock_allocation as f64 * host.clock_speed_multiplier() * elapsed.as_secs_f64()).floor() as u64;
+            for effect in host.tick_defense(ticks) {
+                let (active_exploit, target_server) = &executing[host.next_defense_turn(executing.len())];
+                pending_effects.push((active_exploit, vec![AlgorithmEffectApplication {
+                    host_server: server.clone(),
+                    target_server: target_server.clone(),
+                    effect,
+                    script: defensive_script.clone(),
+                }]));
+            }
+        }
+
+        for (active_exploit, pending_effects) in pending_effects {
+            for pending_effect in pending_effects {
+                apply_effect(&mut events, pending_effect, active_exploit);
+            }
+        }
+
+        // Timed modifications wear off as ticks pass, no matter how many exploits share a server
+        let mut servers: Vec<Arc<Mutex<Server>>> = vec![];
+        for active_exploit in self.active_exploits {
+            let active_exploit = active_exploit.lock().unwrap();
+            let target_server = active_exploit.target.lock().unwrap().server.clone();
+            for server in [active_exploit.hosting_server.clone(), target_server] {
+                if !servers.iter().any(|existing| Arc::ptr_eq(existing, &server)) {
+                    servers.push(server);
+                }
+            }
+        }
+
+        for server in servers {
+            server.lock().unwrap().stats.expire(game_ticks);
+        }
+
+        // Credit pools refill once per tick, no matter how many exploits share a target
+        let mut targets: Vec<Arc<Mutex<ExploitTarget>>> = self.targets.to_vec();
+        for active_exploit in self.active_exploits {
+            let target = lock_and_clone!(active_exploit, target);
+            if !targets.iter().any(|existing| Arc::ptr_eq(existing, &target)) {
+                targets.push(target);
+            }
+        }
+
+        for target in targets {
+            target.lock().unwrap().regenerate_credits(game_ticks);
+        }
+
+        for active_exploit in self.active_exploits {
+            let mut active_exploit = active_exploit.lock().unwrap();
+            if matches!(active_exploit.status(), ActiveExploitStatus::Disconnected)
+                && *active_exploit.auto_reconnect.lock().unwrap()
+            {
+                active_exploit.restart();
+            }
+        }
+
+        events
+    }
+}
+
+/// Applies a single algorithm effect to whichever side of `active_exploit` it targets.
+fn apply_effect(
+    events: &mut Vec<SimulationEvent>,
+    application: AlgorithmEffectApplication,
+    active_exploit: &Arc<Mutex<ActiveExploit>>,
+) {
+    let mut active_exploit = active_exploit.lock().unwrap();
+    let rng = active_exploit.rng.clone();
+    let rng = &mut *rng.lock().unwrap();
+    let from_player_server = {
+        let host_server_name = lock_and_clone!(active_exploit.hosting_server, name);
+        let application_server_name = lock_and_clone!(application.host_server, name);
+
+        host_server_name == application_server_name
+    };
+    let potency_bonus = application.host_server.lock().unwrap().stats.value_of(ServerStatType::EffectPotencyBonus);
+    match application.effect {
+        AlgorithmEffect::Terminate { potency }  => {
+            let value = potency.make_value(rng) + potency_bonus;
+            let terminate_resist = application.target_server.lock().unwrap().stats.value_of(ServerStatType::TerminateResist);
+            let damage = (value - terminate_resist).max(0);
+
+            // The player terminates the target's defensive process; the target terminates the connection itself
+            let value_after_modification = if from_player_server {
+                if damage > 0 {
+                    active_exploit.target.lock().unwrap().interrupt();
+                }
+
+                None
+            } else {
+                let old_health = *active_exploit.connection_current_health.lock().unwrap();
+                let new_health = old_health.saturating_sub(damage.unsigned_abs());
+                *active_exploit.connection_current_health.lock().unwrap() = new_health;
+
+                if new_health == 0 {
+                    active_exploit.stop_execution();
+
+                    if old_health > 0 {
+                        active_exploit.clear_modifications();
+                        events.push(SimulationEvent::Disconnected { exploit_id: active_exploit.id });
+                    }
+                }
+
+                Some(new_health.min(AlgorithmEffectValueT::MAX as u32) as AlgorithmEffectValueT)
+            };
+
+            events.push(SimulationEvent::EffectApplied(ExploitEvent {
+                active_exploit_id: active_exploit.id,
+                from_player_server,
+                algorithm_effect: AlgorithmEffect::Terminate { potency },
+                potency_roll: Some(value),
+                value_after_modification,
+                purged: vec![],
+            }));
+        }
+        AlgorithmEffect::Siphon { potency } => {
+            let value = potency.make_value(rng) + potency_bonus;
+            let target_server = application.target_server.lock().unwrap();
+            let target_stats = &target_server.stats;
+
+            let target_defense = target_stats.value_of(ServerStatType::SiphonResist);
+
+            let siphon_potency = (value - target_defense).max(0);
+
+            // The player takes a share of whatever the target has left, so richer targets pay out more
+            // and every siphon drains the pool a little further, as much as the host's uplink can carry.
+            // Targets just take what got past the player's defenses.
+            let siphon_value = if from_player_server {
+                let mut target = active_exploit.target.lock().unwrap();
+                let share = (siphon_potency as f64 * target.credits as f64 * SIPHON_SHARE_PER_POTENCY).ceil() as u64;
+                let transferable = application.host_server.lock().unwrap().uplink.take_credits(share.min(target.credits));
+                target.siphon_credits(transferable) as i64
+            } else {
+                siphon_potency as i64
+            };
+
+            // Targets siphon from the player just as the player siphons from them
+            events.push(SimulationEvent::CreditsSiphoned {
+                script_id: application.script.lock().unwrap().id.clone(),
+                credits: if from_player_server { siphon_value } else { -siphon_value },
+            });
+
+            events.push(SimulationEvent::EffectApplied(ExploitEvent {
+                active_exploit_id: active_exploit.id,
+                from_player_server,
+                algorithm_effect: AlgorithmEffect::Siphon { potency },
+                potency_roll: Some(value),
+                value_after_modification: Some(i32::try_from(siphon_value).unwrap_or(i32::MAX)),
+                purged: vec![],
+            }));
+        }
+        AlgorithmEffect::Exfil { potency } => {
+            let value = potency.make_value(rng) + potency_bonus;
+            let target_server = application.target_server.lock().unwrap();
+            let target_stats = &target_server.stats;
+            let target_defense = target_stats.value_of(ServerStatType::ExfilResist);
+            let exfil_value = value - target_defense;
+            if exfil_value <= 0 {
+                return;
+            }
+
+            // The algorithm never makes it back if the host's uplink is saturated
+            if from_player_server && !application.host_server.lock().unwrap().uplink.take_exfil() {
+                return;
+            }
+
+            if from_player_server {
+                // The further past the target's defenses, and the harder the target, the better the loot
+                let tier = active_exploit.target.lock().unwrap().tier;
+                let budget = GenerationBudget::from_exfil(exfil_value, tier, rng);
+                let algorithm = AlgorithmGenerator::generate(budget, rng);
+
+                events.push(SimulationEvent::AlgorithmExfiltrated { algorithm });
+            } else {
+                events.push(SimulationEvent::AlgorithmStolen { pick: rng.random_range(0..u64::MAX) });
+            }
+
+            events.push(SimulationEvent::EffectApplied(ExploitEvent {
+                active_exploit_id: active_exploit.id,
+                from_player_server,
+                algorithm_effect: AlgorithmEffect::Exfil { potency },
+                potency_roll: Some(value),
+                value_after_modification: Some(exfil_value),
+                purged: vec![],
+            }));
+        }
+        AlgorithmEffect::Modify { target, stat, potency, duration } => {
+            let server = match target {
+                AlgorithmEffectTarget::SelfServer => application.host_server,
+                AlgorithmEffectTarget::TargetServer => application.target_server,
+            };
+
+            let script_id = application.script.lock().unwrap().id.clone();
+
+            let potency_roll = potency.make_value(rng);
+
+            let source = ServerStatSource::Script { script_id, exploit_id: Some(active_exploit.id) };
+            let stat_instance = match duration {
+                Some(duration) => ServerStatInstance::timed(source, stat.to_owned(), potency_roll, duration),
+                None => ServerStatInstance::new(source, stat.to_owned(), potency_roll),
+            };
+            let purged = server.lock().unwrap().stats.apply_and_purge(stat_instance);
+
+            let new_value = server.lock().unwrap().stats.value_of(stat.to_owned());
+
+            events.push(SimulationEvent::EffectApplied(ExploitEvent {
+                active_exploit_id: active_exploit.id,
+                from_player_server,
+                algorithm_effect: AlgorithmEffect::Modify { target, stat, potency, duration },
+                potency_roll: Some(potency_roll),
+                value_after_modification: Some(new_value),
+                purged,
+            }));
+        }
+        AlgorithmEffect::Purge { target, stat, potency } => {
+            let server = match target {
+                AlgorithmEffectTarget::SelfServer => application.host_server,
+                AlgorithmEffectTarget::TargetServer => application.target_server,
+            };
+
+            let Some((potency_roll, purged)) = purge(&server, &target, std::slice::from_ref(&stat), &potency, rng) else {
+                return;
+            };
+
+            let new_value = server.lock().unwrap().stats.value_of(stat.to_owned());
+
+            events.push(SimulationEvent::EffectApplied(ExploitEvent {
+                active_exploit_id: active_exploit.id,
+                from_player_server,
+                algorithm_effect: AlgorithmEffect::Purge { target, stat, potency },
+                potency_roll: Some(potency_roll),
+                value_after_modification: Some(new_value),
+                purged,
+            }));
+        }
+        AlgorithmEffect::PurgeAll { target, potency } => {
+            let server = match target {
+                AlgorithmEffectTarget::SelfServer => application.host_server,
+                AlgorithmEffectTarget::TargetServer => application.target_server,
+            };
+
+            let Some((potency_roll, purged)) = purge(&server, &target, &ServerStatType::ALL, &potency, rng) else {
+                return;
+            };
+
+            events.push(SimulationEvent::EffectApplied(ExploitEvent {
+                active_exploit_id: active_exploit.id,
+                from_player_server,
+                algorithm_effect: AlgorithmEffect::PurgeAll { target, potency },
+                potency_roll: Some(potency_roll),
+                value_after_modification: None,
+                purged,
+            }));
+        }
+    }
+}
+
+/// Purges each of `stats` on `server`, spending the full potency roll on each.
+/// Purging only ever undoes debuffs on ourselves and buffs on the opponent, depending on `target`.
+/// Returns the potency roll and everything that was purged, or `None` if there was nothing to purge.
+fn purge(
+    server: &Arc<Mutex<Server>>,
+    target: &AlgorithmEffectTarget,
+    stats: &[ServerStatType],
+    potency: &AlgorithmEffectValue,
+    rng: &mut impl Rng,
+) -> Option<(AlgorithmEffectValueT, Vec<ServerStatInstance>)> {
+    let is_self = matches!(target, AlgorithmEffectTarget::SelfServer);
+
+    let mut server = server.lock().unwrap();
+    let has_purgable_modifications = stats.iter().any(|stat| {
+        let modification = server.stats.modification_of(stat.to_owned());
+        modification < 0 && is_self || modification > 0 && !is_self
+    });
+    if !has_purgable_modifications {
+        return None;
+    }
+
+    let potency_roll = {
+        if is_self {
+            potency.make_value(rng).max(0)
+        } else {
+            potency.make_value(rng).min(0)
+        }
+    };
+
+    let purged = stats
+        .iter()
+        .flat_map(|stat| server.stats.purge(stat.to_owned(), potency_roll))
+        .collect();
+
+    Some((potency_roll, purged))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::active_exploit::BASE_RECONNECT_DELAY;
+    use crate::algorithm::algorithm::Algorithm;
+    use crate::algorithm::effect::every_effect;
+    use crate::algorithm::effect::value::AlgorithmEffectValue;
+    use crate::algorithm::procedure::AlgorithmProcedure;
+    use crate::player_state::state::PlayerState;
+    use crate::script::id::ScriptId;
+    use crate::script::Script;
+    use crate::target::definition::corp_definition;
+    use crate::{TICKS_PER_SECOND, TIME_BETWEEN_TICKS};
+    use super::*;
+
+    /// Makes an exploit running every starting algorithm of a fresh `seed` game against its known target.
+    fn make_exploit(seed: u64, auto_reconnect: bool) -> Arc<Mutex<ActiveExploit>> {
+        let mut player_state = PlayerState::new(seed);
+        let procedure = AlgorithmProcedure::from(&player_state.inventory.algorithms);
+        let script = Arc::new(Mutex::new(Script::new(ScriptId::Id(1), vec![Arc::new(Mutex::new(procedure))])));
+
+        Arc::new(Mutex::new(ActiveExploit::new(
+            corp_definition().build(&mut player_state.rng_streams.next_stream()),
+            script,
+            player_state.servers[0].clone(),
+            2_000_000.into(),
+            auto_reconnect,
+            player_state.rng_streams.next_stream(),
+        )))
+    }
+
+    fn run(engine: &SimulationEngine, duration: Duration) -> Vec<SimulationEvent> {
+        let ticks = duration.as_millis() / TIME_BETWEEN_TICKS.as_millis();
+        (0..ticks).flat_map(|_| engine.tick(TIME_BETWEEN_TICKS, 1)).collect()
+    }
+
+    #[test]
+    fn exploits_connect_before_siphoning() {
+        let active_exploits = [make_exploit(1234, false)];
+        let events = run(&SimulationEngine::new(&active_exploits), Duration::from_secs(60));
+
+        let connected_at = events
+            .iter()
+            .position(|event| matches!(event, SimulationEvent::Connected { .. }))
+            .expect("exploit never connected");
+        let siphoned_at = events
+            .iter()
+            .position(|event| matches!(event, SimulationEvent::CreditsSiphoned { .. }))
+            .expect("exploit never siphoned");
+
+        assert!(connected_at < siphoned_at);
+    }
+
+    #[test]
+    fn targets_disconnect_exploits_without_auto_reconnect() {
+        let active_exploits = [make_exploit(1234, false)];
+        let exploit_id = active_exploits[0].lock().unwrap().id;
+        let events = run(&SimulationEngine::new(&active_exploits), Duration::from_secs(60));
+
+        let disconnects = events
+            .iter()
+            .filter(|event| matches!(event, SimulationEvent::Disconnected { exploit_id: id } if *id == exploit_id))
+            .count();
+
+        assert_eq!(disconnects, 1);
+        assert!(matches!(active_exploits[0].lock().unwrap().status(), ActiveExploitStatus::Disconnected));
+    }
+
+    #[test]
+    fn auto_reconnect_restarts_disconnected_exploits() {
+        let active_exploits = [make_exploit(1234, true)];
+        let events = run(&SimulationEngine::new(&active_exploits), Duration::from_secs(60));
+
+        let connects = events.iter().filter(|event| matches!(event, SimulationEvent::Connected { .. })).count();
+        let disconnects = events.iter().filter(|event| matches!(event, SimulationEvent::Disconnected { .. })).count();
+
+        assert!(disconnects > 0);
+        assert!(connects >= disconnects);
+    }
+
+    /// Applies `effect` to a connected exploit, as though the player (or the target) ran it.
+    fn resolve(effect: AlgorithmEffect, from_player_server: bool) -> (Arc<Mutex<ActiveExploit>>, Vec<SimulationEvent>) {
+        let active_exploit = make_exploit(1234, false);
+        active_exploit.lock().unwrap().restore_connection(50, 50, true);
+
+        let events = apply(&active_exploit, effect, from_player_server);
+        (active_exploit, events)
+    }
+
+    /// Applies `effect` to `active_exploit`, as though the player (or the target) ran it.
+    fn apply(active_exploit: &Arc<Mutex<ActiveExploit>>, effect: AlgorithmEffect, from_player_server: bool) -> Vec<SimulationEvent> {
+        let application = {
+            let active_exploit = active_exploit.lock().unwrap();
+            let target = active_exploit.target.lock().unwrap();
+            let (host_server, target_server, script) = if from_player_server {
+                (active_exploit.hosting_server.clone(), target.server.clone(), active_exploit.script.clone())
+            } else {
+                (target.server.clone(), active_exploit.hosting_server.clone(), target.script.clone())
+            };
+
+            AlgorithmEffectApplication { host_server, target_server, effect, script }
+        };
+
+        let mut events = vec![];
+        apply_effect(&mut events, application, active_exploit);
+        events
+    }
+
+    #[test]
+    fn every_effect_resolves_in_both_directions() {
+        for effect in every_effect() {
+            for from_player_server in [true, false] {
+                let (_, events) = resolve(effect.clone(), from_player_server);
+
+                for event in events {
+                    if let SimulationEvent::EffectApplied(exploit_event) = event {
+                        assert_eq!(exploit_event.from_player_server, from_player_server);
+                        assert!(!exploit_event.as_mixed_text().is_empty());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn terminate_interrupts_targets_and_damages_connections() {
+        let terminate = AlgorithmEffect::Terminate { potency: AlgorithmEffectValue::Static(20) };
+
+        let (active_exploit, _) = resolve(terminate.clone(), true);
+        assert_eq!(*active_exploit.lock().unwrap().connection_current_health.lock().unwrap(), 50);
+
+        let (active_exploit, events) = resolve(terminate, false);
+        assert_eq!(*active_exploit.lock().unwrap().connection_current_health.lock().unwrap(), 30);
+        assert!(events.iter().any(|event| matches!(
+            event,
+            SimulationEvent::EffectApplied(ExploitEvent { value_after_modification: Some(30), .. })
+        )));
+    }
+
+    #[test]
+    fn targets_siphon_and_exfil_from_the_player() {
+        let siphon = AlgorithmEffect::Siphon { potency: AlgorithmEffectValue::Static(100) };
+        let (_, events) = resolve(siphon, false);
+        assert!(events.iter().any(|event| matches!(event, SimulationEvent::CreditsSiphoned { credits, .. } if *credits < 0)));
+
+        let exfil = AlgorithmEffect::Exfil { potency: AlgorithmEffectValue::Static(100) };
+        let (_, events) = resolve(exfil.clone(), false);
+        assert!(events.iter().any(|event| matches!(event, SimulationEvent::AlgorithmStolen { .. })));
+        assert!(!events.iter().any(|event| matches!(event, SimulationEvent::AlgorithmExfiltrated { .. })));
+
+        let (_, events) = resolve(exfil, true);
+        assert!(events.iter().any(|event| matches!(event, SimulationEvent::AlgorithmExfiltrated { .. })));
+        assert!(!events.iter().any(|event| matches!(event, SimulationEvent::AlgorithmStolen { .. })));
+    }
+
+    /// Has the player siphon for 10 past the corporation's resist, when it has `credits` left in its pool
+    /// and the player's server has a second's worth of `bandwidth` to carry them.
+    fn siphoned_from(credits: u64, bandwidth: Option<u32>) -> (i64, u64) {
+        let active_exploit = make_exploit(1234, false);
+        active_exploit.lock().unwrap().restore_connection(50, 50, true);
+        {
+            let host_server = lock_and_clone!(active_exploit, hosting_server);
+            let mut host_server = host_server.lock().unwrap();
+            host_server.uplink.bandwidth = bandwidth;
+            host_server.uplink.refill(Duration::from_secs(1));
+        }
+        let target = lock_and_clone!(active_exploit, target);
+        target.lock().unwrap().credits = credits;
+
+        let resist = target.lock().unwrap().server.lock().unwrap().stats.value_of(ServerStatType::SiphonResist);
+        let events = apply(&active_exploit, AlgorithmEffect::Siphon { potency: AlgorithmEffectValue::Static(10 + resist) }, true);
+        let siphoned = events
+            .iter()
+            .find_map(|event| match event {
+                SimulationEvent::CreditsSiphoned { credits, .. } => Some(*credits),
+                _ => None,
+            })
+            .unwrap();
+
+        let credits_left = target.lock().unwrap().credits;
+        (siphoned, credits_left)
+    }
+
+    #[test]
+    fn siphons_scale_with_the_targets_credit_pool() {
+        assert_eq!(siphoned_from(5_000, None), (50, 4_950));
+        assert_eq!(siphoned_from(1_000, None), (10, 990));
+        assert_eq!(siphoned_from(50, None), (1, 49));
+        assert_eq!(siphoned_from(0, None), (0, 0));
+    }
+
+    #[test]
+    fn siphons_are_capped_by_bandwidth() {
+        assert_eq!(siphoned_from(5_000, Some(100)), (50, 4_950));
+
+        // Whatever doesn't fit through the uplink stays with the target
+        assert_eq!(siphoned_from(5_000, Some(20)), (20, 4_980));
+    }
+
+    #[test]
+    fn exfils_need_bandwidth() {
+        let exfiltrated = |bandwidth| {
+            let active_exploit = make_exploit(1234, false);
+            active_exploit.lock().unwrap().restore_connection(50, 50, true);
+            {
+                let host_server = lock_and_clone!(active_exploit, hosting_server);
+                let mut host_server = host_server.lock().unwrap();
+                host_server.uplink.bandwidth = Some(bandwidth);
+                host_server.uplink.refill(Duration::from_secs(1));
+            }
+
+            let exfil = AlgorithmEffect::Exfil { potency: AlgorithmEffectValue::Static(100) };
+            apply(&active_exploit, exfil, true)
+                .iter()
+                .any(|event| matches!(event, SimulationEvent::AlgorithmExfiltrated { .. }))
+        };
+
+        assert!(exfiltrated(100));
+        assert!(!exfiltrated(0));
+    }
+
+    #[test]
+    fn credit_pools_refill_over_time() {
+        let active_exploit = make_exploit(1234, false);
+        let target = lock_and_clone!(active_exploit, target);
+        let mut target = target.lock().unwrap();
+        let max_credits = target.max_credits;
+        target.siphon_credits(max_credits);
+
+        target.regenerate_credits(TICKS_PER_SECOND as u32 - 1);
+        assert_eq!(target.credits, 0);
+
+        target.regenerate_credits(1);
+        assert!(target.credits > 0);
+
+        target.regenerate_credits(10 * 60 * TICKS_PER_SECOND as u32);
+        assert_eq!(target.credits, max_credits);
+    }
+
+    /// A defensive script that raises its host's `stat` by 5 every 1,000 instructions.
+    fn fortify(stat: ServerStatType) -> Arc<Mutex<Script>> {
+        let fortify = Arc::new(Mutex::new(Algorithm {
+            instruction_count: 1_000.into(),
+            instruction_effects: vec![(1_000.into(), vec![AlgorithmEffect::Modify {
+                target: AlgorithmEffectTarget::SelfServer,
+                stat,
+                potency: AlgorithmEffectValue::Static(5),
+                duration: None,
+            }])],
+            ..Default::default()
+        }));
+        let procedure = AlgorithmProcedure::from(&[fortify]);
+
+        Arc::new(Mutex::new(Script::new(ScriptId::Id(2), vec![Arc::new(Mutex::new(procedure))])))
+    }
+
+    #[test]
+    fn defensive_scripts_run_while_connected() {
+        let active_exploit = make_exploit(1234, false);
+        let host_server = active_exploit.lock().unwrap().hosting_server.clone();
+        {
+            let mut host_server = host_server.lock().unwrap();
+            host_server.set_defensive_script(Some(fortify(ServerStatType::SiphonResist)));
+            host_server.reschedule(std::slice::from_ref(&active_exploit));
+        }
+
+        let active_exploits = [active_exploit];
+        let engine = SimulationEngine::new(&active_exploits);
+        engine.tick(TIME_BETWEEN_TICKS, 1);
+        assert_eq!(host_server.lock().unwrap().stats.modification_of(ServerStatType::SiphonResist), 0);
+
+        active_exploits[0].lock().unwrap().restore_connection(50, 50, true);
+        engine.tick(TIME_BETWEEN_TICKS, 1);
+        assert!(host_server.lock().unwrap().stats.modification_of(ServerStatType::SiphonResist) > 0);
+    }
+
+    /// How much a fortifying defensive script raises its host's detection in one tick,
+    /// when the host has `exploit_count` connected exploits sharing its clock with the script.
+    fn fortified_by(exploit_count: usize) -> i32 {
+        let mut player_state = PlayerState::new(1234);
+        let first = make_exploit(1234, false);
+        let (host_server, target, script) = {
+            let first = first.lock().unwrap();
+            (first.hosting_server.clone(), first.target.clone(), first.script.clone())
+        };
+
+        // The target does nothing, so only the defense touches the host's stats
+        {
+            let mut target = target.lock().unwrap();
+            target.script = Arc::new(Mutex::new(Script::new(ScriptId::Id(3), vec![])));
+            target.interrupt();
+        }
+
+        let mut active_exploits = vec![first];
+        for _ in 1..exploit_count {
+            let rng = player_state.rng_streams.next_stream();
+            let active_exploit = ActiveExploit::new(target.clone(), script.clone(), host_server.clone(), 0.into(), false, rng);
+            active_exploits.push(Arc::new(Mutex::new(active_exploit)));
+        }
+
+        {
+            let mut host_server = host_server.lock().unwrap();
+            for active_exploit in &active_exploits {
+                host_server.scheduler.add(lock_and_clone!(active_exploit, id));
+            }
+            host_server.set_defensive_script(Some(fortify(ServerStatType::Detection)));
+            host_server.reschedule(&active_exploits);
+        }
+
+        for active_exploit in &active_exploits {
+            active_exploit.lock().unwrap().restore_connection(50, 50, true);
+        }
+
+        SimulationEngine::new(&active_exploits).tick(TIME_BETWEEN_TICKS, 1);
+        host_server.lock().unwrap().stats.modification_of(ServerStatType::Detection)
+    }
+
+    #[test]
+    fn defensive_scripts_run_once_per_server() {
+        let alone = fortified_by(1);
+        assert!(alone > 0);
+
+        // More exploits means more ways to split the clock, not more copies of the defense
+        assert!(fortified_by(3) < alone);
+    }
+
+    /// Ticks `active_exploit` until it connects, returning how many ticks that took.
+    fn ticks_to_connect(active_exploit: Arc<Mutex<ActiveExploit>>) -> usize {
+        let active_exploits = [active_exploit];
+        let engine = SimulationEngine::new(&active_exploits);
+
+        (1..1_000)
+            .find(|_| engine.tick(TIME_BETWEEN_TICKS, 1).iter().any(|event| matches!(event, SimulationEvent::Connected { .. })))
+            .expect("exploit never connected")
+    }
+
+    #[test]
+    fn connection_strength_extends_connection_health() {
+        let active_exploit = make_exploit(1234, false);
+        let (base_health, host_server) = {
+            let active_exploit = active_exploit.lock().unwrap();
+            (*active_exploit.connection_max_health.lock().unwrap(), active_exploit.hosting_server.clone())
+        };
+
+        host_server.lock().unwrap().stats.add_innate(ServerStatType::ConnectionStrength, 4);
+        active_exploit.lock().unwrap().restore_connection(base_health, base_health, true);
+        SimulationEngine::new(std::slice::from_ref(&active_exploit)).tick(Duration::ZERO, 1);
+
+        let active_exploit = active_exploit.lock().unwrap();
+        assert_eq!(*active_exploit.connection_max_health.lock().unwrap(), base_health + 20);
+        assert_eq!(*active_exploit.connection_current_health.lock().unwrap(), base_health + 20);
+    }
+
+    #[test]
+    fn stealthy_hosts_connect_faster() {
+        let stealthy_exploit = make_exploit(1234, false);
+        lock_and_clone!(stealthy_exploit, hosting_server).lock().unwrap().stats.add_innate(ServerStatType::Stealth, 4);
+
+        assert!(ticks_to_connect(stealthy_exploit) < ticks_to_connect(make_exploit(1234, false)));
+    }
+
+    #[test]
+    fn reconnecting_waits_out_a_delay() {
+        let active_exploit = make_exploit(1234, false);
+        {
+            let mut active_exploit = active_exploit.lock().unwrap();
+            active_exploit.restore_connection(50, 0, true);
+            active_exploit.restart();
+            assert_eq!(*active_exploit.reconnect_delay.lock().unwrap() as i64, BASE_RECONNECT_DELAY);
+        }
+
+        let active_exploits = [active_exploit];
+        let engine = SimulationEngine::new(&active_exploits);
+        for _ in 0..BASE_RECONNECT_DELAY {
+            engine.tick(TIME_BETWEEN_TICKS, 1);
+        }
+        assert_eq!(*active_exploits[0].lock().unwrap().connection_current_health.lock().unwrap(), 0);
+
+        engine.tick(TIME_BETWEEN_TICKS, 1);
+        assert!(*active_exploits[0].lock().unwrap().connection_current_health.lock().unwrap() > 0);
+    }
+
+    #[test]
+    fn reconnect_delays_count_ticks_not_updates() {
+        let active_exploit = make_exploit(1234, false);
+        {
+            let mut active_exploit = active_exploit.lock().unwrap();
+            active_exploit.restore_connection(50, 0, true);
+            active_exploit.restart();
+        }
+
+        // A single slow update covering the whole delay, and a tick besides, starts connecting straight away
+        let active_exploits = [active_exploit];
+        SimulationEngine::new(&active_exploits).tick(TIME_BETWEEN_TICKS, BASE_RECONNECT_DELAY as u32 + 1);
+        let active_exploit = active_exploits[0].lock().unwrap();
+        assert_eq!(*active_exploit.reconnect_delay.lock().unwrap(), 0);
+        assert!(*active_exploit.connection_current_health.lock().unwrap() > 0);
+    }
+
+    #[test]
+    fn manual_restarts_reconnect_straight_away() {
+        let active_exploit = make_exploit(1234, false);
+        let mut active_exploit = active_exploit.lock().unwrap();
+        active_exploit.restore_connection(50, 50, true);
+        active_exploit.restart();
+
+        assert_eq!(*active_exploit.reconnect_delay.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn detection_cancels_out_stealth() {
+        let detected_exploit = make_exploit(1234, false);
+        lock_and_clone!(detected_exploit, hosting_server).lock().unwrap().stats.add_innate(ServerStatType::Stealth, 4);
+        {
+            let target_server = lock_and_clone!(detected_exploit, target).lock().unwrap().server.clone();
+            target_server.lock().unwrap().stats.add_innate(ServerStatType::Detection, 4);
+        }
+
+        assert_eq!(ticks_to_connect(detected_exploit), ticks_to_connect(make_exploit(1234, false)));
+    }
+
+    /// Has the target terminate a full-health connection for 3, after `setup` adjusts both servers' stats.
+    fn health_after_terminate(setup: impl FnOnce(&mut Server, &mut Server)) -> u32 {
+        let active_exploit = make_exploit(1234, false);
+        {
+            let active_exploit = active_exploit.lock().unwrap();
+            let target_server = lock_and_clone!(active_exploit.target, server);
+            setup(&mut active_exploit.hosting_server.lock().unwrap(), &mut target_server.lock().unwrap());
+        }
+        active_exploit.lock().unwrap().restore_connection(50, 50, true);
+
+        apply(&active_exploit, AlgorithmEffect::Terminate { potency: AlgorithmEffectValue::Static(3) }, false);
+        lock_and_clone!(active_exploit, connection_current_health).lock().unwrap().to_owned()
+    }
+
+    #[test]
+    fn terminate_resist_and_potency_bonus_adjust_terminates() {
+        assert_eq!(health_after_terminate(|_, _| {}), 47);
+        assert_eq!(health_after_terminate(|host, _| host.stats.add_innate(ServerStatType::TerminateResist, 2)), 49);
+        assert_eq!(health_after_terminate(|host, _| host.stats.add_innate(ServerStatType::TerminateResist, 5)), 50);
+        assert_eq!(health_after_terminate(|_, target| target.stats.add_innate(ServerStatType::EffectPotencyBonus, 2)), 45);
+    }
+
+    #[test]
+    fn connection_regen_heals_running_exploits_each_second() {
+        let active_exploit = make_exploit(1234, false);
+        lock_and_clone!(active_exploit, hosting_server).lock().unwrap().stats.add_innate(ServerStatType::ConnectionRegen, 5);
+        active_exploit.lock().unwrap().restore_connection(50, 30, true);
+
+        let active_exploits = [active_exploit];
+        let engine = SimulationEngine::new(&active_exploits);
+        let health = || *active_exploits[0].lock().unwrap().connection_current_health.lock().unwrap();
+        for _ in 1..TICKS_PER_SECOND {
+            engine.tick(Duration::ZERO, 1);
+        }
+        assert_eq!(health(), 30);
+
+        engine.tick(Duration::ZERO, 1);
+        assert_eq!(health(), 35);
+
+        // However the ticks are batched up, a second's worth heals once
+        engine.tick(Duration::ZERO, TICKS_PER_SECOND as u32 - 1);
+        assert_eq!(health(), 35);
+        engine.tick(Duration::ZERO, 2);
+        assert_eq!(health(), 40);
+
+        engine.tick(Duration::ZERO, 10 * TICKS_PER_SECOND as u32);
+        assert_eq!(health(), 50);
+    }
+
+    #[test]
+    fn clock_speed_multiplier_speeds_up_scripts() {
+        let progress_after_tick = |clock_speed_multiplier| {
+            let active_exploit = make_exploit(1234, false);
+            lock_and_clone!(active_exploit, hosting_server).lock().unwrap().stats.add_innate(ServerStatType::ClockSpeedMultiplier, clock_speed_multiplier);
+            active_exploit.lock().unwrap().restore_connection(50, 50, true);
+
+            SimulationEngine::new(std::slice::from_ref(&active_exploit)).tick(TIME_BETWEEN_TICKS, 1);
+            active_exploit.lock().unwrap().progress()
+        };
+
+        assert!(progress_after_tick(0) > 0);
+        assert_eq!(progress_after_tick(100), 2 * progress_after_tick(0));
+        assert_eq!(progress_after_tick(-100), 0);
+    }
+
+    #[test]
+    fn timed_modifications_wear_off_each_tick() {
+        let modify = AlgorithmEffect::Modify {
+            target: AlgorithmEffectTarget::SelfServer,
+            stat: ServerStatType::ExfilResist,
+            potency: AlgorithmEffectValue::Static(4),
+            duration: Some(3),
+        };
+        let (active_exploit, _) = resolve(modify.clone(), true);
+        let host_server = lock_and_clone!(active_exploit, hosting_server);
+        assert_eq!(host_server.lock().unwrap().stats.modification_of(ServerStatType::ExfilResist), 4);
+
+        let active_exploits = [active_exploit];
+        let engine = SimulationEngine::new(&active_exploits);
+        engine.tick(Duration::ZERO, 1);
+        engine.tick(Duration::ZERO, 1);
+        assert_eq!(host_server.lock().unwrap().stats.modification_of(ServerStatType::ExfilResist), 4);
+
+        engine.tick(Duration::ZERO, 1);
+        assert_eq!(host_server.lock().unwrap().stats.modification_of(ServerStatType::ExfilResist), 0);
+
+        // A slow frame covering several ticks wears them all off at once
+        apply(&active_exploits[0], modify, true);
+        engine.tick(TIME_BETWEEN_TICKS * 3, 3);
+        assert_eq!(host_server.lock().unwrap().stats.modification_of(ServerStatType::ExfilResist), 0);
+    }
+
+    #[test]
+    fn purges_undo_modifications_towards_baseline() {
+        let debuff = |stat| AlgorithmEffect::Modify {
+            target: AlgorithmEffectTarget::TargetServer,
+            stat,
+            potency: AlgorithmEffectValue::Static(-3),
+            duration: None,
+        };
+        let purge_all = AlgorithmEffect::PurgeAll { target: AlgorithmEffectTarget::SelfServer, potency: AlgorithmEffectValue::Static(2) };
+
+        // There's nothing to purge until the target debuffs the player's server
+        let (active_exploit, events) = resolve(purge_all.clone(), true);
+        assert!(events.is_empty());
+
+        apply(&active_exploit, debuff(ServerStatType::SiphonResist), false);
+        apply(&active_exploit, debuff(ServerStatType::ExfilResist), false);
+
+        let events = apply(&active_exploit, purge_all.clone(), true);
+        let [SimulationEvent::EffectApplied(ExploitEvent { purged, .. })] = events.as_slice() else {
+            panic!("expected a single purge");
+        };
+        assert_eq!(purged.len(), 2);
+
+        let host_server = lock_and_clone!(active_exploit, hosting_server);
+        assert_eq!(host_server.lock().unwrap().stats.modification_of(ServerStatType::SiphonResist), -1);
+        assert_eq!(host_server.lock().unwrap().stats.modification_of(ServerStatType::ExfilResist), -1);
+
+        // Purging never pushes a stat past its baseline
+        apply(&active_exploit, purge_all, true);
+        assert_eq!(host_server.lock().unwrap().stats.modification_of(ServerStatType::SiphonResist), 0);
+        assert_eq!(host_server.lock().unwrap().stats.modification_of(ServerStatType::ExfilResist), 0);
+    }
+
+    /// Has the player's script and the target's script each modify both servers, then ends the exploit with `end`.
+    fn modifications_after(end: impl FnOnce(&Arc<Mutex<ActiveExploit>>)) -> (i32, i32) {
+        let modify = |target| AlgorithmEffect::Modify {
+            target,
+            stat: ServerStatType::ExfilResist,
+            potency: AlgorithmEffectValue::Static(2),
+            duration: None,
+        };
+
+        let active_exploit = make_exploit(1234, false);
+        active_exploit.lock().unwrap().restore_connection(50, 50, true);
+        for (effect, from_player_server) in [
+            (modify(AlgorithmEffectTarget::SelfServer), true),
+            (modify(AlgorithmEffectTarget::TargetServer), true),
+            (modify(AlgorithmEffectTarget::SelfServer), false),
+            (modify(AlgorithmEffectTarget::TargetServer), false),
+        ] {
+            apply(&active_exploit, effect, from_player_server);
+        }
+
+        let (host_server, target_server) = {
+            let active_exploit = active_exploit.lock().unwrap();
+            (active_exploit.hosting_server.clone(), lock_and_clone!(active_exploit.target, server))
+        };
+        assert_eq!(host_server.lock().unwrap().stats.modification_of(ServerStatType::ExfilResist), 4);
+        assert_eq!(target_server.lock().unwrap().stats.modification_of(ServerStatType::ExfilResist), 4);
+
+        end(&active_exploit);
+
+        let host_modification = host_server.lock().unwrap().stats.modification_of(ServerStatType::ExfilResist);
+        let target_modification = target_server.lock().unwrap().stats.modification_of(ServerStatType::ExfilResist);
+        (host_modification, target_modification)
+    }
+
+    #[test]
+    fn stopped_exploits_leave_no_modifications_behind() {
+        let ended = modifications_after(|active_exploit| active_exploit.lock().unwrap().clear_modifications());
+        assert_eq!(ended, (0, 0));
+    }
+
+    #[test]
+    fn restarted_exploits_leave_no_modifications_behind() {
+        let ended = modifications_after(|active_exploit| active_exploit.lock().unwrap().restart());
+        assert_eq!(ended, (0, 0));
+    }
+
+    #[test]
+    fn disconnected_exploits_leave_no_modifications_behind() {
+        let ended = modifications_after(|active_exploit| {
+            apply(active_exploit, AlgorithmEffect::Terminate { potency: AlgorithmEffectValue::Static(100) }, false);
+            assert!(matches!(active_exploit.lock().unwrap().status(), ActiveExploitStatus::Disconnected));
+        });
+        assert_eq!(ended, (0, 0));
+    }
+}
@@ -0,0 +1,29 @@
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+use crate::algorithm::algorithm::Algorithm;
+use crate::event::exploit_event::ExploitEvent;
+use crate::script::id::ScriptId;
+
+/// Something that happened while advancing the simulation.
+/// The simulation never applies these to the player; it's up to the caller to react to them.
+#[derive(Clone)]
+pub enum SimulationEvent {
+    /// An exploit finished connecting, and both sides' scripts started executing.
+    Connected { exploit_id: Uuid },
+
+    /// An exploit's connection was terminated by its target.
+    Disconnected { exploit_id: Uuid },
+
+    /// An algorithm effect was applied by either side of an exploit.
+    EffectApplied(ExploitEvent),
+
+    /// A script siphoned credits from its target.
+    CreditsSiphoned { script_id: ScriptId, credits: i64 },
+
+    /// A script exfiltrated a new algorithm from its target.
+    AlgorithmExfiltrated { algorithm: Arc<Mutex<Algorithm>> },
+
+    /// A target exfiltrated one of the player's algorithms.
+    /// The simulation can't see the inventory, so it's up to the caller to take the algorithm `pick` lands on.
+    AlgorithmStolen { pick: u64 },
+}
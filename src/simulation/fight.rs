@@ -0,0 +1,168 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::active_exploit::ActiveExploit;
+use crate::rng::RngStreams;
+use crate::script::id::ScriptId;
+use crate::simulation::engine::SimulationEngine;
+use crate::simulation::event::SimulationEvent;
+use crate::simulation::scenario::Scenario;
+use crate::TIME_BETWEEN_TICKS;
+
+/// How a single fight of a [Scenario] played out.
+#[derive(Clone, Debug)]
+pub struct FightOutcome {
+    pub seed: u64,
+
+    /// How long the fight lasted; either until the target disconnected the exploit, or the time limit.
+    pub duration: Duration,
+
+    /// When the exploit finished connecting, if it ever did.
+    pub connected_at: Option<Duration>,
+
+    /// When the target disconnected the exploit, if it did.
+    pub disconnected_at: Option<Duration>,
+
+    pub credits: i64,
+    pub siphons: usize,
+    pub exfils: usize,
+
+    /// How many times the target exfiltrated from the player's server.
+    pub algorithms_stolen: usize,
+}
+
+impl FightOutcome {
+    pub fn credits_per_second(&self) -> f64 {
+        self.credits as f64 / self.duration.as_secs_f64()
+    }
+
+    pub fn exfils_per_second(&self) -> f64 {
+        self.exfils as f64 / self.duration.as_secs_f64()
+    }
+
+    /// How long the exploit stayed connected before being disconnected.
+    pub fn time_to_disconnect(&self) -> Option<Duration> {
+        Some(self.disconnected_at? - self.connected_at?)
+    }
+}
+
+/// Runs `scenario` once without reconnecting, until the exploit is disconnected or `time_limit` passes.
+/// The same scenario and seed always play out identically.
+pub fn run_fight(scenario: &Scenario, seed: u64, time_limit: Duration) -> FightOutcome {
+    let mut rng_streams = RngStreams::new(seed);
+    let mut rng = rng_streams.next_stream();
+
+    let active_exploits = [Arc::new(Mutex::new(ActiveExploit::new(
+        scenario.target.build(&mut rng),
+        scenario.script.build(ScriptId::Id(1), &mut rng),
+        scenario.hosting_server.build(),
+        scenario.clock_allocation.into(),
+        false,
+        rng_streams.next_stream(),
+    )))];
+    let engine = SimulationEngine::new(&active_exploits);
+
+    let mut outcome = FightOutcome {
+        seed,
+        duration: Duration::ZERO,
+        connected_at: None,
+        disconnected_at: None,
+        credits: 0,
+        siphons: 0,
+        exfils: 0,
+        algorithms_stolen: 0,
+    };
+
+    while outcome.duration < time_limit && outcome.disconnected_at.is_none() {
+        outcome.duration += TIME_BETWEEN_TICKS;
+
+        for event in engine.tick(TIME_BETWEEN_TICKS, 1) {
+            match event {
+                SimulationEvent::Connected { .. } => outcome.connected_at = Some(outcome.duration),
+                SimulationEvent::Disconnected { .. } => outcome.disconnected_at = Some(outcome.duration),
+                SimulationEvent::CreditsSiphoned { credits, .. } => {
+                    outcome.credits += credits;
+                    outcome.siphons += 1;
+                }
+                SimulationEvent::AlgorithmExfiltrated { .. } => outcome.exfils += 1,
+                SimulationEvent::AlgorithmStolen { .. } => outcome.algorithms_stolen += 1,
+                SimulationEvent::EffectApplied(_) => {}
+            }
+        }
+    }
+
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"(
+        hosting_server: (name: "host", threads: 1, clock_speed: 2000000),
+        clock_allocation: 2000000,
+        script: (procedures: [[
+            (instruction_count: 500000, instruction_effects: [(500000, [Siphon(potency: Range((start: 1, end: 8)))])]),
+        ]]),
+        target: (
+            server: (name: "target", threads: 1, clock_speed: 1000000, stats: [(SiphonResist, 2)]),
+            connection_health: 50,
+            script: (procedures: [[
+                (instruction_count: 100000, instruction_effects: [(100000, [Terminate(potency: Static(1))])]),
+            ]]),
+        ),
+    )"#;
+
+    #[test]
+    fn scenario_parses_from_ron() {
+        let scenario = Scenario::from_ron(SCENARIO).unwrap();
+
+        assert_eq!(scenario.clock_allocation, 2_000_000);
+        assert_eq!(scenario.script.procedures[0].len(), 1);
+        assert!(scenario.hosting_server.stats.is_empty());
+    }
+
+    #[test]
+    fn fights_end_when_disconnected() {
+        let scenario = Scenario::from_ron(SCENARIO).unwrap();
+        let outcome = run_fight(&scenario, 1234, Duration::from_secs(600));
+
+        assert!(outcome.connected_at.is_some());
+        assert_eq!(outcome.disconnected_at, Some(outcome.duration));
+        assert!(outcome.time_to_disconnect().unwrap() > Duration::ZERO);
+        assert!(outcome.siphons > 0);
+    }
+
+    #[test]
+    fn credit_pools_refill_during_fights() {
+        // A target that never fights back, with a pool a single siphon would drain
+        let scenario = Scenario::from_ron(r#"(
+            hosting_server: (name: "host", threads: 1, clock_speed: 2000000),
+            clock_allocation: 2000000,
+            script: (procedures: [[
+                (instruction_count: 100000, instruction_effects: [(100000, [Siphon(potency: Static(1000))])]),
+            ]]),
+            target: (
+                server: (name: "target", threads: 1, clock_speed: 1000000),
+                connection_health: 50,
+                credits: Some(100),
+                script: (procedures: [[
+                    (instruction_count: 100000, instruction_effects: [(100000, [Siphon(potency: Static(0))])]),
+                ]]),
+            ),
+        )"#).unwrap();
+        let outcome = run_fight(&scenario, 1234, Duration::from_secs(60));
+
+        // Far more than the pool held to begin with, as it kept refilling
+        assert!(outcome.credits > 100);
+    }
+
+    #[test]
+    fn fights_are_reproducible() {
+        let scenario = Scenario::from_ron(SCENARIO).unwrap();
+        let first = run_fight(&scenario, 1234, Duration::from_secs(600));
+        let second = run_fight(&scenario, 1234, Duration::from_secs(600));
+
+        assert_eq!(first.duration, second.duration);
+        assert_eq!(first.credits, second.credits);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod engine;
+pub mod event;
+pub mod fight;
+pub mod scenario;
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+use crate::target::definition::{DefinitionError, ScriptDefinition, ServerDefinition, TargetDefinition};
+
+/// A fight between a player's script and a single target, as written by hand in a RON file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Scenario {
+    /// The player's server that the exploit runs from.
+    pub hosting_server: ServerDefinition,
+
+    /// How much of the hosting server's clock speed is given to the exploit.
+    pub clock_allocation: u64,
+
+    pub script: ScriptDefinition,
+
+    pub target: TargetDefinition,
+}
+
+impl Scenario {
+    pub fn from_ron(contents: &str) -> Result<Scenario, DefinitionError> {
+        let scenario: Scenario = ron::from_str(contents)?;
+        scenario.hosting_server.validate("hosting_server")?;
+        scenario.script.validate("script")?;
+        scenario.script.validate_threads("script", &scenario.hosting_server)?;
+        scenario.target.validate("target")?;
+
+        Ok(scenario)
+    }
+}
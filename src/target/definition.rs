@@ -0,0 +1,361 @@
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use bevy::asset::Asset;
+use bevy::reflect::TypePath;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use crate::active_exploit::ExploitTarget;
+use crate::algorithm::algorithm::Algorithm;
+use crate::algorithm::effect::AlgorithmEffect;
+use crate::algorithm::effect::value::AlgorithmEffectValue;
+use crate::algorithm::id::AlgorithmId;
+use crate::algorithm::rarity::AlgorithmRarity;
+use crate::algorithm::procedure::AlgorithmProcedure;
+use crate::rng::random_uuid;
+use crate::script::id::ScriptId;
+use crate::script::Script;
+use crate::server::{Server, ServerDefense, ServerStatInstance, ServerStatInstances, ServerStatSource, ServerStatType};
+use crate::server::scheduler::CpuScheduler;
+use crate::server::uplink::Uplink;
+use crate::target::tier::DifficultyTier;
+use crate::ui::clock_speed::ClockSpeed;
+
+#[derive(Debug)]
+pub enum DefinitionError {
+    /// The definition file couldn't be read.
+    Io(std::io::Error),
+
+    /// The definition isn't valid RON, or doesn't have the expected shape.
+    Parse(ron::error::SpannedError),
+
+    /// A field has a value the simulation can't run with.
+    /// `field` is the path to the offending value, eg. `script.procedures[0][1].instruction_count`.
+    Invalid { field: String, reason: String },
+}
+
+impl DefinitionError {
+    fn invalid(field: impl Into<String>, reason: impl Into<String>) -> DefinitionError {
+        DefinitionError::Invalid { field: field.into(), reason: reason.into() }
+    }
+}
+
+impl Display for DefinitionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DefinitionError::Io(err) => write!(f, "couldn't read definition: {err}"),
+            DefinitionError::Parse(err) => write!(f, "definition is malformed at {err}"),
+            DefinitionError::Invalid { field, reason } => write!(f, "definition has invalid {field}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for DefinitionError {}
+
+impl From<std::io::Error> for DefinitionError {
+    fn from(value: std::io::Error) -> Self {
+        DefinitionError::Io(value)
+    }
+}
+
+impl From<ron::error::SpannedError> for DefinitionError {
+    fn from(value: ron::error::SpannedError) -> Self {
+        DefinitionError::Parse(value)
+    }
+}
+
+/// Joins a field onto the path of the value that contains it, for [DefinitionError::Invalid].
+fn field(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+/// A hand-written exploit target, loaded from a `.target.ron` asset.
+#[derive(Asset, TypePath, Clone, Debug, Serialize, Deserialize)]
+pub struct TargetDefinition {
+    pub server: ServerDefinition,
+
+    /// How much connection health exploits build up before connecting,
+    /// and how much the target's terminations must wear down to disconnect them.
+    pub connection_health: u32,
+
+    #[serde(default)]
+    pub tier: DifficultyTier,
+
+    /// How many credits the target holds when its credit pool is full.
+    /// Defaults to what targets of its tier usually hold.
+    #[serde(default)]
+    pub credits: Option<u64>,
+
+    /// The script the target runs to defend itself once an exploit connects.
+    pub script: ScriptDefinition,
+}
+
+impl TargetDefinition {
+    pub fn from_ron(contents: &[u8]) -> Result<TargetDefinition, DefinitionError> {
+        let definition: TargetDefinition = ron::de::from_bytes(contents)?;
+        definition.validate("")?;
+
+        Ok(definition)
+    }
+
+    pub fn validate(&self, path: &str) -> Result<(), DefinitionError> {
+        self.server.validate(&field(path, "server"))?;
+        if self.connection_health == 0 {
+            return Err(DefinitionError::invalid(field(path, "connection_health"), "must be at least 1"));
+        }
+        if self.credits == Some(0) {
+            return Err(DefinitionError::invalid(field(path, "credits"), "must be at least 1"));
+        }
+
+        self.script.validate(&field(path, "script"))?;
+        self.script.validate_threads(&field(path, "script"), &self.server)
+    }
+
+    /// How many credits the target holds when its credit pool is full.
+    pub fn max_credits(&self) -> u64 {
+        self.credits.unwrap_or_else(|| self.tier.credit_pool())
+    }
+
+    pub fn build(&self, rng: &mut impl Rng) -> Arc<Mutex<ExploitTarget>> {
+        let mut target = ExploitTarget::new(
+            random_uuid(rng),
+            self.server.build(),
+            self.script.build(ScriptId::Invalid, rng),
+            self.connection_health,
+            self.max_credits(),
+        );
+        target.tier = self.tier;
+
+        Arc::new(Mutex::new(target))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ServerDefinition {
+    pub name: String,
+    pub threads: u32,
+    pub clock_speed: u64,
+
+    /// The server's innate stats.
+    #[serde(default)]
+    pub stats: Vec<(ServerStatType, i32)>,
+}
+
+impl ServerDefinition {
+    pub fn validate(&self, path: &str) -> Result<(), DefinitionError> {
+        if self.threads == 0 {
+            return Err(DefinitionError::invalid(field(path, "threads"), "must be at least 1"));
+        }
+        if self.clock_speed == 0 {
+            return Err(DefinitionError::invalid(field(path, "clock_speed"), "must be at least 1"));
+        }
+
+        Ok(())
+    }
+
+    pub fn build(&self) -> Arc<Mutex<Server>> {
+        let stats = self
+            .stats
+            .iter()
+            .map(|(stat_type, value)| ServerStatInstance::new(ServerStatSource::Innate, stat_type.clone(), *value))
+            .collect::<Vec<_>>();
+
+        Arc::new(Mutex::new(Server {
+            name: self.name.clone(),
+            threads: self.threads,
+            clock_speed: self.clock_speed.into(),
+            effective_clock_speed: self.clock_speed.into(),
+            uplink: Uplink::default(),
+            stats: ServerStatInstances::from(&stats),
+            running_scripts: vec![],
+            scheduler: CpuScheduler::new(),
+            busy_clock_speed: ClockSpeed::new(0),
+            defensive_script: None,
+            defense: ServerDefense::default(),
+        }))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScriptDefinition {
+    /// Each procedure is the ordered list of algorithms it executes.
+    pub procedures: Vec<Vec<AlgorithmDefinition>>,
+}
+
+impl ScriptDefinition {
+    pub fn validate(&self, path: &str) -> Result<(), DefinitionError> {
+        if self.procedures.is_empty() {
+            return Err(DefinitionError::invalid(field(path, "procedures"), "must have at least one procedure"));
+        }
+
+        for (procedure_index, procedure) in self.procedures.iter().enumerate() {
+            if procedure.is_empty() {
+                return Err(DefinitionError::invalid(
+                    field(path, &format!("procedures[{procedure_index}]")),
+                    "must have at least one algorithm",
+                ));
+            }
+
+            for (algorithm_index, algorithm) in procedure.iter().enumerate() {
+                algorithm.validate(&field(path, &format!("procedures[{procedure_index}][{algorithm_index}]")))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `server` has enough threads to run every procedure in parallel.
+    pub fn validate_threads(&self, path: &str, server: &ServerDefinition) -> Result<(), DefinitionError> {
+        if self.procedures.len() > server.threads as usize {
+            return Err(DefinitionError::invalid(
+                field(path, "procedures"),
+                format!("needs {} threads, but the server only has {}", self.procedures.len(), server.threads),
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn build(&self, id: ScriptId, rng: &mut impl Rng) -> Arc<Mutex<Script>> {
+        let procedures = self
+            .procedures
+            .iter()
+            .map(|algorithms| {
+                let algorithms = algorithms
+                    .iter()
+                    .map(|algorithm| algorithm.build(rng))
+                    .collect::<Vec<_>>();
+
+                Arc::new(Mutex::new(AlgorithmProcedure::from(&algorithms)))
+            })
+            .collect();
+
+        Arc::new(Mutex::new(Script::new(id, procedures)))
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AlgorithmDefinition {
+    pub instruction_count: u64,
+    pub instruction_effects: Vec<(u64, Vec<AlgorithmEffect>)>,
+}
+
+impl AlgorithmDefinition {
+    pub fn validate(&self, path: &str) -> Result<(), DefinitionError> {
+        if self.instruction_count == 0 {
+            return Err(DefinitionError::invalid(field(path, "instruction_count"), "must be at least 1"));
+        }
+
+        for (effect_index, (instruction, effects)) in self.instruction_effects.iter().enumerate() {
+            let effect_path = field(path, &format!("instruction_effects[{effect_index}]"));
+            if !(1..=self.instruction_count).contains(instruction) {
+                return Err(DefinitionError::invalid(
+                    effect_path,
+                    format!("instruction {instruction} is outside the algorithm's 1..={} instructions", self.instruction_count),
+                ));
+            }
+
+            for effect in effects {
+                let (AlgorithmEffect::Terminate { potency }
+                | AlgorithmEffect::Siphon { potency }
+                | AlgorithmEffect::Exfil { potency }
+                | AlgorithmEffect::Modify { potency, .. }
+                | AlgorithmEffect::Purge { potency, .. }
+                | AlgorithmEffect::PurgeAll { potency, .. }) = effect;
+
+                if let AlgorithmEffectValue::Range(range) = potency && range.is_empty() {
+                    return Err(DefinitionError::invalid(
+                        effect_path,
+                        format!("potency range {}..{} is empty", range.start, range.end),
+                    ));
+                }
+
+                if let AlgorithmEffect::Modify { duration: Some(0), .. } = effect {
+                    return Err(DefinitionError::invalid(effect_path, "modify duration must be at least 1 tick"));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn build(&self, rng: &mut impl Rng) -> Arc<Mutex<Algorithm>> {
+        Arc::new(Mutex::new(Algorithm {
+            id: AlgorithmId::Id(random_uuid(rng)),
+            rarity: AlgorithmRarity::Common,
+            instruction_count: self.instruction_count.into(),
+            instruction_effects: self
+                .instruction_effects
+                .iter()
+                .map(|(instruction, effects)| ((*instruction).into(), effects.clone()))
+                .collect(),
+        }))
+    }
+}
+
+/// The first corporation the player can target, for tests that need a real target without an asset server.
+#[cfg(test)]
+pub(crate) fn corp_definition() -> TargetDefinition {
+    TargetDefinition::from_ron(include_bytes!("../../assets/targets/corp.target.ron")).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CORP: &str = include_str!("../../assets/targets/corp.target.ron");
+
+    fn invalid_field(contents: &str) -> String {
+        match TargetDefinition::from_ron(contents.as_bytes()) {
+            Err(DefinitionError::Invalid { field, .. }) => field,
+            other => panic!("expected an invalid definition, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn corp_target_is_valid() {
+        let definition = TargetDefinition::from_ron(CORP.as_bytes()).unwrap();
+
+        assert_eq!(definition.server.stats, vec![(ServerStatType::SiphonResist, 3), (ServerStatType::ExfilResist, 8)]);
+        assert_eq!(definition.script.procedures.len(), 2);
+    }
+
+    #[test]
+    fn malformed_definitions_report_their_position() {
+        let err = TargetDefinition::from_ron(b"(server: ())").unwrap_err();
+
+        assert!(matches!(err, DefinitionError::Parse(_)));
+        assert!(err.to_string().contains("1:"), "{err}");
+    }
+
+    #[test]
+    fn invalid_values_name_their_field() {
+        assert_eq!(invalid_field(&CORP.replace("threads: 2", "threads: 0")), "server.threads");
+        assert_eq!(invalid_field(&CORP.replace("connection_health: 50", "connection_health: 0")), "connection_health");
+        assert_eq!(
+            invalid_field(&CORP.replace("(250000, [Terminate", "(2500000, [Terminate")),
+            "script.procedures[0][0].instruction_effects[0]",
+        );
+        assert_eq!(
+            invalid_field(&CORP.replace("Terminate(potency: Static(1))", "Terminate(potency: Range((start: 2, end: 2)))")),
+            "script.procedures[0][0].instruction_effects[0]",
+        );
+    }
+
+    #[test]
+    fn scripts_need_a_thread_per_procedure() {
+        let mut definition = corp_definition();
+        definition.script.procedures.push(definition.script.procedures[0].clone());
+
+        match definition.validate("") {
+            Err(DefinitionError::Invalid { field, .. }) => assert_eq!(field, "script.procedures"),
+            other => panic!("expected an invalid definition, got {other:?}"),
+        }
+
+        definition.server.threads += 1;
+        assert!(definition.validate("").is_ok());
+    }
+}
@@ -0,0 +1,203 @@
+use rand::prelude::IndexedRandom;
+use rand::{Rng, RngExt};
+use crate::algorithm::effect::{AlgorithmEffect, target::AlgorithmEffectTarget, value::AlgorithmEffectValue};
+use crate::server::ServerStatType;
+use crate::target::definition::{AlgorithmDefinition, ScriptDefinition, ServerDefinition, TargetDefinition};
+use crate::target::tier::DifficultyTier;
+use crate::TICKS_PER_SECOND;
+
+const NAME_PREFIXES: &[&str] = &[
+    "Omni", "Cyber", "Nano", "Hex", "Quantum", "Vertex", "Cobalt", "Iron", "Neo", "Arc", "Helix", "Strata",
+];
+
+const NAME_SUFFIXES: &[&str] = &[
+    "Corp", "Dynamics", "Systems", "Labs", "Holdings", "Networks", "Industries", "Logistics",
+];
+
+/// Every defensive algorithm runs for the same number of instructions,
+/// so each of a target's procedures finishes its pass at the same time.
+const DEFENSE_INSTRUCTION_COUNT: u64 = 1_000_000;
+
+/// How many ticks the resist buffs targets give themselves last.
+const DEFENSE_MODIFY_DURATION: u32 = 30 * TICKS_PER_SECOND as u32;
+
+/// What a generated target specialises in defending with, on top of the resists every target has.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DefenseProfile {
+    /// Shrugs off terminates, and keeps connections to it short.
+    Hardened,
+
+    /// Notices intruders quickly, and keeps them waiting to get back in.
+    Vigilant,
+
+    /// Runs its defenses faster than its hardware should allow.
+    Overclocked,
+
+    /// Hits back harder with every effect it runs.
+    Aggressive,
+}
+
+impl DefenseProfile {
+    const ALL: [DefenseProfile; 4] = [
+        DefenseProfile::Hardened,
+        DefenseProfile::Vigilant,
+        DefenseProfile::Overclocked,
+        DefenseProfile::Aggressive,
+    ];
+
+    /// The innate stats a target of this profile gets at `level`.
+    fn stats(self, level: u32) -> Vec<(ServerStatType, i32)> {
+        let level = level as i32;
+        match self {
+            DefenseProfile::Hardened => vec![
+                (ServerStatType::TerminateResist, 1 + level / 2),
+                (ServerStatType::ConnectionStrength, 1 + level / 2),
+            ],
+            DefenseProfile::Vigilant => vec![(ServerStatType::Detection, 2 + level / 2)],
+            DefenseProfile::Overclocked => vec![(ServerStatType::ClockSpeedMultiplier, 20 + 5 * level)],
+            DefenseProfile::Aggressive => vec![(ServerStatType::EffectPotencyBonus, 1 + level / 3)],
+        }
+    }
+
+    /// The stat a target of this profile buffs on itself, alongside its resists.
+    fn buffed_stat(self) -> ServerStatType {
+        match self {
+            DefenseProfile::Hardened => ServerStatType::TerminateResist,
+            DefenseProfile::Vigilant => ServerStatType::Detection,
+            DefenseProfile::Overclocked => ServerStatType::ClockSpeedMultiplier,
+            DefenseProfile::Aggressive => ServerStatType::EffectPotencyBonus,
+        }
+    }
+}
+
+pub struct TargetGenerator;
+
+impl TargetGenerator {
+    /// Generates a corporation whose hardware, defenses and payouts all scale with `tier`.
+    pub fn generate(tier: DifficultyTier, rng: &mut impl Rng) -> TargetDefinition {
+        let level = tier.0;
+
+        let name = format!(
+            "{}{}",
+            NAME_PREFIXES.choose(rng).unwrap(),
+            NAME_SUFFIXES.choose(rng).unwrap(),
+        );
+
+        // Jitter within a tier is kept well below the step between tiers, so the ladder always climbs
+        let clock_speed = (1_600_000.0 * 1.25f64.powi(level as i32) * rng.random_range(0.95..=1.05)) as u64;
+        let threads = 2 + level / 2;
+        let siphon_resist = 3 + 2 * level as i32 + rng.random_range(-1..=1);
+        let exfil_resist = 8 + 2 * level as i32 + rng.random_range(-1..=1);
+        let profile = *DefenseProfile::ALL.choose(rng).unwrap();
+
+        let mut stats = vec![
+            (ServerStatType::SiphonResist, siphon_resist),
+            (ServerStatType::ExfilResist, exfil_resist),
+        ];
+        stats.extend(profile.stats(level));
+
+        TargetDefinition {
+            server: ServerDefinition {
+                name,
+                threads,
+                clock_speed,
+                stats,
+            },
+            connection_health: 50 + 10 * level,
+            tier,
+            credits: Some((tier.credit_pool() as f64 * rng.random_range(0.75..=1.5)) as u64),
+            script: Self::generate_defenses(level, threads, profile, rng),
+        }
+    }
+
+    /// One procedure per thread: the first terminates connections, the second purges debuffs,
+    /// and any others shore up the target's own resists, or whatever its `profile` specialises in.
+    fn generate_defenses(level: u32, threads: u32, profile: DefenseProfile, rng: &mut impl Rng) -> ScriptDefinition {
+        let terminate_count = 4 + level as u64 / 2;
+        let terminate_potency = if level < 3 {
+            AlgorithmEffectValue::Static(1)
+        } else {
+            AlgorithmEffectValue::Range(1..(2 + level as i32 / 3))
+        };
+        let terminate = AlgorithmDefinition {
+            instruction_count: DEFENSE_INSTRUCTION_COUNT,
+            instruction_effects: (1..=terminate_count)
+                .map(|n| {
+                    let instruction = DEFENSE_INSTRUCTION_COUNT * n / terminate_count;
+                    (instruction, vec![AlgorithmEffect::Terminate { potency: terminate_potency.clone() }])
+                })
+                .collect(),
+        };
+
+        let purge = AlgorithmDefinition {
+            instruction_count: DEFENSE_INSTRUCTION_COUNT,
+            instruction_effects: vec![(DEFENSE_INSTRUCTION_COUNT, vec![AlgorithmEffect::PurgeAll {
+                potency: AlgorithmEffectValue::Static(1 + level as i32 / 2),
+                target: AlgorithmEffectTarget::SelfServer,
+            }])],
+        };
+
+        let mut procedures = vec![vec![terminate], vec![purge]];
+        while (procedures.len() as u32) < threads {
+            let stat = [ServerStatType::SiphonResist, ServerStatType::ExfilResist, profile.buffed_stat()].choose(rng).unwrap().clone();
+            procedures.push(vec![AlgorithmDefinition {
+                instruction_count: DEFENSE_INSTRUCTION_COUNT,
+                instruction_effects: vec![(DEFENSE_INSTRUCTION_COUNT, vec![AlgorithmEffect::Modify {
+                    target: AlgorithmEffectTarget::SelfServer,
+                    stat,
+                    potency: AlgorithmEffectValue::Range(1..(2 + level as i32 / 2)),
+                    duration: Some(DEFENSE_MODIFY_DURATION),
+                }])],
+            }]);
+        }
+
+        ScriptDefinition { procedures }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use crate::rng::RngStreams;
+    use super::*;
+
+    #[test]
+    fn generated_targets_are_valid() {
+        let mut rng = RngStreams::new(1234).next_stream();
+        for tier in 1..20 {
+            let definition = TargetGenerator::generate(DifficultyTier(tier), &mut rng);
+            definition
+                .validate("")
+                .unwrap_or_else(|err| panic!("tier {tier} generated an invalid target: {err}"));
+        }
+    }
+
+    #[test]
+    fn higher_tiers_are_harder() {
+        let mut rng = RngStreams::new(1234).next_stream();
+        for tier in 1..20 {
+            let easier = TargetGenerator::generate(DifficultyTier(tier), &mut rng);
+            let harder = TargetGenerator::generate(DifficultyTier(tier + 1), &mut rng);
+
+            assert!(harder.server.clock_speed > easier.server.clock_speed, "tier {tier}");
+            assert!(harder.server.threads >= easier.server.threads, "tier {tier}");
+            assert!(harder.connection_health > easier.connection_health, "tier {tier}");
+            assert!(harder.server.stats[0].1 >= easier.server.stats[0].1, "tier {tier}");
+        }
+    }
+
+    #[test]
+    fn targets_specialise_in_different_defenses() {
+        let mut rng = RngStreams::new(1234).next_stream();
+        let mut specialities = HashSet::new();
+        for _ in 0..100 {
+            let definition = TargetGenerator::generate(DifficultyTier(3), &mut rng);
+            let speciality = definition.server.stats[2..].iter().map(|(stat, _)| stat.clone()).collect::<Vec<_>>();
+
+            assert!(!speciality.is_empty());
+            specialities.insert(speciality);
+        }
+
+        assert_eq!(specialities.len(), DefenseProfile::ALL.len());
+    }
+}
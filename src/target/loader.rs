@@ -0,0 +1,31 @@
+use bevy::asset::io::Reader;
+use bevy::asset::{AssetLoader, LoadContext};
+use bevy::reflect::TypePath;
+use crate::target::definition::{DefinitionError, TargetDefinition};
+
+/// Loads and validates `.target.ron` assets.
+/// Invalid definitions fail to load, and while hot reloading, the last valid definition is kept.
+#[derive(Default, TypePath)]
+pub struct TargetDefinitionLoader;
+
+impl AssetLoader for TargetDefinitionLoader {
+    type Asset = TargetDefinition;
+    type Settings = ();
+    type Error = DefinitionError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<TargetDefinition, DefinitionError> {
+        let mut contents = vec![];
+        reader.read_to_end(&mut contents).await?;
+
+        TargetDefinition::from_ron(&contents)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["target.ron"]
+    }
+}
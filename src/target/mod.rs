@@ -0,0 +1,7 @@
+pub mod definition;
+pub mod generator;
+pub mod loader;
+pub mod plugin;
+pub mod state;
+pub(crate) mod systems;
+pub mod tier;
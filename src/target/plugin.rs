@@ -0,0 +1,18 @@
+use bevy::app::{App, Plugin, Startup, Update};
+use bevy::asset::AssetApp;
+use crate::target::definition::TargetDefinition;
+use crate::target::loader::TargetDefinitionLoader;
+use crate::target::systems::*;
+
+pub struct TargetPlugin;
+
+impl Plugin for TargetPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            .init_asset::<TargetDefinition>()
+            .init_asset_loader::<TargetDefinitionLoader>()
+            .add_observer(on_exploit_started)
+            .add_systems(Startup, load_target_definitions)
+            .add_systems(Update, sync_known_targets);
+    }
+}
@@ -0,0 +1,9 @@
+use bevy::asset::Handle;
+use bevy::prelude::Resource;
+use crate::target::definition::TargetDefinition;
+
+/// Keeps every target definition loaded, so they stay available to hot reload.
+#[derive(Resource)]
+pub struct TargetDefinitions {
+    pub handles: Vec<Handle<TargetDefinition>>,
+}
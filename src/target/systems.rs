@@ -0,0 +1,199 @@
+use std::sync::{Arc, Mutex};
+use bevy::asset::{AssetEvent, AssetServer, Assets};
+use bevy::log::info;
+use bevy::prelude::{Commands, MessageReader, On, Res, ResMut};
+use crate::active_exploit::ExploitTarget;
+use crate::event::exploit_started::ExploitStarted;
+use crate::lock_and_clone;
+use crate::player_state::state::PlayerState;
+use crate::script::id::ScriptId;
+use crate::target::definition::TargetDefinition;
+use crate::target::state::TargetDefinitions;
+
+/// Every target definition in the game, relative to the assets folder.
+/// These are listed rather than loading the whole folder, as folders can't be loaded on the web.
+const TARGET_DEFINITION_PATHS: &[&str] = &[
+    "targets/corp.target.ron",
+];
+
+pub(crate) fn load_target_definitions(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let handles = TARGET_DEFINITION_PATHS
+        .iter()
+        .map(|path| asset_server.load(*path))
+        .collect();
+
+    commands.insert_resource(TargetDefinitions { handles });
+}
+
+/// Adds newly loaded definitions to the player's known targets, and applies hot reloaded definitions
+/// to the targets already built from them.
+/// Definitions load after the save does, so a loaded definition is only new if no known target was built from it.
+pub(crate) fn sync_known_targets(
+    mut asset_events: MessageReader<AssetEvent<TargetDefinition>>,
+    asset_server: Res<AssetServer>,
+    definitions: Res<Assets<TargetDefinition>>,
+    mut player_state: ResMut<PlayerState>,
+) {
+    for asset_event in asset_events.read() {
+        let (AssetEvent::Added { id } | AssetEvent::Modified { id }) = asset_event else {
+            continue;
+        };
+
+        let (Some(definition), Some(path)) = (definitions.get(*id), asset_server.get_path(*id)) else {
+            continue;
+        };
+
+        match asset_event {
+            AssetEvent::Modified { .. } => reload_target_definition(&mut player_state, &path.to_string(), definition),
+            _ => add_target_definition(&mut player_state, &path.to_string(), definition),
+        }
+    }
+}
+
+/// Connecting to one of the hardest targets on offer opens up harder ones.
+pub(crate) fn on_exploit_started(
+    evt: On<ExploitStarted>,
+    mut player_state: ResMut<PlayerState>,
+) {
+    let tier = player_state
+        .active_exploits
+        .iter()
+        .find(|exploit| exploit.lock().unwrap().id == evt.exploit_id)
+        .map(|exploit| lock_and_clone!(exploit, target, tier));
+
+    if let Some(tier) = tier {
+        player_state.extend_target_ladder(tier);
+    }
+}
+
+/// The known target built from the definition at `path`, if there is one.
+fn target_defined_by(player_state: &PlayerState, path: &str) -> Option<Arc<Mutex<ExploitTarget>>> {
+    player_state
+        .known_targets
+        .iter()
+        .find(|target| target.lock().unwrap().definition.as_deref() == Some(path))
+        .cloned()
+}
+
+/// Adds a new known target built from the definition at `path`, unless one was already built from it
+/// (eg. restored from a save), in which case it's left exactly as it is.
+pub(crate) fn add_target_definition(player_state: &mut PlayerState, path: &str, definition: &TargetDefinition) {
+    if target_defined_by(player_state, path).is_some() {
+        return;
+    }
+
+    let target = definition.build(&mut player_state.rng_streams.next_stream());
+    target.lock().unwrap().definition = Some(path.to_string());
+    player_state.known_targets.push(target);
+}
+
+/// Redefines the known target built from `path` in place, so exploits against it see the new definition,
+/// or adds a new known target if there isn't one yet.
+pub(crate) fn reload_target_definition(player_state: &mut PlayerState, path: &str, definition: &TargetDefinition) {
+    if let Some(target) = target_defined_by(player_state, path) {
+        let mut rng = player_state.rng_streams.next_stream();
+        let mut target = target.lock().unwrap();
+        target.redefine(
+            definition.server.build(),
+            definition.script.build(ScriptId::Invalid, &mut rng),
+            definition.connection_health,
+            definition.max_credits(),
+        );
+        target.tier = definition.tier;
+
+        info!("reloaded target definition {path}");
+        return;
+    }
+
+    add_target_definition(player_state, path, definition);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::save;
+    use crate::server::{ServerStatInstance, ServerStatSource, ServerStatType, ServerStats};
+    use crate::target::definition::corp_definition;
+    use crate::target::tier::DifficultyTier;
+    use super::*;
+
+    const PATH: &str = "targets/corp.target.ron";
+
+    #[test]
+    fn new_definitions_become_known_targets() {
+        let mut player_state = PlayerState::new(1234);
+        let generated_targets = player_state.known_targets.len();
+        add_target_definition(&mut player_state, PATH, &corp_definition());
+
+        assert_eq!(player_state.known_targets.len(), generated_targets + 1);
+        assert_eq!(player_state.known_targets.last().unwrap().lock().unwrap().definition.as_deref(), Some(PATH));
+    }
+
+    #[test]
+    fn reloaded_definitions_update_existing_targets() {
+        let mut player_state = PlayerState::new(1234);
+        add_target_definition(&mut player_state, PATH, &corp_definition());
+        let target_count = player_state.known_targets.len();
+        let target = player_state.known_targets.last().unwrap().clone();
+        let target_id = target.lock().unwrap().id;
+
+        let mut definition = corp_definition();
+        definition.server.stats = vec![(ServerStatType::SiphonResist, 10)];
+        definition.connection_health = 80;
+        reload_target_definition(&mut player_state, PATH, &definition);
+
+        assert_eq!(player_state.known_targets.len(), target_count);
+        assert!(Arc::ptr_eq(player_state.known_targets.last().unwrap(), &target));
+
+        let target = target.lock().unwrap();
+        assert_eq!(target.id, target_id);
+        assert_eq!(target.connection_health, 80);
+        assert_eq!(target.server.lock().unwrap().stats.value_of(ServerStatType::SiphonResist), 10);
+    }
+
+    #[test]
+    fn loading_definitions_leaves_restored_targets_alone() {
+        let mut player_state = PlayerState::new(1234);
+        add_target_definition(&mut player_state, PATH, &corp_definition());
+        let weakened = ServerStatInstance::new(
+            ServerStatSource::Script { script_id: ScriptId::Id(1), exploit_id: None },
+            ServerStatType::SiphonResist,
+            -1,
+        );
+        player_state.known_targets.last().unwrap().lock().unwrap().server.lock().unwrap().stats.apply_and_purge(weakened);
+
+        let contents = save::serialize(&player_state).unwrap();
+        let (mut restored, _) = save::deserialize(&contents).unwrap();
+        let target_count = restored.known_targets.len();
+        let target = restored.known_targets.last().unwrap().clone();
+        let server = target.lock().unwrap().server.clone();
+        let siphon_resist = server.lock().unwrap().stats.value_of(ServerStatType::SiphonResist);
+
+        // Definitions always finish loading after the save has been restored
+        add_target_definition(&mut restored, PATH, &corp_definition());
+
+        assert_eq!(restored.known_targets.len(), target_count);
+        assert!(Arc::ptr_eq(&target.lock().unwrap().server, &server));
+        assert_eq!(server.lock().unwrap().stats.value_of(ServerStatType::SiphonResist), siphon_resist);
+    }
+
+    #[test]
+    fn connecting_to_harder_targets_extends_the_ladder() {
+        let mut player_state = PlayerState::new(1234);
+        let tiers = |player_state: &PlayerState| player_state
+            .known_targets
+            .iter()
+            .map(|target| target.lock().unwrap().tier.0)
+            .collect::<Vec<_>>();
+        assert_eq!(tiers(&player_state), vec![1, 2]);
+
+        player_state.extend_target_ladder(DifficultyTier(1));
+        assert_eq!(tiers(&player_state), vec![1, 2, 3]);
+
+        // Reaching an easier tier again doesn't generate anything new
+        player_state.extend_target_ladder(DifficultyTier(1));
+        assert_eq!(tiers(&player_state), vec![1, 2, 3]);
+    }
+}
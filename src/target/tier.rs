@@ -0,0 +1,26 @@
+use std::fmt::{Display, Formatter};
+use serde::{Deserialize, Serialize};
+
+/// How hard a target is to exploit, and how well it pays off.
+/// Tier 0 is the hand-written starting corporation; generated targets start at tier 1.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DifficultyTier(pub u32);
+
+impl DifficultyTier {
+    /// How many credits targets of this tier usually hold when their credit pool is full.
+    /// Siphons take a share of what's left, so richer pools are what make harder targets pay out more.
+    pub fn credit_pool(self) -> u64 {
+        1_000 * (1 + self.0 as u64)
+    }
+
+    pub fn next(self) -> DifficultyTier {
+        DifficultyTier(self.0 + 1)
+    }
+}
+
+impl Display for DifficultyTier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
@@ -1,3 +1,6 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Serialize, Deserialize)]
 pub enum TutorialProgression {
     /// The option to start with a tutorial hasn't been presented to the player yet.
     None,
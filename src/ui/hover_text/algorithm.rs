@@ -2,6 +2,7 @@ use bevy_egui::egui::text::LayoutJob;
 use bevy_egui::egui::{Color32, TextFormat};
 use crate::{loc, PlayerState};
 use crate::algorithm::algorithm::Algorithm;
+use crate::algorithm::rarity::AlgorithmRarity;
 use crate::l10n::message_id::MessageId;
 use crate::ui::hover_text::OnHoverText;
 
@@ -10,6 +11,20 @@ impl OnHoverText for Algorithm {
 
     fn on_hover_text(&self, state: &Self::State) -> LayoutJob {
         let mut hover_text_layout_job = LayoutJob::default();
+
+        let rarity_text_format = TextFormat {
+            color: match self.rarity {
+                AlgorithmRarity::Common => Color32::LIGHT_GRAY,
+                AlgorithmRarity::Uncommon => Color32::LIGHT_GREEN,
+                AlgorithmRarity::Rare => Color32::LIGHT_BLUE,
+                AlgorithmRarity::Epic => Color32::from_rgb(200, 120, 255),
+                AlgorithmRarity::Legendary => Color32::ORANGE,
+            },
+            ..Default::default()
+        };
+        hover_text_layout_job.append(&state.localize(&self.rarity), 0.0, rarity_text_format);
+        hover_text_layout_job.append("\n", 0.0, TextFormat::default());
+
         hover_text_layout_job.append(
             &state.localize(&self.instruction_count),
             0.0,
@@ -25,11 +40,18 @@ impl OnHoverText for Algorithm {
 
         let mut effect_text_format = TextFormat::default();
         effect_text_format.color = Color32::GOLD;
-        for (_, effects) in &self.instruction_effects {
+        for (instruction, effects) in &self.instruction_effects {
+            hover_text_layout_job.append(
+                &loc!(state, MessageId::UiAlgorithmEffectCheckpoint, [("instruction", (**instruction).into())].into()),
+                10.0,
+                TextFormat::default(),
+            );
+            hover_text_layout_job.append("\n", 10.0, TextFormat::default());
+
             for effect in effects {
                 hover_text_layout_job.append(
                     &state.localize(effect),
-                    10.0,
+                    20.0,
                     effect_text_format.clone(),
                 );
 
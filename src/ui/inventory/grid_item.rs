@@ -66,6 +66,7 @@ impl InventoryGridItem for AlgorithmGridItem {
                 AlgorithmEffect::Exfil { .. } => display_str.push_str("X"),
                 AlgorithmEffect::Modify { .. } => display_str.push_str("~"),
                 AlgorithmEffect::Purge { .. } => display_str.push_str("+"),
+                AlgorithmEffect::PurgeAll { .. } => display_str.push('*'),
             }
         }
 
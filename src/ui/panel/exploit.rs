@@ -3,9 +3,10 @@ use bevy::asset::AssetServer;
 use bevy::audio::{AudioPlayer, PlaybackSettings};
 use bevy::prelude::Commands;
 use bevy_egui::egui;
-use bevy_egui::egui::{Context, Ui};
-use crate::{lock_and_clone, ExploitTarget, PlayerState};
+use bevy_egui::egui::{Color32, Context, RichText, Ui};
+use crate::{loc, lock_and_clone, ExploitTarget, PlayerState};
 use crate::event::request_start_exploit::RequestStartExploitEvent;
+use crate::l10n::message_id::MessageId;
 use crate::script::Script;
 use crate::server::Server;
 use crate::ui::hover_text::OnHoverText;
@@ -27,6 +28,12 @@ impl ExploitPanel {
     }
 }
 
+impl Default for ExploitPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Panel for ExploitPanel {
     fn update(
         &mut self,
@@ -37,14 +44,21 @@ impl Panel for ExploitPanel {
         asset_server: &AssetServer,
     ) {
         ui.heading("Targets");
-        ui.horizontal(|ui| {
-            for exploit_target in &player_state.known_targets {
-                let exploit_target_id = exploit_target.lock().unwrap().id;
+        let mut known_targets = player_state.known_targets.iter().collect::<Vec<_>>();
+        known_targets.sort_by_key(|target| target.lock().unwrap().tier);
+        ui.horizontal_wrapped(|ui| {
+            for exploit_target in known_targets {
+                let (exploit_target_id, tier, credits, max_credits) = {
+                    let exploit_target = exploit_target.lock().unwrap();
+                    (exploit_target.id, exploit_target.tier, exploit_target.credits, exploit_target.max_credits)
+                };
                 let is_selected = match self.selected_exploit_target {
                     Some(ref target) => target.lock().unwrap().id == exploit_target_id,
                     None => false,
                 };
-                if ui.selectable_label(is_selected, format!("{}", lock_and_clone!(exploit_target, server, name))).clicked() {
+                // ZJ-TODO: loc
+                let label = format!("{} (tier {tier}, ${credits}/${max_credits})", lock_and_clone!(exploit_target, server, name));
+                if ui.selectable_label(is_selected, label).clicked() {
                     self.selected_exploit_target = Some(exploit_target.to_owned());
                 }
             }
@@ -105,8 +119,25 @@ impl Panel for ExploitPanel {
             });
 
         ui.separator();
+        let mut has_enough_threads = true;
+        if let (Some(script), Some(server)) = (&self.selected_script, &self.selected_server) {
+            let threads_required = script.lock().unwrap().threads_required();
+            let available_threads = player_state.available_threads(server);
+            if threads_required > available_threads {
+                has_enough_threads = false;
+                ui.label(RichText::new(loc!(
+                    player_state,
+                    MessageId::UiExploitNotEnoughThreads,
+                    [
+                        ("threads_required", threads_required.into()),
+                        ("thread_count", available_threads.into()),
+                    ].into()
+                )).color(Color32::RED));
+            }
+        }
+
         let required_fields_set = self.selected_exploit_target.is_some() && self.selected_script.is_some() && self.selected_server.is_some();
-        if ui.add_enabled(required_fields_set, egui::Button::new("Run")).clicked() {
+        if ui.add_enabled(required_fields_set && has_enough_threads, egui::Button::new("Run")).clicked() {
             commands.trigger(RequestStartExploitEvent {
                 target: self.selected_exploit_target.as_ref().unwrap().clone(),
                 script: self.selected_script.as_ref().unwrap().clone(),
@@ -2,11 +2,19 @@ use bevy::asset::AssetServer;
 use bevy::prelude::Commands;
 use bevy_egui::egui;
 use bevy_egui::egui::{Context, Ui};
+use crate::algorithm::market::AlgorithmMarket;
+use crate::event::request_purchase_algorithm::RequestPurchaseAlgorithmEvent;
+use crate::event::request_purchase_server::RequestPurchaseServerEvent;
 use crate::event::request_purchase_unlock::RequestPurchaseUnlockEvent;
+use crate::event::request_sell_algorithm::RequestSellAlgorithmEvent;
+use crate::event::request_upgrade_server::RequestUpgradeServerEvent;
 use crate::{loc, PlayerState, PlayerUnlock};
 use crate::event::tutorial_data_dump_purchased::TutorialDataDumpPurchased;
 use crate::l10n::message_id::MessageId;
+use crate::server::market::{ServerMarket, ServerUpgrade, CLOCK_SPEED_UPGRADE};
 use crate::tutorial::progression::TutorialProgression;
+use crate::ui::clock_speed::ClockSpeed;
+use crate::ui::hover_text::OnHoverText;
 use crate::ui::panel::Panel;
 
 pub struct MarketPanel {
@@ -58,5 +66,114 @@ impl Panel for MarketPanel {
                 });
             }
         }
+
+        ui.heading(loc!(player_state, MessageId::MarketAlgorithmsHeader));
+
+        let time_until_restock = player_state.algorithm_market.time_until_restock.as_secs();
+        ui.label(loc!(
+            player_state,
+            MessageId::MarketAlgorithmRestock,
+            [
+                ("minutes", (time_until_restock / 60).into()),
+                ("seconds", (time_until_restock % 60).into()),
+            ].into()
+        ));
+
+        if player_state.algorithm_market.stock.is_empty() {
+            ui.label(loc!(player_state, MessageId::MarketAlgorithmSoldOut));
+        }
+
+        for algorithm_arc in &player_state.algorithm_market.stock {
+            let algorithm = algorithm_arc.lock().unwrap().clone();
+            let credit_cost = AlgorithmMarket::price(&algorithm);
+            let purchase_button = egui::Button::new(Self::priced_label(
+                player_state,
+                player_state.localize(&algorithm.instruction_count),
+                credit_cost,
+            ));
+
+            let purchase_response = ui
+                .add_enabled(credit_cost <= player_state.credits, purchase_button)
+                .on_hover_text(algorithm.on_hover_text(player_state))
+                .on_disabled_hover_text(algorithm.on_hover_text(player_state));
+
+            if purchase_response.clicked() {
+                commands.trigger(RequestPurchaseAlgorithmEvent {
+                    algorithm: algorithm_arc.clone(),
+                });
+            }
+        }
+
+        if !player_state.inventory.algorithms.is_empty() {
+            ui.heading(loc!(player_state, MessageId::MarketAlgorithmSellHeader));
+        }
+
+        for algorithm_arc in &player_state.inventory.algorithms {
+            let algorithm = algorithm_arc.lock().unwrap().clone();
+            let credit_value = AlgorithmMarket::sell_price(&algorithm);
+
+            ui.horizontal(|horiz_ui| {
+                horiz_ui
+                    .label(player_state.localize(&algorithm.instruction_count))
+                    .on_hover_text(algorithm.on_hover_text(player_state));
+
+                let sell_text = loc!(player_state, MessageId::MarketAlgorithmSell, [("credit_value", credit_value.into())].into());
+                if horiz_ui.button(sell_text).clicked() {
+                    commands.trigger(RequestSellAlgorithmEvent {
+                        algorithm: algorithm_arc.clone(),
+                    });
+                }
+            });
+        }
+
+        ui.heading(loc!(player_state, MessageId::MarketServersHeader));
+
+        let credit_cost = ServerMarket::server_cost(player_state.servers.len());
+        let purchase_button = egui::Button::new(Self::priced_label(
+            player_state,
+            loc!(player_state, MessageId::MarketServerPurchase),
+            credit_cost,
+        ));
+        if ui.add_enabled(credit_cost <= player_state.credits, purchase_button).clicked() {
+            commands.trigger(RequestPurchaseServerEvent);
+        }
+
+        for server_arc in &player_state.servers {
+            let server = server_arc.lock().unwrap().clone();
+            ui.horizontal(|horiz_ui| {
+                horiz_ui.label(&server.name);
+
+                for upgrade in [ServerUpgrade::ClockSpeed, ServerUpgrade::Threads, ServerUpgrade::ConnectionStrength, ServerUpgrade::Stealth] {
+                    let upgrade_name = match upgrade {
+                        ServerUpgrade::ClockSpeed => loc!(
+                            player_state,
+                            MessageId::MarketServerUpgradeClockSpeed,
+                            [("clock_speed", player_state.localize(&ClockSpeed::new(CLOCK_SPEED_UPGRADE)).into())].into()
+                        ),
+                        ServerUpgrade::Threads => loc!(player_state, MessageId::MarketServerUpgradeThreads),
+                        ServerUpgrade::ConnectionStrength => loc!(player_state, MessageId::MarketServerUpgradeConnectionStrength),
+                        ServerUpgrade::Stealth => loc!(player_state, MessageId::MarketServerUpgradeStealth),
+                    };
+
+                    let credit_cost = ServerMarket::upgrade_cost(&server, upgrade);
+                    let upgrade_button = egui::Button::new(Self::priced_label(player_state, upgrade_name, credit_cost));
+                    if horiz_ui.add_enabled(credit_cost <= player_state.credits, upgrade_button).clicked() {
+                        commands.trigger(RequestUpgradeServerEvent {
+                            server: server_arc.clone(),
+                            upgrade,
+                        });
+                    }
+                }
+            });
+        }
+    }
+}
+
+impl MarketPanel {
+    fn priced_label(player_state: &PlayerState, name: String, credit_cost: u128) -> String {
+        format!(
+            "{name}\n{}",
+            loc!(player_state, MessageId::MarketUnlockCreditCost, [("credit_cost", credit_cost.into())].into()),
+        )
     }
 }
\ No newline at end of file
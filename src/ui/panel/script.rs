@@ -28,6 +28,12 @@ impl ScriptsPanel {
     }
 }
 
+impl Default for ScriptsPanel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Panel for ScriptsPanel {
     fn update(
         &mut self,
@@ -145,62 +151,147 @@ impl Panel for ScriptsPanel {
 
         egui::CentralPanel::default().show(ctx, |ui| {
             let script = self.script_builder.current_script();
+            let threads_required = script.threads_required();
 
             let mut algorithm_to_remove: Option<Arc<Mutex<Algorithm>>> = None;
+            let mut procedure_to_select: Option<usize> = None;
+            let mut procedure_to_remove: Option<usize> = None;
+            let mut branch_point_to_toggle: Option<usize> = None;
+
+            // Procedures run in parallel, so they're laid out side by side
+            ui.horizontal_top(|ui| {
+                for (procedure_idx, procedure) in script.procedures.iter().enumerate() {
+                    ui.vertical(|ui| {
+                        ui.horizontal(|ui| {
+                            let is_selected = self.script_builder.selected_procedure() == procedure_idx;
+                            let header = RichText::new(format!(
+                                "{} {}",
+                                loc!(player_state, MessageId::UiAlgorithmProcedureHeader),
+                                procedure_idx + 1,
+                            )).heading();
+                            if ui.selectable_label(is_selected, header).clicked() {
+                                procedure_to_select = Some(procedure_idx);
+                            }
 
-            for (_, procedure) in script.procedures.iter().enumerate() {
-                ui.heading(loc!(player_state, MessageId::UiAlgorithmProcedureHeader));
+                            if ui.small_button(loc!(player_state, MessageId::UiScriptRemoveProcedure)).clicked() {
+                                procedure_to_remove = Some(procedure_idx);
+                            }
+                        });
 
-                ui.group(|ui| {
-                    ui.label("Start");
-                });
+                        ui.group(|ui| {
+                            ui.label("Start");
+                        });
 
-                let down_arrow_img = egui::Image::new(egui::include_image!("../../../assets/sprites/down-arrow.png"))
-                    .max_size([32f32, 32f32].into());
-                ui.add(down_arrow_img.clone());
+                        let down_arrow_img = egui::Image::new(egui::include_image!("../../../assets/sprites/down-arrow.png"))
+                            .max_size([32f32, 32f32].into());
+                        ui.add(down_arrow_img.clone());
+
+                        let is_selected_procedure = self.script_builder.selected_procedure() == procedure_idx;
+                        let procedure = procedure.lock().unwrap();
+                        for (node_idx, node) in procedure.nodes().iter().enumerate() {
+                            let algorithm = node.algorithm.clone();
+                            let algorithm_inner = algorithm.lock().unwrap();
+                            let group = ui.group(|ui| {
+                                ui.horizontal(|ui| {
+                                    ui.label(RichText::new(format!("#{}", node_idx + 1)).strong());
+
+                                    // Algorithms simply following the one above them need no explanation
+                                    let follows_previous = node_idx > 0 && node.predecessors == [node_idx - 1];
+                                    if !node.predecessors.is_empty() && !follows_previous {
+                                        let predecessors = node
+                                            .predecessors
+                                            .iter()
+                                            .map(|predecessor| format!("#{}", predecessor + 1))
+                                            .collect::<Vec<_>>()
+                                            .join(", ");
+                                        ui.label(loc!(
+                                            player_state,
+                                            MessageId::UiScriptNodeFollows,
+                                            [("nodes", predecessors.into())].into()
+                                        ));
+                                    }
+                                });
 
-                let procedure = procedure.lock().unwrap();
-                let mut algorithms = procedure.iterator();
-                while let Some(algorithm) = algorithms.next() {
-                    let algorithm = algorithm.upgrade().unwrap();
-                    let algorithm_inner = algorithm.lock().unwrap();
-                    let group = ui.group(|ui| {
-                        ui.label(player_state.localize(&algorithm_inner.instruction_count));
+                                ui.label(player_state.localize(&algorithm_inner.instruction_count));
 
-                        ui.label(loc!(player_state, MessageId::UiAlgorithmEffectsHeader));
-                        for (_, effects) in &algorithm_inner.instruction_effects {
-                            for effect in effects {
-                                ui
-                                    .label(RichText::new(player_state.localize(effect))
-                                    .color(Color32::GOLD));
+                                ui.label(loc!(player_state, MessageId::UiAlgorithmEffectsHeader));
+                                for (_, effects) in &algorithm_inner.instruction_effects {
+                                    for effect in effects {
+                                        ui
+                                            .label(RichText::new(player_state.localize(effect))
+                                            .color(Color32::GOLD));
+                                    }
+                                }
+                            });
+
+                            if group.response.interact(Sense::hover()).hovered() {
+                                ui.painter().rect_stroke(
+                                    group.response.rect,
+                                    ui.style().visuals.widgets.noninteractive.corner_radius,
+                                    egui::Stroke::new(2.0, Color32::RED),
+                                    StrokeKind::Outside
+                                );
                             }
-                        }
+
+                            if group.response.interact(Sense::click()).clicked() {
+                                algorithm_to_remove = Some(algorithm.clone());
+
+                                commands.spawn((
+                                    AudioPlayer::new(asset_server.load("audio/click.ogg")),
+                                    PlaybackSettings::ONCE
+                                ));
+                            }
+
+                            // Kept outside the group, as clicking the group removes the algorithm
+                            if is_selected_procedure {
+                                let is_branch_point = self.script_builder.branch_points().contains(&node_idx);
+                                if ui.selectable_label(is_branch_point, loc!(player_state, MessageId::UiScriptBranchFrom)).clicked() {
+                                    branch_point_to_toggle = Some(node_idx);
+                                }
+                            }
+
+                            ui.add(down_arrow_img.clone());
+                        };
+
+                        ui.group(|ui| {
+                            ui.label("End");
+                        });
                     });
+                }
+            });
 
-                    if group.response.interact(Sense::hover()).hovered() {
-                        ui.painter().rect_stroke(
-                            group.response.rect,
-                            ui.style().visuals.widgets.noninteractive.corner_radius,
-                            egui::Stroke::new(2.0, Color32::RED),
-                            StrokeKind::Outside
-                        );
-                    }
-
-                    if group.response.interact(Sense::click()).clicked() {
-                        algorithm_to_remove = Some(algorithm.clone());
-
-                        commands.spawn((
-                            AudioPlayer::new(asset_server.load("audio/click.ogg")),
-                            PlaybackSettings::ONCE
-                        ));
-                    }
-
-                    ui.add(down_arrow_img.clone());
-                };
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button(loc!(player_state, MessageId::UiScriptAddProcedure)).clicked() {
+                    self.script_builder.add_procedure();
+
+                    commands.spawn((
+                        AudioPlayer::new(asset_server.load("audio/click.ogg")),
+                        PlaybackSettings::ONCE
+                    ));
+                }
+
+                ui.label(loc!(
+                    player_state,
+                    MessageId::UiScriptThreadsRequired,
+                    [("thread_count", threads_required.into())].into()
+                ));
+            });
 
-                ui.group(|ui| {
-                    ui.label("End");
-                });
+            if let Some(node_idx) = branch_point_to_toggle {
+                self.script_builder.toggle_branch_point(node_idx);
+            }
+
+            if let Some(procedure_idx) = procedure_to_select {
+                self.script_builder.select_procedure(procedure_idx);
+            }
+
+            if let Some(procedure_idx) = procedure_to_remove {
+                for algorithm in self.script_builder.remove_procedure(procedure_idx) {
+                    commands.trigger(InventoryItemAdded {
+                        item: InventoryItem::Algorithm(algorithm),
+                    });
+                }
             }
 
             if let Some(algorithm) = algorithm_to_remove {
@@ -1,9 +1,14 @@
+use std::sync::{Arc, Mutex};
 use bevy::asset::AssetServer;
+use bevy::log::warn;
 use bevy::prelude::Commands;
 use bevy_egui::egui;
 use bevy_egui::egui::{Context, Ui};
 use crate::{loc, lock_and_clone, PlayerState};
+use crate::event::request_assign_defensive_script::RequestAssignDefensiveScriptEvent;
 use crate::l10n::message_id::MessageId;
+use crate::server::Server;
+use crate::server::scheduler::{Allocation, DEFENSE_ID};
 use crate::ui::clock_speed::ClockSpeed;
 use crate::ui::panel::Panel;
 
@@ -14,65 +19,157 @@ pub struct ServersPanel {
 impl Panel for ServersPanel {
     fn update(
         &mut self,
-        _: &mut Commands,
+        commands: &mut Commands,
         _: &Context,
         ui: &mut Ui,
         player_state: &PlayerState,
         _: &AssetServer,
     ) {
-        for server_arc in &player_state.servers {
-            let server = server_arc.lock().unwrap().clone();
-            ui.group(|group_ui| {
-                group_ui.vertical_centered(|vert_ui| {
-                    vert_ui.heading(&server.name);
+        for rack in &player_state.racks {
+            ui.heading(&rack.name);
 
-                    vert_ui.label(player_state.localize(&server.clock_speed));
+            let power_label = egui::RichText::new(loc!(
+                player_state,
+                MessageId::UiRackPower,
+                [
+                    ("power_draw", rack.power_draw().into()),
+                    ("power_capacity", rack.power_capacity.into()),
+                ].into()
+            ));
+            // Over capacity, every server in the rack is running slower than it could
+            ui.label(if rack.power_utilization() > 1.0 { power_label.color(egui::Color32::RED) } else { power_label });
 
-                    vert_ui.label(loc!(
-                        player_state,
-                        MessageId::UiServerThreadCount,
-                        [("thread_count", server.threads.into())].into()
-                    ));
+            ui.label(loc!(
+                player_state,
+                MessageId::UiRackBandwidth,
+                [
+                    ("bandwidth", rack.bandwidth.into()),
+                    ("bandwidth_per_server", rack.bandwidth_per_server().into()),
+                ].into()
+            ));
 
-                    let active_exploits_on_this_server: Vec<_> = player_state
-                        .active_exploits
-                        .iter()
-                        .filter(|exploit| {
-                            lock_and_clone!(exploit, hosting_server, name) == server.name
-                        })
-                        .collect();
+            for server_arc in &rack.servers {
+                Self::server_ui(commands, ui, player_state, server_arc);
+            }
+        }
+    }
+}
 
-                    if !active_exploits_on_this_server.is_empty() {
-                        vert_ui.heading("Active Exploits");
-                    }
+impl ServersPanel {
+    fn server_ui(commands: &mut Commands, ui: &mut Ui, player_state: &PlayerState, server_arc: &Arc<Mutex<Server>>) {
+        let server = server_arc.lock().unwrap().clone();
+        ui.group(|group_ui| {
+            group_ui.vertical_centered(|vert_ui| {
+                vert_ui.heading(&server.name);
 
-                    for exploit in active_exploits_on_this_server {
-                        let hosting_server = lock_and_clone!(exploit, hosting_server);
-                        let target_server = lock_and_clone!(exploit, target, server);
-                        let target_name = lock_and_clone!(target_server, name);
+                vert_ui.label(player_state.localize(&server.clock_speed));
 
-                        vert_ui.horizontal(|horiz_ui| {
-                            let mut clock_allocation = lock_and_clone!(exploit, clock_allocation);
+                if *server.effective_clock_speed < *server.clock_speed {
+                    vert_ui.colored_label(egui::Color32::RED, loc!(
+                        player_state,
+                        MessageId::UiServerUnderclocked,
+                        [("clock_speed", player_state.localize(&server.effective_clock_speed).into())].into()
+                    ));
+                }
 
-                            horiz_ui.label(target_name);
+                vert_ui.label(loc!(
+                    player_state,
+                    MessageId::UiServerThreadCount,
+                    [("thread_count", server.threads.into())].into()
+                ));
 
-                            let mut slider = egui::Slider::new(
-                                &mut *clock_allocation,
-                                // ZJ-TODO: this should be available CPU, not total clock speed
-                                0 ..= *lock_and_clone!(hosting_server, clock_speed)
-                            );
+                vert_ui.horizontal(|horiz_ui| {
+                    horiz_ui.label(loc!(player_state, MessageId::UiServerDefensiveScript));
 
-                            slider = slider.custom_formatter(|val, _| {
-                                player_state.localize(&ClockSpeed::new(val.round() as u64))
-                            });
+                    let defensive_script_id = server.defensive_script.as_ref().map(|script| lock_and_clone!(script, id));
+                    let selected_text = match &defensive_script_id {
+                        Some(script_id) => format!("{script_id}"),
+                        None => loc!(player_state, MessageId::UiServerDefensiveScriptNone),
+                    };
 
-                            horiz_ui.add(slider);
+                    egui::ComboBox::from_id_salt(("defensive_script", &server.name))
+                        .selected_text(selected_text)
+                        .show_ui(horiz_ui, |combo_ui| {
+                            if combo_ui.selectable_label(defensive_script_id.is_none(), loc!(player_state, MessageId::UiServerDefensiveScriptNone)).clicked()
+                                && defensive_script_id.is_some()
+                            {
+                                commands.trigger(RequestAssignDefensiveScriptEvent { server: server_arc.clone(), script: None });
+                            }
 
-                            exploit.lock().unwrap().clock_allocation = clock_allocation;
+                            for script in &player_state.scripts {
+                                let script_id = lock_and_clone!(script, id);
+                                let is_selected = defensive_script_id.as_ref() == Some(&script_id);
+                                if combo_ui.selectable_label(is_selected, format!("{script_id}")).clicked() && !is_selected {
+                                    commands.trigger(RequestAssignDefensiveScriptEvent { server: server_arc.clone(), script: Some(script.clone()) });
+                                }
+                            }
                         });
-                    }
                 });
+
+                if !server.scheduler.entries().is_empty() {
+                    vert_ui.heading(loc!(player_state, MessageId::UiServerActiveExploitsHeader));
+                }
+
+                let capacity = *server.clock_speed;
+                for (exploit_id, allocation) in server.scheduler.entries() {
+                    // The defensive script has its own share of the clock, alongside the exploits
+                    let (target_name, clock_allocation) = if *exploit_id == DEFENSE_ID {
+                        (loc!(player_state, MessageId::UiServerDefensiveScript), server.defense.clock_allocation.clone())
+                    } else {
+                        let Some(exploit) = player_state
+                            .active_exploits
+                            .iter()
+                            .find(|exploit| lock_and_clone!(exploit, id) == *exploit_id)
+                        else {
+                            continue;
+                        };
+
+                        (lock_and_clone!(lock_and_clone!(exploit, target, server), name), lock_and_clone!(exploit, clock_allocation))
+                    };
+
+                    vert_ui.horizontal(|horiz_ui| {
+                        horiz_ui.label(target_name);
+
+                        let mut is_pinned = matches!(allocation, Allocation::Pinned(_));
+                        let mut new_allocation = *allocation;
+                        if horiz_ui.checkbox(&mut is_pinned, loc!(player_state, MessageId::UiServerExploitPin)).changed() {
+                            new_allocation = if is_pinned {
+                                // Pin whatever it's getting now, so toggling doesn't disturb anything else
+                                let available = capacity.saturating_sub(server.scheduler.pinned_except(*exploit_id));
+                                Allocation::Pinned((*clock_allocation).min(available))
+                            } else {
+                                Allocation::default()
+                            };
+                        }
+
+                        match &mut new_allocation {
+                            Allocation::Pinned(clock_speed) => {
+                                // Only what other pins haven't already claimed can be pinned
+                                let available = capacity.saturating_sub(server.scheduler.pinned_except(*exploit_id));
+                                let slider = egui::Slider::new(clock_speed, 0..=available)
+                                    .custom_formatter(|val, _| {
+                                        player_state.localize(&ClockSpeed::new(val.round() as u64))
+                                    });
+
+                                horiz_ui.add(slider);
+                            }
+                            Allocation::Weighted(weight) => {
+                                horiz_ui.label(loc!(player_state, MessageId::UiServerExploitWeight));
+                                horiz_ui.add(egui::DragValue::new(weight).range(1..=100));
+                                horiz_ui.label(player_state.localize(&clock_allocation));
+                            }
+                        }
+
+                        if new_allocation != *allocation {
+                            let mut server = server_arc.lock().unwrap();
+                            match server.scheduler.set_allocation(*exploit_id, new_allocation, capacity) {
+                                Ok(()) => server.reschedule(&player_state.active_exploits),
+                                Err(err) => warn!("couldn't change allocation of {exploit_id}: {err}"),
+                            }
+                        }
+                    });
+                }
             });
-        }
+        });
     }
-}
\ No newline at end of file
+}
@@ -9,7 +9,9 @@ use bevy_egui::egui::Widget;
 use crate::{loc, PlayerState};
 use crate::event::exploit_event::ExploitEvent;
 use crate::event::exploit_started::ExploitStarted;
+use crate::event::offline_catch_up_finished::OfflineCatchUpFinished;
 use crate::l10n::message_id::MessageId;
+use crate::offline::state::OfflineCatchUp;
 use crate::tutorial::progression::TutorialProgression;
 use crate::ui::panel::exploit::ExploitPanel;
 use crate::ui::panel::market::MarketPanel;
@@ -17,6 +19,7 @@ use crate::ui::panel::Panel;
 use crate::ui::panel::script::ScriptsPanel;
 use crate::ui::panel::server::ServersPanel;
 use crate::ui::state::{ActivePanel, UiState};
+use crate::ui::window::offline_summary::OfflineSummaryWindow;
 use crate::ui::window::Window;
 
 pub struct UiPlugin;
@@ -28,6 +31,7 @@ impl Plugin for UiPlugin {
             .add_systems(EguiPrimaryContextPass, update_ui)
             .add_observer(on_active_exploit_event)
             .add_observer(on_active_exploit_started)
+            .add_observer(on_offline_catch_up_finished)
             .insert_resource(UiState {
                 image_loaders_initialized: false,
                 active_panel: ActivePanel::Home,
@@ -36,6 +40,7 @@ impl Plugin for UiPlugin {
                 scripts_panel_state: ScriptsPanel::new(),
                 exploit_panel_state: ExploitPanel::new(),
                 active_exploit_windows: vec![],
+                offline_summary_window: None,
             });
     }
 }
@@ -79,6 +84,11 @@ fn update_ui(
         window.update(&mut commands, ctx, &mut player_state, &asset_server);
     }
 
+    if let Some(window) = &mut ui_state.offline_summary_window {
+        window.update(&mut commands, ctx, &player_state, &asset_server);
+    }
+    ui_state.offline_summary_window.take_if(|window| !window.open);
+
     // Main panel must be last
     update_main_panel(&mut commands, ctx, &mut ui_state, &mut player_state, &asset_server)?;
 
@@ -234,7 +244,13 @@ fn update_side_panel(
 fn on_active_exploit_event(
     evt: On<ExploitEvent>,
     mut ui_state: ResMut<UiState>,
+    offline_catch_up: Option<Res<OfflineCatchUp>>,
 ) {
+    // Hours of offline events would bury the log; they're summarized instead
+    if offline_catch_up.is_some() {
+        return;
+    }
+
     let Some(window) = ui_state
         .active_exploit_windows
         .iter_mut()
@@ -259,4 +275,15 @@ fn on_active_exploit_started(
     };
 
     window.event_log.clear();
+}
+
+fn on_offline_catch_up_finished(
+    evt: On<OfflineCatchUpFinished>,
+    mut ui_state: ResMut<UiState>,
+) {
+    if evt.summary.elapsed < OfflineSummaryWindow::MIN_ELAPSED {
+        return;
+    }
+
+    ui_state.offline_summary_window = Some(OfflineSummaryWindow::new(evt.summary.clone()));
 }
\ No newline at end of file
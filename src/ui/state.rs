@@ -4,6 +4,7 @@ use crate::ui::panel::market::MarketPanel;
 use crate::ui::panel::script::ScriptsPanel;
 use crate::ui::panel::server::ServersPanel;
 use crate::ui::window::active_exploit::ActiveExploitWindow;
+use crate::ui::window::offline_summary::OfflineSummaryWindow;
 
 pub(crate) enum ActivePanel {
     Home,
@@ -23,4 +24,5 @@ pub(crate) struct UiState {
     pub scripts_panel_state: ScriptsPanel,
     pub exploit_panel_state: ExploitPanel,
     pub active_exploit_windows: Vec<ActiveExploitWindow>,
+    pub offline_summary_window: Option<OfflineSummaryWindow>,
 }
\ No newline at end of file
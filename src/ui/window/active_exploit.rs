@@ -10,9 +10,10 @@ use crate::event::request_restart_exploit::RequestRestartExploitEvent;
 use crate::event::request_stop_exploit::RequestStopExploitEvent;
 use crate::l10n::message_id::MessageId;
 use crate::player_state::unlocks::PlayerUnlock;
-use crate::server::ServerStats;
+use crate::server::{ServerStatInstances, ServerStats};
 use crate::ui::mixed_text::MixedTextNode;
 use crate::ui::window::Window;
+use crate::TICKS_PER_SECOND;
 
 pub struct ActiveExploitWindow {
     pub logs_stick_to_bottom: bool,
@@ -28,6 +29,21 @@ impl ActiveExploitWindow {
             event_log: vec![],
         }
     }
+
+    /// Lists each stat's value, followed by the modifications to it that will wear off.
+    fn stats_ui(ui: &mut egui::Ui, stats: &ServerStatInstances) {
+        for (stat_type, stat_instances) in stats.stat_instances() {
+            let stat_value = stat_instances.iter().map(|stat| stat.value()).sum::<i32>();
+            ui.label(format!("\t{stat_type:?}: {stat_value}"));
+
+            for stat in stat_instances {
+                if let Some(remaining_ticks) = stat.remaining_ticks() {
+                    let remaining_seconds = remaining_ticks as f64 / TICKS_PER_SECOND as f64;
+                    ui.label(format!("\t\t{:+} ({remaining_seconds:.1}s left)", stat.value()));
+                }
+            }
+        }
+    }
 }
 
 impl Window for ActiveExploitWindow {
@@ -51,11 +67,9 @@ impl Window for ActiveExploitWindow {
             // .open(&mut zj_todo_window_open);
         window.show(&ctx, |ui| {
             ui.label(format!("Your Server: {}", lock_and_clone!(active_exploit.hosting_server, name)));
+            Self::stats_ui(ui, &lock_and_clone!(active_exploit.hosting_server, stats));
             ui.label(format!("Target Server: {}", lock_and_clone!(active_exploit.target, server, name)));
-            let stat_values = lock_and_clone!(active_exploit.target, server, stats).stat_values();
-            for (stat_type, stat_value) in stat_values {
-                ui.label(format!("\t{stat_type:?}: {stat_value}"));
-            }
+            Self::stats_ui(ui, &lock_and_clone!(active_exploit.target, server, stats));
 
             ui.label(format!("Allocated CPU: {}", player_state.localize(&active_exploit.clock_allocation)));
 
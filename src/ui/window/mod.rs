@@ -4,6 +4,7 @@ use bevy_egui::egui::Context;
 use crate::PlayerState;
 
 pub mod active_exploit;
+pub mod offline_summary;
 
 /// Any state that can be drawn as a floating window
 pub trait Window {
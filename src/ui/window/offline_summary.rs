@@ -0,0 +1,64 @@
+use std::time::Duration;
+use bevy::prelude::{AssetServer, Commands};
+use bevy_egui::egui;
+use bevy_egui::egui::Context;
+use crate::{loc, PlayerState};
+use crate::l10n::message_id::MessageId;
+use crate::offline::state::OfflineSummary;
+use crate::ui::window::Window;
+
+pub struct OfflineSummaryWindow {
+    pub open: bool,
+    pub summary: OfflineSummary,
+}
+
+impl OfflineSummaryWindow {
+    /// Short absences (eg. restarting the game) aren't worth interrupting the player for.
+    pub const MIN_ELAPSED: Duration = Duration::from_secs(60);
+
+    pub fn new(summary: OfflineSummary) -> Self {
+        OfflineSummaryWindow {
+            open: true,
+            summary,
+        }
+    }
+}
+
+impl Window for OfflineSummaryWindow {
+    fn update(
+        &mut self,
+        _commands: &mut Commands,
+        ctx: &Context,
+        player_state: &PlayerState,
+        _asset_server: &AssetServer,
+    ) {
+        let elapsed_minutes = self.summary.elapsed.as_secs() / 60;
+
+        egui::Window::new(loc!(player_state, MessageId::UiWindowOfflineSummaryTitle))
+            .pivot(egui::Align2::CENTER_CENTER)
+            .default_pos(ctx.content_rect().center())
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(loc!(player_state, MessageId::UiOfflineSummaryElapsed, [
+                    ("hours", (elapsed_minutes / 60).into()),
+                    ("minutes", (elapsed_minutes % 60).into()),
+                ].into()));
+                ui.label(loc!(player_state, MessageId::UiOfflineSummaryCredits, [
+                    ("credits", self.summary.credits_gained.into()),
+                ].into()));
+                ui.label(loc!(player_state, MessageId::UiOfflineSummaryAlgorithms, [
+                    ("algorithm_count", self.summary.algorithms_gained.into()),
+                ].into()));
+                if self.summary.algorithms_lost > 0 {
+                    ui.label(loc!(player_state, MessageId::UiOfflineSummaryAlgorithmsLost, [
+                        ("algorithm_count", self.summary.algorithms_lost.into()),
+                    ].into()));
+                }
+
+                if ui.button(loc!(player_state, MessageId::UiConfirmationDismiss)).clicked() {
+                    self.open = false;
+                }
+            });
+    }
+}